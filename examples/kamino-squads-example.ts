@@ -14,6 +14,7 @@ import { Connection, Keypair, PublicKey, LAMPORTS_PER_SOL } from "@solana/web3.j
 import { KaminoMarket, KaminoAction } from "@kamino-finance/klend-sdk";
 import * as multisig from "@sqds/multisig";
 import { MagicRoulette } from "../target/types/magic_roulette";
+import { calculateBorrowAmount } from "../sdk/kamino-helpers";
 
 // ============================================================================
 // SETUP
@@ -41,7 +42,7 @@ async function createGameWithLoan() {
 
   // Game parameters
   const entryFee = new BN(0.1 * LAMPORTS_PER_SOL); // 0.1 SOL
-  const collateralAmount = new BN(0.11 * LAMPORTS_PER_SOL); // 0.11 SOL (110%)
+  const collateralAmount = new BN(0.12 * LAMPORTS_PER_SOL); // 0.12 SOL (110% of the loan, borrow fee included)
   const gameMode = { oneVsOne: {} };
   const vrfSeed = Keypair.generate().publicKey.toBytes();
 
@@ -87,10 +88,12 @@ async function createGameWithLoan() {
     program.programId
   );
 
-  // 6. Create game with loan
+  // 6. Create game with loan, borrowing enough to cover Kamino's borrow fee
   console.log("🎲 Creating game with loan...");
+  const borrowFeeBps = solReserve.getBorrowFee().mul(10_000).toNumber();
+  const borrowAmount = calculateBorrowAmount(entryFee, borrowFeeBps);
   const tx = await program.methods
-    .createGameWithLoan(gameMode, entryFee, collateralAmount, Array.from(vrfSeed))
+    .createGameWithLoan(gameMode, entryFee, collateralAmount, borrowAmount, Array.from(vrfSeed))
     .accounts({
       game: gamePda,
       platformConfig,
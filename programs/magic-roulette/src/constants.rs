@@ -1,36 +1,79 @@
 use anchor_lang::prelude::*;
 
-// MagicBlock VRF Program ID (Devnet)
-// Update this with actual VRF program ID from MagicBlock when available
-pub const MAGICBLOCK_VRF_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
-    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01
-]);
+// MagicBlock VRF Program ID
+pub const MAGICBLOCK_VRF_PROGRAM_ID: Pubkey = ephemeral_vrf_sdk::consts::VRF_PROGRAM_ID;
 
-// MagicBlock Delegation Program ID
-pub const MAGICBLOCK_DELEGATION_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
-    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02
-]);
+// Identity PDA the VRF program signs randomness callbacks with
+pub const MAGICBLOCK_VRF_PROGRAM_IDENTITY: Pubkey = ephemeral_vrf_sdk::consts::VRF_PROGRAM_IDENTITY;
 
 // Kamino Lend Program ID (mainnet-beta)
 // KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD
 pub const KAMINO_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
-    0x9b, 0x5a, 0x3c, 0x8f, 0x1e, 0x7d, 0x4b, 0x2a,
-    0x6f, 0x9e, 0x8d, 0x7c, 0x5b, 0x4a, 0x3e, 0x2d,
-    0x1c, 0x0b, 0x9a, 0x8f, 0x7e, 0x6d, 0x5c, 0x4b,
-    0x3a, 0x2e, 0x1d, 0x0c, 0x9b, 0x8a, 0x7f, 0x6e
+    0x04, 0xb2, 0xac, 0xb1, 0x12, 0x58, 0xcc, 0xe3,
+    0x68, 0x2c, 0x41, 0x8b, 0xa8, 0x72, 0xff, 0x3d,
+    0xf9, 0x11, 0x02, 0x71, 0x2f, 0x15, 0xaf, 0x12,
+    0xb6, 0xbe, 0x69, 0xb3, 0x43, 0x5b, 0x00, 0x08
 ]);
 
 // Minimum entry fee (0.1 tokens with 9 decimals)
 pub const MIN_ENTRY_FEE: u64 = 100_000_000;
 
+// Minimum entry fee of SOL games (0.01 SOL)
+pub const MIN_SOL_ENTRY_FEE: u64 = 10_000_000;
+
 // Maximum entry fee (1000 tokens with 9 decimals)
 pub const MAX_ENTRY_FEE: u64 = 1_000_000_000_000;
 
+// Maximum combined platform + treasury fee (20%)
+pub const MAX_TOTAL_FEE_BPS: u16 = 2000;
+
 // Game expiry time (24 hours)
 pub const GAME_EXPIRY_SECONDS: i64 = 86400;
+
+// Default time a player has to take their shot (2 minutes)
+pub const DEFAULT_TURN_TIMEOUT_SECONDS: i64 = 120;
+
+// Default share of each treasury fee credited back to the game's players (20%)
+pub const DEFAULT_REWARDS_SHARE_BPS: u16 = 2000;
+
+// Pyth Solana Receiver Program ID, owner of PriceUpdateV2 accounts
+// rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    0x0c, 0xb7, 0xfa, 0xbb, 0x52, 0xf7, 0xa6, 0x48,
+    0xbb, 0x5b, 0x31, 0x7d, 0x9a, 0x01, 0x8b, 0x90,
+    0x57, 0xcb, 0x02, 0x47, 0x74, 0xfa, 0xfe, 0x01,
+    0xe6, 0xc4, 0xdf, 0x98, 0xcc, 0x38, 0x58, 0x81
+]);
+
+// Switchboard On-Demand Program ID, owner of pull feed accounts
+// SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv
+pub const SWITCHBOARD_ON_DEMAND_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    0x06, 0x73, 0xbd, 0x46, 0xf2, 0xe4, 0x7e, 0x04,
+    0xf1, 0x2b, 0xd9, 0x2f, 0xb7, 0x31, 0x96, 0x8e,
+    0xcd, 0x9d, 0x97, 0x57, 0xc2, 0x74, 0xda, 0x87,
+    0x47, 0x6f, 0x46, 0x5c, 0x04, 0x0c, 0x65, 0x73
+]);
+
+// Oldest oracle price a loan may be valued at (60 seconds)
+pub const ORACLE_MAX_AGE_SECONDS: i64 = 60;
+
+// Widest oracle confidence interval accepted, relative to the price (2%)
+pub const ORACLE_MAX_CONFIDENCE_BPS: u64 = 200;
+
+// Default collateral floor for Kamino loans, relative to the loan (110%)
+pub const DEFAULT_MIN_COLLATERAL_RATIO_BPS: u16 = 11_000;
+
+// Squads v4 Multisig Program ID
+// SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf
+pub const SQUADS_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    0x06, 0x81, 0xc4, 0xce, 0x47, 0xe2, 0x23, 0x68,
+    0xb8, 0xb1, 0x55, 0x5e, 0xc8, 0x87, 0xaf, 0x09,
+    0x2e, 0xfc, 0x7e, 0xfb, 0xb6, 0x6c, 0xa3, 0xf5,
+    0x2f, 0xbf, 0x68, 0xd4, 0xac, 0x9c, 0xb7, 0xa8
+]);
+
+// Squads vault holding platform fees
+pub const SQUADS_PLATFORM_VAULT_INDEX: u8 = 0;
+
+// Squads vault holding the treasury
+pub const SQUADS_TREASURY_VAULT_INDEX: u8 = 1;
@@ -72,7 +72,7 @@ pub enum GameError {
     CannotJoinAiGame,
     
     // Kamino integration errors
-    #[msg("Insufficient collateral - minimum 110% of entry fee required")]
+    #[msg("Insufficient collateral - below the platform's minimum collateral ratio")]
     InsufficientCollateral,
     
     #[msg("Loan repayment failed")]
@@ -84,6 +84,9 @@ pub enum GameError {
     #[msg("Invalid Kamino obligation")]
     InvalidKaminoObligation,
     
+    #[msg("Invalid Kamino reserve")]
+    InvalidKaminoReserve,
+    
     #[msg("Collateral withdrawal failed")]
     CollateralWithdrawalFailed,
     
@@ -108,4 +111,63 @@ pub enum GameError {
     
     #[msg("Invalid winner - does not match game participants")]
     InvalidWinner,
+    
+    #[msg("Game has not expired yet")]
+    GameNotExpired,
+    
+    #[msg("Refund account does not match game entrants")]
+    InvalidRefundAccount,
+    
+    #[msg("Turn deadline has not passed")]
+    TurnNotExpired,
+    
+    #[msg("Invalid turn timeout")]
+    InvalidTurnTimeout,
+    
+    #[msg("Player stats account does not match game entrants")]
+    InvalidPlayerStats,
+    
+    #[msg("Treasury rewards account does not match game entrants")]
+    InvalidRewardsAccount,
+    
+    // Token-2022 extension errors
+    #[msg("Mint is non-transferable - winnings could never be paid out")]
+    NonTransferableMint,
+    
+    #[msg("Mint has a permanent delegate that could drain the game vault")]
+    PermanentDelegateMint,
+    
+    #[msg("Mint freezes new token accounts by default")]
+    FrozenByDefaultMint,
+    
+    #[msg("Loan position does not match game borrowers")]
+    InvalidLoanPosition,
+    
+    // Oracle errors
+    #[msg("Oracle account is not the trusted price feed for this asset")]
+    InvalidOracleAccount,
+    
+    #[msg("Oracle price is too old")]
+    StaleOraclePrice,
+    
+    #[msg("Oracle price confidence interval is too wide")]
+    OracleConfidenceTooWide,
+    
+    #[msg("Minimum collateral ratio must be at least 100%")]
+    InvalidCollateralRatio,
+    
+    #[msg("Account is not a Squads multisig or one of its vaults")]
+    InvalidMultisig,
+    
+    #[msg("The treasury of a multisig platform is its Squads treasury vault")]
+    TreasuryManagedByMultisig,
+    
+    #[msg("Invalid treasury address")]
+    InvalidTreasury,
+    
+    #[msg("Invalid Kamino program")]
+    InvalidKaminoProgram,
+    
+    #[msg("Borrowed SOL left after Kamino's fees does not cover the entry fee")]
+    BorrowBelowEntryFee,
 }
@@ -0,0 +1,244 @@
+use anchor_lang::prelude::*;
+use crate::state::GameMode;
+
+// ============================================================================
+// GAME LIFECYCLE EVENTS
+// ============================================================================
+
+#[event]
+pub struct GameCreated {
+    pub game: Pubkey,
+    pub game_id: u64,
+    pub creator: Pubkey,
+    pub game_mode: GameMode,
+    pub entry_fee: u64,
+    pub is_ai_game: bool,
+    pub has_loan: bool,
+}
+
+#[event]
+pub struct PlayerJoined {
+    pub game: Pubkey,
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub team: u8,
+    pub total_pot: u64,
+    pub is_full: bool,
+}
+
+#[event]
+pub struct GameCancelled {
+    pub game: Pubkey,
+    pub game_id: u64,
+    pub caller: Pubkey,
+    pub entrants_refunded: u8,
+    pub total_refunded: u64,
+}
+
+#[event]
+pub struct Delegated {
+    pub game: Pubkey,
+    pub game_id: u64,
+    pub payer: Pubkey,
+}
+
+#[event]
+pub struct GameCommitted {
+    pub game: Pubkey,
+    pub game_id: u64,
+    pub undelegated: bool,
+}
+
+#[event]
+pub struct RandomnessRequested {
+    pub game: Pubkey,
+    pub game_id: u64,
+}
+
+#[event]
+pub struct RandomnessFulfilled {
+    pub game: Pubkey,
+    pub game_id: u64,
+    pub turn_deadline: Option<i64>,
+}
+
+/// A player pulled the trigger; the shot resolves when its randomness arrives
+#[event]
+pub struct TriggerPulled {
+    pub game: Pubkey,
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub shot_number: u8,
+}
+
+#[event]
+pub struct ShotTaken {
+    pub game: Pubkey,
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub shot_number: u8,
+    pub chamber: u8,
+    pub fired: bool,
+    pub randomness: [u8; 32], // VRF output this shot was resolved from
+}
+
+#[event]
+pub struct GameFinished {
+    pub game: Pubkey,
+    pub game_id: u64,
+    pub winner_team: u8,
+    pub timed_out: bool,
+    pub shots_taken: u8,
+}
+
+#[event]
+pub struct GameFinalized {
+    pub game: Pubkey,
+    pub game_id: u64,
+    pub winner_team: u8,
+    pub total_pot: u64,
+    pub platform_fee: u64,
+    pub treasury_fee: u64,
+    pub per_winner: u64,
+    pub rewards_credited: u64,
+}
+
+#[event]
+pub struct GameClosed {
+    pub game: Pubkey,
+    pub game_id: u64,
+    pub creator: Pubkey,
+    pub caller: Pubkey,
+}
+
+#[event]
+pub struct RewardsClaimed {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub total_claimed: u64,
+    pub lamports: u64,
+    pub total_claimed_lamports: u64,
+}
+
+// ============================================================================
+// KAMINO LOAN EVENTS
+// ============================================================================
+
+#[event]
+pub struct LoanOpened {
+    pub game: Pubkey,
+    pub game_id: u64,
+    pub borrower: Pubkey,
+    pub obligation: Pubkey,
+    pub collateral_amount: u64,
+    pub loan_amount: u64,
+}
+
+#[event]
+pub struct LoanSettled {
+    pub game: Pubkey,
+    pub game_id: u64,
+    pub borrower: Pubkey,
+    pub amount_repaid: u64,
+    pub collateral_returned: bool,
+}
+
+/// A borrower lost; their LoanPosition stays open until `settle_loan`
+#[event]
+pub struct LoanOutstanding {
+    pub game: Pubkey,
+    pub game_id: u64,
+    pub borrower: Pubkey,
+    pub obligation: Pubkey,
+    pub debt: u64,
+}
+
+// ============================================================================
+// PLATFORM CONFIG EVENTS - every change records old and new values
+// ============================================================================
+
+#[event]
+pub struct PlatformInitialized {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub platform_mint: Pubkey,
+    pub platform_fee_bps: u16,
+    pub treasury_fee_bps: u16,
+    pub multisig_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct PausedUpdated {
+    pub authority: Pubkey,
+    pub old_paused: bool,
+    pub new_paused: bool,
+}
+
+#[event]
+pub struct FeesUpdated {
+    pub authority: Pubkey,
+    pub old_platform_fee_bps: u16,
+    pub new_platform_fee_bps: u16,
+    pub old_treasury_fee_bps: u16,
+    pub new_treasury_fee_bps: u16,
+}
+
+#[event]
+pub struct TreasuryUpdated {
+    pub authority: Pubkey,
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+}
+
+#[event]
+pub struct TurnTimeoutUpdated {
+    pub authority: Pubkey,
+    pub old_turn_timeout_seconds: i64,
+    pub new_turn_timeout_seconds: i64,
+}
+
+#[event]
+pub struct RewardsShareUpdated {
+    pub authority: Pubkey,
+    pub old_rewards_share_bps: u16,
+    pub new_rewards_share_bps: u16,
+}
+
+#[event]
+pub struct MinCollateralRatioUpdated {
+    pub authority: Pubkey,
+    pub old_min_collateral_ratio_bps: u16,
+    pub new_min_collateral_ratio_bps: u16,
+}
+
+#[event]
+pub struct AssetOracleUpdated {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub old_pyth_feed_id: [u8; 32],
+    pub new_pyth_feed_id: [u8; 32],
+    pub old_switchboard_feed: Pubkey,
+    pub new_switchboard_feed: Pubkey,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub multisig: Pubkey,
+    pub vault: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub remaining_treasury_balance: u64,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub authority: Pubkey,
+    pub old_pending_authority: Option<Pubkey>,
+    pub new_pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityAccepted {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
@@ -0,0 +1,260 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use crate::{errors::GameError, events::*, state::*, constants::MAX_TOTAL_FEE_BPS};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdatePlatformConfig<'info> {
+    #[account(
+        mut,
+        seeds = [PlatformConfig::SEED],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
+    /// Platform authority, or Squads vault 0 of a multisig platform
+    #[account(
+        constraint = authority.key() == platform_config.admin() @ GameError::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetAssetOracle<'info> {
+    #[account(
+        seeds = [PlatformConfig::SEED],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
+    /// Platform authority, or Squads vault 0 of a multisig platform.
+    /// Pays for a new AssetOracle
+    #[account(
+        mut,
+        constraint = authority.key() == platform_config.admin() @ GameError::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+    
+    /// SPL Token or Token-2022 mint being priced
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = AssetOracle::LEN,
+        seeds = [AssetOracle::SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub asset_oracle: Account<'info, AssetOracle>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [PlatformConfig::SEED],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
+    /// Authority proposed by propose_authority
+    #[account(
+        constraint = Some(new_authority.key()) == platform_config.pending_authority
+            @ GameError::Unauthorized
+    )]
+    pub new_authority: Signer<'info>,
+}
+
+pub fn set_turn_timeout(ctx: Context<UpdatePlatformConfig>, turn_timeout_seconds: i64) -> Result<()> {
+    require!(turn_timeout_seconds > 0, GameError::InvalidTurnTimeout);
+    
+    let config = &mut ctx.accounts.platform_config;
+    let old_timeout = config.turn_timeout_seconds;
+    config.turn_timeout_seconds = turn_timeout_seconds;
+    
+    emit_cpi!(TurnTimeoutUpdated {
+        authority: ctx.accounts.authority.key(),
+        old_turn_timeout_seconds: old_timeout,
+        new_turn_timeout_seconds: turn_timeout_seconds,
+    });
+    
+    msg!("Turn timeout updated: {}s -> {}s", old_timeout, turn_timeout_seconds);
+    
+    Ok(())
+}
+
+pub fn set_rewards_share(ctx: Context<UpdatePlatformConfig>, rewards_share_bps: u16) -> Result<()> {
+    require!(rewards_share_bps <= 10000, GameError::InvalidFeeConfig);
+    
+    let config = &mut ctx.accounts.platform_config;
+    let old_share = config.rewards_share_bps;
+    config.rewards_share_bps = rewards_share_bps;
+    
+    emit_cpi!(RewardsShareUpdated {
+        authority: ctx.accounts.authority.key(),
+        old_rewards_share_bps: old_share,
+        new_rewards_share_bps: rewards_share_bps,
+    });
+    
+    msg!("Rewards share updated: {} bps -> {} bps", old_share, rewards_share_bps);
+    
+    Ok(())
+}
+
+pub fn set_min_collateral_ratio(ctx: Context<UpdatePlatformConfig>, min_collateral_ratio_bps: u16) -> Result<()> {
+    // Loans must stay over-collateralised
+    require!(min_collateral_ratio_bps >= 10000, GameError::InvalidCollateralRatio);
+    
+    let config = &mut ctx.accounts.platform_config;
+    let old_ratio = config.min_collateral_ratio_bps;
+    config.min_collateral_ratio_bps = min_collateral_ratio_bps;
+    
+    emit_cpi!(MinCollateralRatioUpdated {
+        authority: ctx.accounts.authority.key(),
+        old_min_collateral_ratio_bps: old_ratio,
+        new_min_collateral_ratio_bps: min_collateral_ratio_bps,
+    });
+    
+    msg!("Minimum collateral ratio updated: {} bps -> {} bps", old_ratio, min_collateral_ratio_bps);
+    
+    Ok(())
+}
+
+pub fn set_asset_oracle(
+    ctx: Context<SetAssetOracle>,
+    pyth_feed_id: [u8; 32],
+    switchboard_feed: Pubkey,
+) -> Result<()> {
+    let mint = ctx.accounts.mint.key();
+    let asset_oracle = &mut ctx.accounts.asset_oracle;
+    let old_pyth_feed_id = asset_oracle.pyth_feed_id;
+    let old_switchboard_feed = asset_oracle.switchboard_feed;
+    
+    asset_oracle.mint = mint;
+    asset_oracle.pyth_feed_id = pyth_feed_id;
+    asset_oracle.switchboard_feed = switchboard_feed;
+    asset_oracle.bump = ctx.bumps.asset_oracle;
+    
+    emit_cpi!(AssetOracleUpdated {
+        authority: ctx.accounts.authority.key(),
+        mint,
+        old_pyth_feed_id,
+        new_pyth_feed_id: pyth_feed_id,
+        old_switchboard_feed,
+        new_switchboard_feed: switchboard_feed,
+    });
+    
+    msg!("Price feeds for {} updated", mint);
+    
+    Ok(())
+}
+
+pub fn set_paused(ctx: Context<UpdatePlatformConfig>, paused: bool) -> Result<()> {
+    let config = &mut ctx.accounts.platform_config;
+    let old_paused = config.paused;
+    config.paused = paused;
+    
+    emit_cpi!(PausedUpdated {
+        authority: ctx.accounts.authority.key(),
+        old_paused,
+        new_paused: paused,
+    });
+    
+    if paused {
+        msg!("⏸️ Platform paused");
+    } else {
+        msg!("▶️ Platform unpaused");
+    }
+    
+    Ok(())
+}
+
+pub fn update_fees(
+    ctx: Context<UpdatePlatformConfig>,
+    platform_fee_bps: u16,
+    treasury_fee_bps: u16,
+) -> Result<()> {
+    // Same cap as initialize_platform_with_multisig
+    let total_fees = platform_fee_bps
+        .checked_add(treasury_fee_bps)
+        .ok_or(GameError::ArithmeticOverflow)?;
+    
+    require!(total_fees <= MAX_TOTAL_FEE_BPS, GameError::InvalidFeeConfig);
+    
+    let config = &mut ctx.accounts.platform_config;
+    let old_platform_fee_bps = config.platform_fee_bps;
+    let old_treasury_fee_bps = config.treasury_fee_bps;
+    config.platform_fee_bps = platform_fee_bps;
+    config.treasury_fee_bps = treasury_fee_bps;
+    
+    emit_cpi!(FeesUpdated {
+        authority: ctx.accounts.authority.key(),
+        old_platform_fee_bps,
+        new_platform_fee_bps: platform_fee_bps,
+        old_treasury_fee_bps,
+        new_treasury_fee_bps: treasury_fee_bps,
+    });
+    
+    msg!("Fees updated: platform {} -> {} bps, treasury {} -> {} bps",
+        old_platform_fee_bps, platform_fee_bps,
+        old_treasury_fee_bps, treasury_fee_bps
+    );
+    
+    Ok(())
+}
+
+pub fn update_treasury(ctx: Context<UpdatePlatformConfig>, new_treasury: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.platform_config;
+    config.require_treasury_update(&new_treasury)?;
+    
+    let old_treasury = config.treasury;
+    config.treasury = new_treasury;
+    
+    emit_cpi!(TreasuryUpdated {
+        authority: ctx.accounts.authority.key(),
+        old_treasury,
+        new_treasury,
+    });
+    
+    msg!("Treasury updated: {} -> {}", old_treasury, new_treasury);
+    
+    Ok(())
+}
+
+pub fn propose_authority(ctx: Context<UpdatePlatformConfig>, new_authority: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.platform_config;
+    let old_pending = config.pending_authority;
+    config.pending_authority = Some(new_authority);
+    
+    emit_cpi!(AuthorityProposed {
+        authority: ctx.accounts.authority.key(),
+        old_pending_authority: old_pending,
+        new_pending_authority: new_authority,
+    });
+    
+    msg!("Authority transfer proposed: {} -> {}", config.authority, new_authority);
+    
+    Ok(())
+}
+
+pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let config = &mut ctx.accounts.platform_config;
+    let old_authority = config.authority;
+    let new_authority = ctx.accounts.new_authority.key();
+    
+    // SECURITY: A previous multisig keeps no admin rights
+    config.transfer_authority(new_authority);
+    
+    emit_cpi!(AuthorityAccepted {
+        old_authority,
+        new_authority,
+    });
+    
+    msg!("Authority transferred: {} -> {}", old_authority, new_authority);
+    
+    Ok(())
+}
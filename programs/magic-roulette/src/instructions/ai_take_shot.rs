@@ -1,66 +1,66 @@
 use anchor_lang::prelude::*;
-use crate::{errors::GameError, state::*};
+use anchor_lang::Discriminator;
+use ephemeral_vrf_sdk::anchor::vrf;
+use ephemeral_vrf_sdk::consts::DEFAULT_EPHEMERAL_QUEUE;
+use crate::{errors::GameError, events::*, state::*};
+use super::vrf_instructions::game_randomness_request;
 
+#[vrf]
+#[event_cpi]
 #[derive(Accounts)]
 pub struct AiTakeShot<'info> {
     #[account(
         mut,
-        seeds = [b"game", game.game_id.to_le_bytes().as_ref()],
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
         bump = game.bump,
         constraint = game.is_ai_game @ GameError::InvalidGameMode,
         constraint = game.ai_player == Some(ai_bot.key()) @ GameError::Unauthorized
     )]
     pub game: Account<'info, Game>,
     
-    /// AI bot signer - must match game.ai_player
+    /// AI bot signer - must match game.ai_player; pays for the shot's VRF request
+    #[account(mut)]
     pub ai_bot: Signer<'info>,
+    
+    /// CHECK: MagicBlock VRF oracle queue (ephemeral rollup queue)
+    #[account(mut, address = DEFAULT_EPHEMERAL_QUEUE)]
+    pub oracle_queue: AccountInfo<'info>,
 }
 
 pub fn ai_take_shot(ctx: Context<AiTakeShot>) -> Result<()> {
     let game = &mut ctx.accounts.game;
-    
-    require!(
-        game.status == GameStatus::InProgress,
-        GameError::GameNotInProgress
-    );
-    
-    require!(
-        game.vrf_fulfilled,
-        GameError::VrfNotReady
-    );
+    let ai_bot = ctx.accounts.ai_bot.key();
     
     // Verify it's AI's turn
-    let current_player = game.get_current_player();
+    let current_player = game.get_current_player()?;
     require!(
-        current_player == ctx.accounts.ai_bot.key(),
+        current_player == ai_bot,
         GameError::NotYourTurn
     );
     
-    // AI takes shot (decision made off-chain by AI service)
-    game.shots_taken += 1;
+    // AI pulls the trigger (decision made off-chain by AI service); the shot
+    // is resolved by resolve_shot_callback like any other
+    let caller_seed = game.pull_trigger(Clock::get()?.unix_timestamp)?;
+    let ix = game_randomness_request(
+        &ai_bot,
+        &ctx.accounts.oracle_queue.key(),
+        &game.key(),
+        &ctx.accounts.event_authority.key(),
+        crate::instruction::ResolveShotCallback::DISCRIMINATOR,
+        caller_seed,
+    );
+    
+    ctx.accounts
+        .invoke_signed_vrf(&ctx.accounts.ai_bot.to_account_info(), &ix)?;
     
-    msg!("🤖 AI Bot takes shot #{}", game.shots_taken);
+    emit_cpi!(TriggerPulled {
+        game: ctx.accounts.game.key(),
+        game_id: ctx.accounts.game.game_id,
+        player: ai_bot,
+        shot_number: ctx.accounts.game.shots_taken + 1,
+    });
     
-    // Check if bullet fired
-    if game.current_chamber == game.bullet_chamber {
-        // AI hit the bullet - Human wins!
-        game.winner_team = Some(0);  // Team A (human) wins
-        game.status = GameStatus::Finished;
-        game.finished_at = Some(Clock::get()?.unix_timestamp);
-        
-        msg!("💥 AI Bot hit the bullet!");
-        msg!("🎉 Human player wins! (Practice mode - no prizes)");
-    } else {
-        // Safe - advance to next turn
-        game.current_chamber += 1;
-        if game.current_chamber > 6 {
-            game.current_chamber = 1;
-        }
-        
-        game.current_turn += 1;
-        
-        msg!("✓ Click. AI Bot survived");
-    }
+    msg!("🤖 AI Bot pulls the trigger for shot #{}", ctx.accounts.game.shots_taken + 1);
     
     Ok(())
 }
@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_2022::spl_token_2022::extension::StateWithExtensions;
+use anchor_spl::token_2022::spl_token_2022::state::Account as TokenAccountState;
+use anchor_spl::token_2022::spl_token_2022::ID as TOKEN_2022_PROGRAM_ID;
+use crate::{errors::GameError, events::*, state::*, token::GameTreasury};
+
+// Helper function to check a refund token account belongs to the entrant
+fn validate_refund_account(token_account: &AccountInfo, owner: &Pubkey, mint: &Pubkey) -> Result<()> {
+    require!(
+        token_account.owner == &TOKEN_2022_PROGRAM_ID,
+        GameError::InvalidRefundAccount
+    );
+
+    let data = token_account.try_borrow_data()?;
+    let account = StateWithExtensions::<TokenAccountState>::unpack(&data)?.base;
+    require!(
+        account.owner == *owner && account.mint == *mint,
+        GameError::InvalidRefundAccount
+    );
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelGame<'info> {
+    #[account(
+        mut,
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
+        bump = game.bump,
+        close = creator
+    )]
+    pub game: Box<Account<'info, Game>>,
+
+    /// Creator or, after expiry, anyone
+    pub caller: Signer<'info>,
+
+    /// Game creator (receives reclaimed rent)
+    /// CHECK: Validated against game.creator
+    #[account(
+        mut,
+        constraint = creator.key() == game.creator @ GameError::Unauthorized
+    )]
+    pub creator: AccountInfo<'info>,
+
+    // Token-2022 accounts
+    /// CHECK: Token-2022 mint, must be the mint the game was created with.
+    /// Writable so withheld transfer fees can be harvested before the vault closes
+    #[account(mut, constraint = game.mint == Some(mint.key()) @ GameError::InvalidMint)]
+    pub mint: AccountInfo<'info>,
+
+    #[account(mut)]
+    /// CHECK: Creator's token account (validated in instruction logic)
+    pub creator_token_account: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        address = Game::find_vault_address(&game.key()).0 @ GameError::InvalidVaultOwner
+    )]
+    /// CHECK: Game vault PDA created with the game
+    pub game_vault: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token2022>,
+    // remaining_accounts: token accounts of every entrant after the creator, in seat order,
+    // followed by any TransferHook accounts the mint needs
+}
+
+pub fn cancel_game<'info>(ctx: Context<'_, '_, 'info, 'info, CancelGame<'info>>) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(!game.is_ai_game, GameError::InvalidGameMode);
+
+    // SECURITY: Only games that never started can be cancelled. The creator
+    // may cancel until the game fills, anyone once it expires
+    // EFFECTS: Record the cancellation before interactions
+    game.cancel(&ctx.accounts.caller.key(), now)?;
+
+    // SECURITY: Refund accounts must belong to the entrants, in seat order
+    let mint = ctx.accounts.mint.key();
+    let entrants = game.entrants();
+    require!(
+        ctx.remaining_accounts.len() >= entrants.len() - 1,
+        GameError::InvalidRefundAccount
+    );
+    let (refund_accounts, hook_accounts) = ctx.remaining_accounts.split_at(entrants.len() - 1);
+    validate_refund_account(&ctx.accounts.creator_token_account, &game.creator, &mint)?;
+    for (account, entrant) in refund_accounts.iter().zip(entrants.iter().skip(1)) {
+        validate_refund_account(account, entrant, &mint)?;
+    }
+
+    let total_pot = game.total_pot;
+    let refunds = game.refunds();
+
+    // Game PDA signer seeds
+    let game_id_bytes = game.game_id.to_le_bytes();
+    let bump_seed = [game.bump];
+    let seeds: &[&[u8]] = &[
+        Game::SEED,
+        game_id_bytes.as_ref(),
+        &bump_seed,
+    ];
+    let signer = &[seeds];
+
+    // INTERACTIONS: Refund every entrant, then close the emptied vault
+    // and return its rent to the creator
+    let treasury = GameTreasury::token(
+        ctx.accounts.game_vault.to_account_info(),
+        game.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        hook_accounts,
+        signer,
+    )?;
+    treasury.payout(&ctx.accounts.creator_token_account, refunds[0].1)?;
+    for (player_token_account, (_, refund)) in refund_accounts.iter().zip(refunds.iter().skip(1)) {
+        treasury.payout(player_token_account, *refund)?;
+    }
+    treasury.close(&ctx.accounts.creator)?;
+
+    emit_cpi!(GameCancelled {
+        game: game.key(),
+        game_id: game.game_id,
+        caller: ctx.accounts.caller.key(),
+        entrants_refunded: entrants.len() as u8,
+        total_refunded: total_pot,
+    });
+    
+    msg!("Game {} cancelled", game.game_id);
+    msg!("Refunded {} entrant(s) {} in total", entrants.len(), total_pot);
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use crate::{errors::GameError, events::*, state::*, token::GameTreasury};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelGameSol<'info> {
+    #[account(
+        mut,
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
+        bump = game.bump,
+        close = creator
+    )]
+    pub game: Account<'info, Game>,
+
+    /// Creator or, after expiry, anyone
+    pub caller: Signer<'info>,
+
+    /// Game creator (receives refund and reclaimed rent)
+    /// CHECK: Validated against game.creator
+    #[account(
+        mut,
+        constraint = creator.key() == game.creator @ GameError::Unauthorized
+    )]
+    pub creator: AccountInfo<'info>,
+
+    /// Game vault PDA - holds SOL for this game
+    /// CHECK: PDA for holding SOL
+    #[account(
+        mut,
+        seeds = [Game::VAULT_SEED, game.key().as_ref()],
+        bump
+    )]
+    pub game_vault: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: wallets of every entrant after the creator, in seat order
+}
+
+pub fn cancel_game_sol<'info>(ctx: Context<'_, '_, 'info, 'info, CancelGameSol<'info>>) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(!game.is_ai_game, GameError::InvalidGameMode);
+    require!(!game.has_loan(), GameError::InvalidGameMode);
+
+    // SECURITY: Only games that never started can be cancelled. The creator
+    // may cancel until the game fills, anyone once it expires
+    // EFFECTS: Record the cancellation before interactions
+    game.cancel(&ctx.accounts.caller.key(), now)?;
+
+    let entrants = game.entrants();
+    require!(
+        ctx.remaining_accounts.len() == entrants.len() - 1,
+        GameError::InvalidRefundAccount
+    );
+    for (account, entrant) in ctx.remaining_accounts.iter().zip(entrants.iter().skip(1)) {
+        require!(account.key() == *entrant, GameError::InvalidRefundAccount);
+    }
+
+    // SECURITY: Verify vault holds the whole pot
+    let total_pot = game.total_pot;
+    require!(
+        ctx.accounts.game_vault.lamports() >= total_pot,
+        GameError::InsufficientVaultBalance
+    );
+
+    let refunds = game.refunds();
+
+    let game_key = game.key();
+    let seeds = &[
+        Game::VAULT_SEED,
+        game_key.as_ref(),
+        &[ctx.bumps.game_vault],
+    ];
+    let signer = &[&seeds[..]];
+
+    // INTERACTIONS: Refund joiners first, then sweep the creator's share and rent
+    let treasury = GameTreasury::sol(
+        ctx.accounts.game_vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        signer,
+    );
+    for (player, (_, refund)) in ctx.remaining_accounts.iter().zip(refunds.iter().skip(1)) {
+        treasury.payout(player, *refund)?;
+    }
+    treasury.close(&ctx.accounts.creator)?;
+
+    emit_cpi!(GameCancelled {
+        game: game.key(),
+        game_id: game.game_id,
+        caller: ctx.accounts.caller.key(),
+        entrants_refunded: entrants.len() as u8,
+        total_refunded: total_pot,
+    });
+    
+    msg!("🚫 Game {} cancelled", game.game_id);
+    msg!("   Refunded {} entrant(s) {} SOL in total", entrants.len(), total_pot as f64 / 1_000_000_000.0);
+
+    Ok(())
+}
@@ -0,0 +1,188 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::spl_token::native_mint;
+use crate::{errors::GameError, events::*, state::*, constants::KAMINO_PROGRAM_ID, token::{GameTreasury, VaultWsol}};
+use super::finalize_game_with_loan::{BorrowerLoan, VaultRepayAccounts};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelGameWithLoan<'info> {
+    #[account(
+        mut,
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
+        bump = game.bump,
+        close = creator,
+        constraint = game.has_loan() @ GameError::InvalidKaminoObligation
+    )]
+    pub game: Box<Account<'info, Game>>,
+
+    /// Creator or, after expiry, anyone
+    pub caller: Signer<'info>,
+
+    /// Game creator (receives their refund and the reclaimed rent)
+    /// CHECK: Validated against game.creator
+    #[account(
+        mut,
+        constraint = creator.key() == game.creator @ GameError::Unauthorized
+    )]
+    pub creator: AccountInfo<'info>,
+
+    /// Game vault PDA - holds SOL for this game
+    /// CHECK: PDA for holding SOL
+    #[account(
+        mut,
+        seeds = [Game::VAULT_SEED, game.key().as_ref()],
+        bump
+    )]
+    pub game_vault: AccountInfo<'info>,
+
+    /// Game vault's wrapped SOL account, open only while loans are repaid
+    /// CHECK: Address derived from the game vault
+    #[account(
+        mut,
+        address = VaultWsol::new(&game_vault.key()).account @ GameError::InvalidVaultOwner
+    )]
+    pub vault_wsol: AccountInfo<'info>,
+
+    /// CHECK: Kamino program - validated against known program ID
+    #[account(
+        constraint = kamino_program.key() == KAMINO_PROGRAM_ID @ GameError::InvalidKaminoProgram
+    )]
+    pub kamino_program: AccountInfo<'info>,
+
+    /// CHECK: Liquidity mint of the SOL reserve, wrapped SOL
+    #[account(address = native_mint::ID @ GameError::InvalidMint)]
+    pub reserve_liquidity_mint: AccountInfo<'info>,
+
+    /// CHECK: Instructions sysvar, where Kamino checks for its refresh instructions
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+
+    pub token_program: Program<'info, anchor_spl::token::Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: wallets of every entrant after the creator, in seat
+    // order, then the BorrowerLoan accounts of every borrower, in seat order
+}
+
+pub fn cancel_game_with_loan<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CancelGameWithLoan<'info>>,
+) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+    let now = Clock::get()?.unix_timestamp;
+
+    // SECURITY: Only games that never started can be cancelled. The creator
+    // may cancel until the game fills, anyone once it expires
+    // EFFECTS: Record the cancellation before interactions
+    game.cancel(&ctx.accounts.caller.key(), now)?;
+
+    let entrants = game.entrants();
+    require!(
+        ctx.remaining_accounts.len() >= entrants.len() - 1,
+        GameError::InvalidRefundAccount
+    );
+    let (refund_accounts, loan_accounts) = ctx.remaining_accounts.split_at(entrants.len() - 1);
+    for (account, entrant) in refund_accounts.iter().zip(entrants.iter().skip(1)) {
+        require!(account.key() == *entrant, GameError::InvalidRefundAccount);
+    }
+    let game_key = game.key();
+    let mut loans = BorrowerLoan::load_all(&game_key, &game.borrowers(), loan_accounts)?;
+
+    // SECURITY: Verify vault holds the whole pot
+    let total_pot = game.total_pot;
+    require!(
+        ctx.accounts.game_vault.lamports() >= total_pot,
+        GameError::InsufficientVaultBalance
+    );
+
+    // A borrower's refund goes back to Kamino first and only the rest to
+    // them; any shortfall stays on their LoanPosition for settle_loan
+    let refunds = game.refunds();
+    let refund_of = |player: &Pubkey| {
+        refunds
+            .iter()
+            .find(|(entrant, _)| entrant == player)
+            .map_or(0, |(_, refund)| *refund)
+    };
+    for loan in loans.iter_mut() {
+        loan.cover(refund_of(&loan.borrower));
+    }
+
+    // EFFECTS: Record what each loan got back before interactions
+    for loan in loans.iter_mut() {
+        loan.record()?;
+    }
+
+    let seeds = &[
+        Game::VAULT_SEED,
+        game_key.as_ref(),
+        &[ctx.bumps.game_vault],
+    ];
+    let signer = &[&seeds[..]];
+
+    // INTERACTIONS: Repay loans, then refund joiners; the creator's refund
+    // is swept back to them with the vault
+    let treasury = GameTreasury::sol(
+        ctx.accounts.game_vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        signer,
+    );
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let associated_token_program = ctx.accounts.associated_token_program.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let repay_accounts = VaultRepayAccounts {
+        game_vault: &ctx.accounts.game_vault,
+        vault_wsol: &ctx.accounts.vault_wsol,
+        reserve_liquidity_mint: &ctx.accounts.reserve_liquidity_mint,
+        kamino_program: &ctx.accounts.kamino_program,
+        token_program: &token_program,
+        associated_token_program: &associated_token_program,
+        system_program: &system_program,
+        instruction_sysvar: &ctx.accounts.instruction_sysvar,
+    };
+    for loan in &loans {
+        msg!("💰 Repaying {} SOL of {}'s Kamino loan", loan.repaid as f64 / 1e9, loan.borrower);
+        loan.repay_from_vault(&repay_accounts, signer)?;
+
+        emit_cpi!(LoanSettled {
+            game: game_key,
+            game_id: game.game_id,
+            borrower: loan.borrower,
+            amount_repaid: loan.repaid,
+            collateral_returned: false,
+        });
+        if loan.outstanding() > 0 {
+            emit_cpi!(LoanOutstanding {
+                game: game_key,
+                game_id: game.game_id,
+                borrower: loan.borrower,
+                obligation: loan.obligation.key(),
+                debt: loan.outstanding(),
+            });
+        }
+    }
+    for (player, (entrant, refund)) in refund_accounts.iter().zip(refunds.iter().skip(1)) {
+        let repaid = loans
+            .iter()
+            .find(|loan| loan.borrower == *entrant)
+            .map_or(0, |loan| loan.repaid);
+        treasury.payout(player, refund - repaid)?;
+    }
+
+    // Sweep whatever is left (the creator's refund and rent) back to the creator
+    treasury.close(&ctx.accounts.creator)?;
+
+    emit_cpi!(GameCancelled {
+        game: game.key(),
+        game_id: game.game_id,
+        caller: ctx.accounts.caller.key(),
+        entrants_refunded: entrants.len() as u8,
+        total_refunded: total_pot,
+    });
+    
+    msg!("🚫 Game {} cancelled", game.game_id);
+    msg!("   Refunded {} entrant(s) {} SOL in total", entrants.len(), total_pot as f64 / 1e9);
+
+    Ok(())
+}
@@ -1,35 +1,22 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::program_pack::Pack;
-use anchor_spl::token_2022::{Token2022, transfer_checked, TransferChecked};
-use anchor_spl::token_2022::spl_token_2022::state::Mint as MintState;
-use anchor_spl::token_2022::spl_token_2022::ID as TOKEN_2022_PROGRAM_ID;
-use crate::{errors::GameError, state::*};
-
-// Helper function to get mint decimals with ownership validation
-fn get_mint_decimals(mint_account: &AccountInfo) -> Result<u8> {
-    // SECURITY: Validate mint is owned by Token-2022 program
-    require!(
-        mint_account.owner == &TOKEN_2022_PROGRAM_ID,
-        GameError::InvalidMint
-    );
-    
-    let mint_data = mint_account.try_borrow_data()?;
-    let mint = MintState::unpack(&mint_data)?;
-    Ok(mint.decimals)
-}
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::TokenAccount;
+use crate::{errors::GameError, events::*, state::*, token};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ClaimRewards<'info> {
     #[account(
         mut,
-        seeds = [b"rewards", player.key().as_ref()],
+        seeds = [TreasuryRewards::SEED, player.key().as_ref()],
         bump = rewards.bump
     )]
     pub rewards: Account<'info, TreasuryRewards>,
     
+    /// Platform config, holds the SOL rewards and owns the token rewards vault
     #[account(
         mut,
-        seeds = [b"platform"],
+        seeds = [PlatformConfig::SEED],
         bump = platform_config.bump
     )]
     pub platform_config: Account<'info, PlatformConfig>,
@@ -37,64 +24,80 @@ pub struct ClaimRewards<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
     
-    // Token accounts
-    /// CHECK: Token-2022 mint
-    pub mint: AccountInfo<'info>,
+    // Token accounts, required only to claim platform-mint rewards
+    /// CHECK: Platform Token-2022 mint - token rewards only accrue in this mint
+    #[account(
+        constraint = mint.key() == platform_config.platform_mint @ GameError::InvalidMint
+    )]
+    pub mint: Option<AccountInfo<'info>>,
     
-    #[account(mut)]
-    /// CHECK: Treasury vault token account
-    pub treasury_vault: AccountInfo<'info>,
+    /// Player rewards pool, owned by the platform config PDA
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = platform_config,
+        associated_token::token_program = token_program
+    )]
+    pub rewards_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     
     #[account(mut)]
     /// CHECK: Player's token account
-    pub player_token_account: AccountInfo<'info>,
+    pub player_token_account: Option<AccountInfo<'info>>,
     
-    pub token_program: Program<'info, Token2022>,
+    pub token_program: Option<Program<'info, Token2022>>,
+    // remaining_accounts: TransferHook accounts, if the mint has a hook
 }
 
-pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
-    let rewards = &mut ctx.accounts.rewards;
-    
-    require!(
-        rewards.claimable_amount > 0,
-        GameError::NoRewardsToClaim
-    );
-    
-    let amount = rewards.claimable_amount;
+pub fn claim_rewards<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimRewards<'info>>) -> Result<()> {
+    // EFFECTS: Zero the claimable balances before paying anything out
+    let (amount, lamports) = ctx.accounts.rewards.take_claimable(Clock::get()?.unix_timestamp)?;
     
-    // Treasury PDA signer
-    let platform_config = &ctx.accounts.platform_config;
-    let seeds = &[
-        b"platform".as_ref(),
-        &[platform_config.bump],
-    ];
-    let signer = &[&seeds[..]];
+    // INTERACTIONS: SOL rewards are held by the platform config PDA itself
+    if lamports > 0 {
+        ctx.accounts.platform_config.sub_lamports(lamports)?;
+        ctx.accounts.player.add_lamports(lamports)?;
+    }
     
-    // Transfer rewards
-    transfer_checked(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            TransferChecked {
-                from: ctx.accounts.treasury_vault.to_account_info(),
-                to: ctx.accounts.player_token_account.to_account_info(),
-                authority: ctx.accounts.treasury_vault.to_account_info(),
-                mint: ctx.accounts.mint.to_account_info(),
-            },
+    // INTERACTIONS: Token rewards come out of the vault owned by the platform config PDA
+    if amount > 0 {
+        let (Some(mint), Some(rewards_vault), Some(player_token_account), Some(token_program)) = (
+            &ctx.accounts.mint,
+            &ctx.accounts.rewards_vault,
+            &ctx.accounts.player_token_account,
+            &ctx.accounts.token_program,
+        ) else {
+            return err!(GameError::InvalidMint);
+        };
+        
+        let seeds = &[
+            PlatformConfig::SEED,
+            &[ctx.accounts.platform_config.bump],
+        ];
+        let signer = &[&seeds[..]];
+        
+        token::transfer_checked(
+            &token_program.to_account_info(),
+            &rewards_vault.to_account_info(),
+            mint,
+            player_token_account,
+            &ctx.accounts.platform_config.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            token::mint_decimals(mint)?,
             signer,
-        ),
-        amount,
-        get_mint_decimals(&ctx.accounts.mint)?,
-    )?;
-    
-    // Update rewards account
-    rewards.total_claimed = rewards.total_claimed
-        .checked_add(amount)
-        .ok_or(GameError::ArithmeticOverflow)?;
+        )?;
+    }
     
-    rewards.claimable_amount = 0;
-    rewards.last_claim = Clock::get()?.unix_timestamp;
+    let rewards = &ctx.accounts.rewards;
+    emit_cpi!(RewardsClaimed {
+        player: ctx.accounts.player.key(),
+        amount,
+        total_claimed: rewards.total_claimed,
+        lamports,
+        total_claimed_lamports: rewards.total_claimed_lamports,
+    });
     
-    msg!("Player {} claimed {} rewards", ctx.accounts.player.key(), amount);
+    msg!("Player {} claimed {} rewards and {} SOL", ctx.accounts.player.key(), amount, lamports as f64 / 1e9);
     
     Ok(())
 }
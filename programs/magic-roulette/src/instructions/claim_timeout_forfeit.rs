@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use crate::{errors::GameError, events::*, state::*};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimTimeoutForfeit<'info> {
+    #[account(
+        mut,
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
+        bump = game.bump
+    )]
+    pub game: Account<'info, Game>,
+}
+
+pub fn claim_timeout_forfeit(ctx: Context<ClaimTimeoutForfeit>) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+    let now = Clock::get()?.unix_timestamp;
+    
+    // SECURITY: Only once the current player has let their clock run out -
+    // a shooter waiting on their shot's randomness has already acted, and a
+    // stalled request is retried with retry_shot instead
+    require!(!game.vrf_pending, GameError::VrfRequestPending);
+    let deadline = game.turn_deadline.ok_or(GameError::GameNotInProgress)?;
+    require!(now > deadline, GameError::TurnNotExpired);
+    
+    // Idle player forfeits - opposing team wins
+    let idle_player = game.get_current_player()?;
+    let losing_team = game.current_team()?;
+    game.winner_team = Some(1 - losing_team);
+    game.transition(GameStatus::Finished)?;
+    game.finished_at = Some(now);
+    game.turn_deadline = None;
+    
+    emit_cpi!(GameFinished {
+        game: game.key(),
+        game_id: game.game_id,
+        winner_team: 1 - losing_team,
+        timed_out: true,
+        shots_taken: game.shots_taken,
+    });
+    
+    msg!("⏰ Player {} timed out in game {}", idle_player, game.game_id);
+    msg!("Team {} wins by forfeit!", 1 - losing_team);
+    
+    Ok(())
+}
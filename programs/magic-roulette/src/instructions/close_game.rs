@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::TokenAccount;
+use crate::{errors::GameError, events::*, state::*, token::GameTreasury};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseGame<'info> {
+    #[account(
+        mut,
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
+        bump = game.bump,
+        close = creator
+    )]
+    pub game: Box<Account<'info, Game>>,
+
+    /// Anyone may close a settled game; the rent always goes to the creator
+    pub caller: Signer<'info>,
+
+    /// Game creator (receives the vault and game account rent)
+    /// CHECK: Validated against game.creator
+    #[account(
+        mut,
+        constraint = creator.key() == game.creator @ GameError::Unauthorized
+    )]
+    pub creator: AccountInfo<'info>,
+
+    /// Game vault PDA - holds SOL, or is a token account owned by the game
+    /// CHECK: PDA validated by seeds
+    #[account(
+        mut,
+        seeds = [Game::VAULT_SEED, game.key().as_ref()],
+        bump
+    )]
+    pub game_vault: AccountInfo<'info>,
+
+    // Token-2022 accounts, required for token games only
+    /// CHECK: Token-2022 mint, must be the mint the game was created with.
+    /// Writable so withheld transfer fees can be harvested before the vault closes
+    #[account(mut, constraint = game.mint == Some(mint.key()) @ GameError::InvalidMint)]
+    pub mint: Option<AccountInfo<'info>>,
+
+    /// Creator's token account, receives anything still left in the vault
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == game.creator @ GameError::Unauthorized
+    )]
+    pub creator_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Option<Program<'info, Token2022>>,
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: any TransferHook accounts the mint needs
+}
+
+pub fn close_game<'info>(ctx: Context<'_, '_, 'info, 'info, CloseGame<'info>>) -> Result<()> {
+    let game = &ctx.accounts.game;
+
+    // SECURITY: Only settled games can be closed
+    require!(
+        game.status == GameStatus::Settled,
+        GameError::InvalidGameStatus
+    );
+
+    let game_key = game.key();
+    let vault_bump = [ctx.bumps.game_vault];
+    let vault_seeds: &[&[u8]] = &[Game::VAULT_SEED, game_key.as_ref(), &vault_bump];
+    let game_id_bytes = game.game_id.to_le_bytes();
+    let game_bump = [game.bump];
+    let game_seeds: &[&[u8]] = &[Game::SEED, game_id_bytes.as_ref(), &game_bump];
+
+    // INTERACTIONS: Sweep whatever is left in the vault to the creator, then
+    // close it; the game account is closed to the creator on exit
+    if game.mint.is_some() {
+        let (Some(mint), Some(creator_token_account), Some(token_program)) = (
+            &ctx.accounts.mint,
+            &ctx.accounts.creator_token_account,
+            &ctx.accounts.token_program,
+        ) else {
+            return err!(GameError::InvalidMint);
+        };
+
+        let signer = &[game_seeds];
+        let treasury = GameTreasury::token(
+            ctx.accounts.game_vault.to_account_info(),
+            game.to_account_info(),
+            mint.to_account_info(),
+            token_program.to_account_info(),
+            ctx.remaining_accounts,
+            signer,
+        )?;
+        treasury.payout(&creator_token_account.to_account_info(), treasury.balance()?)?;
+        treasury.close(&ctx.accounts.creator)?;
+    } else {
+        let signer = &[vault_seeds];
+        let treasury = GameTreasury::sol(
+            ctx.accounts.game_vault.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            signer,
+        );
+        treasury.close(&ctx.accounts.creator)?;
+    }
+
+    emit_cpi!(GameClosed {
+        game: game_key,
+        game_id: game.game_id,
+        creator: game.creator,
+        caller: ctx.accounts.caller.key(),
+    });
+
+    msg!("🧹 Game {} closed, rent returned to {}", game.game_id, game.creator);
+
+    Ok(())
+}
@@ -1,20 +1,21 @@
 use anchor_lang::prelude::*;
-use crate::{errors::GameError, state::*};
+use crate::{errors::GameError, events::*, state::*};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CreateAiGame<'info> {
     #[account(
         init,
         payer = player,
         space = Game::LEN,
-        seeds = [b"game", platform_config.total_games.to_le_bytes().as_ref()],
+        seeds = [Game::SEED, platform_config.total_games.to_le_bytes().as_ref()],
         bump
     )]
     pub game: Account<'info, Game>,
     
     #[account(
         mut,
-        seeds = [b"platform"],
+        seeds = [PlatformConfig::SEED],
         bump = platform_config.bump,
         constraint = !platform_config.paused @ GameError::PlatformPaused
     )]
@@ -44,6 +45,7 @@ pub fn create_ai_game(
     game.status = GameStatus::WaitingForPlayers;
     game.entry_fee = 0;  // FREE - No entry fee for practice mode
     game.total_pot = 0;  // No prize pool
+    game.mint = None;
     
     // AI settings
     game.is_ai_game = true;
@@ -62,6 +64,11 @@ pub fn create_ai_game(
     game.current_chamber = 1;
     game.current_turn = 0;
     game.shots_taken = 0;
+    game.lock_turn_order();
+    game.transition(GameStatus::Ready)?;  // Both seats are filled from the start
+    game.turn_timeout_seconds = platform_config.turn_timeout_seconds;
+    game.turn_deadline = None;
+    game.shots_survived = [0; 4];
     
     // VRF
     game.vrf_seed = vrf_seed;
@@ -77,6 +84,16 @@ pub fn create_ai_game(
     
     platform_config.total_games += 1;
     
+    emit_cpi!(GameCreated {
+        game: game.key(),
+        game_id: game.game_id,
+        creator: game.creator,
+        game_mode: game.game_mode,
+        entry_fee: game.entry_fee,
+        is_ai_game: game.is_ai_game,
+        has_loan: game.has_loan(),
+    });
+    
     msg!("🤖 AI Practice Game {} created", game.game_id);
     msg!("   Difficulty: {:?}", ai_difficulty);
     msg!("   Mode: FREE Practice (No entry fee, no prizes)");
@@ -1,23 +1,9 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::program_pack::Pack;
-use anchor_spl::token_2022::{Token2022, transfer_checked, TransferChecked};
-use anchor_spl::token_2022::spl_token_2022::state::Mint as MintState;
-use anchor_spl::token_2022::spl_token_2022::ID as TOKEN_2022_PROGRAM_ID;
-use crate::{errors::GameError, state::*};
-
-// Helper function to get mint decimals with ownership validation
-fn get_mint_decimals(mint_account: &AccountInfo) -> Result<u8> {
-    // SECURITY: Validate mint is owned by Token-2022 program
-    require!(
-        mint_account.owner == &TOKEN_2022_PROGRAM_ID,
-        GameError::InvalidMint
-    );
-    
-    let mint_data = mint_account.try_borrow_data()?;
-    let mint = MintState::unpack(&mint_data)?;
-    Ok(mint.decimals)
-}
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::TokenAccount;
+use crate::{errors::GameError, events::*, state::*, token::{self, GameTreasury}};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(game_mode: GameMode, entry_fee: u64)]
 pub struct CreateGame<'info> {
@@ -25,14 +11,14 @@ pub struct CreateGame<'info> {
         init,
         payer = creator,
         space = Game::LEN,
-        seeds = [b"game", platform_config.total_games.to_le_bytes().as_ref()],
+        seeds = [Game::SEED, platform_config.total_games.to_le_bytes().as_ref()],
         bump
     )]
     pub game: Account<'info, Game>,
     
     #[account(
         mut,
-        seeds = [b"platform"],
+        seeds = [PlatformConfig::SEED],
         bump = platform_config.bump,
         constraint = !platform_config.paused @ GameError::PlatformPaused
     )]
@@ -41,29 +27,59 @@ pub struct CreateGame<'info> {
     #[account(mut)]
     pub creator: Signer<'info>,
     
+    /// Creator's lifetime stats, created on their first game
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = PlayerStats::LEN,
+        seeds = [PlayerStats::SEED, creator.key().as_ref()],
+        bump
+    )]
+    pub creator_stats: Account<'info, PlayerStats>,
+    
+    /// Creator's treasury rewards, credited when games settle
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = TreasuryRewards::LEN,
+        seeds = [TreasuryRewards::SEED, creator.key().as_ref()],
+        bump
+    )]
+    pub creator_rewards: Account<'info, TreasuryRewards>,
+    
     // Token-2022 accounts for entry fee
-    /// CHECK: Token-2022 mint
+    /// CHECK: Token-2022 mint, extensions validated in create_game
     pub mint: AccountInfo<'info>,
     
     #[account(mut)]
     /// CHECK: Creator's token account
     pub creator_token_account: AccountInfo<'info>,
     
-    #[account(mut)]
-    /// CHECK: Game vault token account
-    pub game_vault: AccountInfo<'info>,
+    /// Game vault PDA, a token account owned by the game
+    #[account(
+        init,
+        payer = creator,
+        seeds = [Game::VAULT_SEED, game.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = game,
+        token::token_program = token_program
+    )]
+    pub game_vault: Box<InterfaceAccount<'info, TokenAccount>>,
     
     pub token_program: Program<'info, Token2022>,
     pub system_program: Program<'info, System>,
+    // remaining_accounts: TransferHook accounts, if the mint has a hook
 }
 
-pub fn create_game(
-    ctx: Context<CreateGame>,
+pub fn create_game<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CreateGame<'info>>,
     game_mode: GameMode,
     entry_fee: u64,
     vrf_seed: [u8; 32],
 ) -> Result<()> {
-    require!(entry_fee > 0, GameError::InsufficientEntryFee);
+    // SECURITY: At least 0.1 of a whole token
+    Game::require_entry_fee(entry_fee, Some(token::mint_decimals(&ctx.accounts.mint)?))?;
     
     let game = &mut ctx.accounts.game;
     let platform_config = &mut ctx.accounts.platform_config;
@@ -74,7 +90,7 @@ pub fn create_game(
     game.game_mode = game_mode;
     game.status = GameStatus::WaitingForPlayers;
     game.entry_fee = entry_fee;
-    game.total_pot = entry_fee;
+    game.mint = Some(ctx.accounts.mint.key());
     
     // Initialize teams
     game.team_a = [ctx.accounts.creator.key(), Pubkey::default()];
@@ -87,6 +103,11 @@ pub fn create_game(
     game.current_chamber = 1;
     game.current_turn = 0;
     game.shots_taken = 0;
+    game.turn_order = [0u8; 4];
+    game.turn_order_len = 0;
+    game.turn_timeout_seconds = platform_config.turn_timeout_seconds;
+    game.turn_deadline = None;
+    game.shots_survived = [0; 4];
     
     // VRF (MagicBlock VRF Plugin)
     game.vrf_seed = vrf_seed;
@@ -100,25 +121,43 @@ pub fn create_game(
     game.finished_at = None;
     game.bump = ctx.bumps.game;
     
-    // Transfer entry fee to game vault
-    transfer_checked(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            TransferChecked {
-                from: ctx.accounts.creator_token_account.to_account_info(),
-                to: ctx.accounts.game_vault.to_account_info(),
-                authority: ctx.accounts.creator.to_account_info(),
-                mint: ctx.accounts.mint.to_account_info(),
-            },
-        ),
+    // SECURITY: Rejects mints whose extensions could trap the pot
+    let treasury = GameTreasury::token(
+        ctx.accounts.game_vault.to_account_info(),
+        game.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.remaining_accounts,
+        &[],
+    )?;
+    
+    // Transfer entry fee to game vault; transfer-fee mints withhold part
+    // of it, so the pot is whatever actually arrived
+    game.total_pot = treasury.deposit(
+        &ctx.accounts.creator.to_account_info(),
+        &ctx.accounts.creator_token_account,
         entry_fee,
-        get_mint_decimals(&ctx.accounts.mint)?,
     )?;
+    require!(game.total_pot > 0, GameError::InsufficientEntryFee);
     
     platform_config.total_games += 1;
     
     msg!("Game {} created: {:?} mode, {} entry fee", 
         game.game_id, game_mode, entry_fee);
     
+    emit_cpi!(GameCreated {
+        game: game.key(),
+        game_id: game.game_id,
+        creator: game.creator,
+        game_mode: game.game_mode,
+        entry_fee: game.entry_fee,
+        is_ai_game: game.is_ai_game,
+        has_loan: game.has_loan(),
+    });
+    
+    // First game for this wallet creates its stats and rewards accounts
+    ctx.accounts.creator_stats.ensure_initialized(ctx.accounts.creator.key(), ctx.bumps.creator_stats);
+    ctx.accounts.creator_rewards.ensure_initialized(ctx.accounts.creator.key(), ctx.bumps.creator_rewards);
+    
     Ok(())
 }
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::{transfer, Transfer};
-use crate::{errors::GameError, state::*};
+use crate::{errors::GameError, events::*, state::*, token::GameTreasury};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(game_mode: GameMode, entry_fee: u64)]
 pub struct CreateGameSol<'info> {
@@ -9,14 +9,14 @@ pub struct CreateGameSol<'info> {
         init,
         payer = creator,
         space = Game::LEN,
-        seeds = [b"game", platform_config.total_games.to_le_bytes().as_ref()],
+        seeds = [Game::SEED, platform_config.total_games.to_le_bytes().as_ref()],
         bump
     )]
     pub game: Account<'info, Game>,
     
     #[account(
         mut,
-        seeds = [b"platform"],
+        seeds = [PlatformConfig::SEED],
         bump = platform_config.bump,
         constraint = !platform_config.paused @ GameError::PlatformPaused
     )]
@@ -25,11 +25,31 @@ pub struct CreateGameSol<'info> {
     #[account(mut)]
     pub creator: Signer<'info>,
     
+    /// Creator's lifetime stats, created on their first game
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = PlayerStats::LEN,
+        seeds = [PlayerStats::SEED, creator.key().as_ref()],
+        bump
+    )]
+    pub creator_stats: Account<'info, PlayerStats>,
+    
+    /// Creator's treasury rewards, credited when games settle
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = TreasuryRewards::LEN,
+        seeds = [TreasuryRewards::SEED, creator.key().as_ref()],
+        bump
+    )]
+    pub creator_rewards: Account<'info, TreasuryRewards>,
+    
     /// Game vault PDA - holds SOL for this game
     /// CHECK: PDA for holding SOL
     #[account(
         mut,
-        seeds = [b"game_vault", game.key().as_ref()],
+        seeds = [Game::VAULT_SEED, game.key().as_ref()],
         bump
     )]
     pub game_vault: AccountInfo<'info>,
@@ -44,7 +64,7 @@ pub fn create_game_sol(
     vrf_seed: [u8; 32],
 ) -> Result<()> {
     // Validate entry fee (minimum 0.01 SOL = 10_000_000 lamports)
-    require!(entry_fee >= 10_000_000, GameError::InsufficientEntryFee);
+    Game::require_entry_fee(entry_fee, None)?;
     
     let game = &mut ctx.accounts.game;
     let platform_config = &mut ctx.accounts.platform_config;
@@ -56,6 +76,7 @@ pub fn create_game_sol(
     game.status = GameStatus::WaitingForPlayers;
     game.entry_fee = entry_fee;
     game.total_pot = entry_fee;
+    game.mint = None;
     
     // AI settings (not AI game)
     game.is_ai_game = false;
@@ -74,6 +95,11 @@ pub fn create_game_sol(
     game.current_chamber = 1;
     game.current_turn = 0;
     game.shots_taken = 0;
+    game.turn_order = [0u8; 4];
+    game.turn_order_len = 0;
+    game.turn_timeout_seconds = platform_config.turn_timeout_seconds;
+    game.turn_deadline = None;
+    game.shots_survived = [0; 4];
     
     // VRF
     game.vrf_seed = vrf_seed;
@@ -88,16 +114,13 @@ pub fn create_game_sol(
     game.bump = ctx.bumps.game;
     
     // Transfer entry fee (SOL) to game vault
-    transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.creator.to_account_info(),
-                to: ctx.accounts.game_vault.to_account_info(),
-            },
-        ),
-        entry_fee,
-    )?;
+    let treasury = GameTreasury::sol(
+        ctx.accounts.game_vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        &[],
+    );
+    let creator = ctx.accounts.creator.to_account_info();
+    treasury.deposit(&creator, &creator, entry_fee)?;
     
     platform_config.total_games += 1;
     
@@ -106,5 +129,19 @@ pub fn create_game_sol(
     msg!("   Entry fee: {} SOL", entry_fee as f64 / 1_000_000_000.0);
     msg!("   Creator: {}", ctx.accounts.creator.key());
     
+    emit_cpi!(GameCreated {
+        game: game.key(),
+        game_id: game.game_id,
+        creator: game.creator,
+        game_mode: game.game_mode,
+        entry_fee: game.entry_fee,
+        is_ai_game: game.is_ai_game,
+        has_loan: game.has_loan(),
+    });
+    
+    // First game for this wallet creates its stats and rewards accounts
+    ctx.accounts.creator_stats.ensure_initialized(ctx.accounts.creator.key(), ctx.bumps.creator_stats);
+    ctx.accounts.creator_rewards.ensure_initialized(ctx.accounts.creator.key(), ctx.bumps.creator_rewards);
+    
     Ok(())
 }
@@ -1,8 +1,15 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::{transfer, Transfer};
-use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed, account_info::AccountInfo as SolanaAccountInfo};
-use crate::{errors::GameError, state::*, constants::KAMINO_PROGRAM_ID};
+use anchor_lang::solana_program::{
+    instruction::Instruction,
+    program::{invoke, invoke_signed},
+    system_instruction, sysvar,
+};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use crate::{errors::GameError, events::*, kamino, oracle, state::*, constants::KAMINO_PROGRAM_ID, token::VaultWsol};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(game_mode: GameMode, entry_fee: u64, collateral_amount: u64)]
 pub struct CreateGameWithLoan<'info> {
@@ -10,14 +17,14 @@ pub struct CreateGameWithLoan<'info> {
         init,
         payer = player,
         space = Game::LEN,
-        seeds = [b"game", platform_config.total_games.to_le_bytes().as_ref()],
+        seeds = [Game::SEED, platform_config.total_games.to_le_bytes().as_ref()],
         bump
     )]
     pub game: Account<'info, Game>,
     
     #[account(
         mut,
-        seeds = [b"platform"],
+        seeds = [PlatformConfig::SEED],
         bump = platform_config.bump,
         constraint = !platform_config.paused @ GameError::PlatformPaused
     )]
@@ -26,15 +33,53 @@ pub struct CreateGameWithLoan<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
     
+    /// Creator's lifetime stats, created on their first game
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = PlayerStats::LEN,
+        seeds = [PlayerStats::SEED, player.key().as_ref()],
+        bump
+    )]
+    pub player_stats: Box<Account<'info, PlayerStats>>,
+    
+    /// Player's treasury rewards, credited when games settle
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = TreasuryRewards::LEN,
+        seeds = [TreasuryRewards::SEED, player.key().as_ref()],
+        bump
+    )]
+    pub player_rewards: Box<Account<'info, TreasuryRewards>>,
+    
+    /// Tracks the loan until its debt is repaid
+    #[account(
+        init,
+        payer = player,
+        space = LoanPosition::LEN,
+        seeds = [LoanPosition::SEED, game.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub loan_position: Box<Account<'info, LoanPosition>>,
+    
     /// Game vault PDA - holds SOL for this game
     /// CHECK: PDA for holding SOL
     #[account(
         mut,
-        seeds = [b"game_vault", game.key().as_ref()],
+        seeds = [Game::VAULT_SEED, game.key().as_ref()],
         bump
     )]
     pub game_vault: AccountInfo<'info>,
     
+    /// Game vault's wrapped SOL account, open only while the loan is paid in
+    /// CHECK: Address derived from the game vault
+    #[account(
+        mut,
+        address = VaultWsol::new(&game_vault.key()).account @ GameError::InvalidVaultOwner
+    )]
+    pub vault_wsol: AccountInfo<'info>,
+    
     // Kamino Lend accounts
     /// CHECK: Kamino lending market (validated by Kamino program)
     pub lending_market: AccountInfo<'info>,
@@ -42,19 +87,35 @@ pub struct CreateGameWithLoan<'info> {
     /// CHECK: Kamino lending market authority (validated by Kamino program)
     pub lending_market_authority: AccountInfo<'info>,
     
-    /// CHECK: SOL reserve account (validated by Kamino program)
+    /// CHECK: Reserve the SOL is borrowed from, checked against sol_oracle
     #[account(mut)]
     pub reserve: AccountInfo<'info>,
     
-    /// CHECK: Reserve liquidity supply (validated by Kamino program)
+    /// CHECK: Liquidity mint of the SOL reserve, wrapped SOL
+    #[account(address = native_mint::ID @ GameError::InvalidMint)]
+    pub reserve_liquidity_mint: AccountInfo<'info>,
+    
+    /// CHECK: Liquidity supply of the SOL reserve, checked against it
     #[account(mut)]
     pub reserve_liquidity_supply: AccountInfo<'info>,
     
-    /// CHECK: Reserve collateral mint (validated by Kamino program)
+    /// CHECK: Fee vault of the SOL reserve, receives the borrow fee; checked against it
+    #[account(mut)]
+    pub reserve_liquidity_fee_receiver: AccountInfo<'info>,
+    
+    /// CHECK: Reserve the collateral is deposited into, checked against collateral_oracle
+    #[account(mut)]
+    pub collateral_reserve: AccountInfo<'info>,
+    
+    /// CHECK: Liquidity supply of the collateral reserve, checked against it
+    #[account(mut)]
+    pub collateral_reserve_liquidity_supply: AccountInfo<'info>,
+    
+    /// CHECK: cToken mint of the collateral reserve, checked against it
     #[account(mut)]
     pub reserve_collateral_mint: AccountInfo<'info>,
     
-    /// CHECK: Reserve collateral supply (validated by Kamino program)
+    /// CHECK: cToken supply of the collateral reserve, checked against it
     #[account(mut)]
     pub reserve_collateral_supply: AccountInfo<'info>,
     
@@ -62,9 +123,13 @@ pub struct CreateGameWithLoan<'info> {
     #[account(mut)]
     pub obligation: AccountInfo<'info>,
     
-    /// CHECK: Player's collateral token account (validated by Kamino program)
-    #[account(mut)]
-    pub player_collateral_account: AccountInfo<'info>,
+    /// Player's collateral token account, the deposit source; its mint is
+    /// the one the collateral is priced as
+    #[account(
+        mut,
+        constraint = player_collateral_account.mint == collateral_mint.key() @ GameError::InvalidMint
+    )]
+    pub player_collateral_account: Box<InterfaceAccount<'info, TokenAccount>>,
     
     /// CHECK: Obligation collateral account (validated by Kamino program)
     #[account(mut)]
@@ -72,111 +137,264 @@ pub struct CreateGameWithLoan<'info> {
     
     /// CHECK: Kamino lending program - validated against known program ID
     #[account(
-        constraint = kamino_program.key() == KAMINO_PROGRAM_ID @ GameError::InvalidKaminoMarket
+        constraint = kamino_program.key() == KAMINO_PROGRAM_ID @ GameError::InvalidKaminoProgram
     )]
     pub kamino_program: AccountInfo<'info>,
     
-    /// CHECK: Pyth SOL price feed (for oracle)
+    /// CHECK: Instructions sysvar, where Kamino checks for its refresh instructions
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+    
+    // Oracle accounts
+    /// Price feeds registered for SOL, the borrowed asset
+    #[account(
+        seeds = [AssetOracle::SEED, native_mint::ID.as_ref()],
+        bump = sol_oracle.bump
+    )]
+    pub sol_oracle: Box<Account<'info, AssetOracle>>,
+    
+    /// CHECK: Pyth SOL price update, checked against sol_oracle
     pub pyth_sol_price: AccountInfo<'info>,
     
-    /// CHECK: Switchboard SOL price feed (for oracle)
+    /// CHECK: Switchboard SOL pull feed, checked against sol_oracle
     pub switchboard_sol_price: AccountInfo<'info>,
     
+    /// Mint of the deposited collateral
+    pub collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    /// Price feeds registered for the collateral mint
+    #[account(
+        seeds = [AssetOracle::SEED, collateral_mint.key().as_ref()],
+        bump = collateral_oracle.bump
+    )]
+    pub collateral_oracle: Box<Account<'info, AssetOracle>>,
+    
+    /// CHECK: Pyth collateral price update, checked against collateral_oracle
+    pub collateral_pyth_price: AccountInfo<'info>,
+    
+    /// CHECK: Switchboard collateral pull feed, checked against collateral_oracle
+    pub collateral_switchboard_price: AccountInfo<'info>,
+    
     pub token_program: Program<'info, anchor_spl::token::Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
     pub clock: Sysvar<'info, Clock>,
 }
 
+/// Borrow into the game vault with `borrow_ix`.
+///
+/// Kamino lends wrapped SOL, so the loan is paid into the vault's wSOL
+/// account, opened at the player's expense, then unwrapped into the vault
+/// by closing it; the player gets the account's rent back. klend takes its
+/// origination fee out of the loan, so the player borrows enough to cover
+/// it: the vault keeps exactly `entry_fee` and anything above it goes back
+/// to the player too. `accounts` holds every account of the borrow, the wSOL
+/// account and the programs involved.
+pub fn borrow_into_vault<'info>(
+    borrow_ix: &Instruction,
+    entry_fee: u64,
+    wsol: &VaultWsol,
+    player: &AccountInfo<'info>,
+    vault_wsol: &AccountInfo<'info>,
+    accounts: &[AccountInfo<'info>],
+    vault_signer: &[&[&[u8]]],
+) -> Result<()> {
+    invoke(&wsol.open(player.key), accounts)?;
+    invoke(borrow_ix, accounts)?;
+    
+    // SECURITY: What arrived after Kamino's fees must cover the entry fee
+    let (received, rent) = VaultWsol::balance(vault_wsol)?;
+    require!(received >= entry_fee, GameError::BorrowBelowEntryFee);
+    let surplus = received - entry_fee;
+    
+    invoke_signed(&wsol.close()?, accounts, vault_signer)?;
+    invoke_signed(
+        &system_instruction::transfer(&wsol.vault, player.key, rent + surplus),
+        accounts,
+        vault_signer,
+    )?;
+    Ok(())
+}
+
 pub fn create_game_with_loan(
     ctx: Context<CreateGameWithLoan>,
     game_mode: GameMode,
     entry_fee: u64,
     collateral_amount: u64,
+    borrow_amount: u64,
     vrf_seed: [u8; 32],
 ) -> Result<()> {
     // Validate entry fee (minimum 0.01 SOL = 10_000_000 lamports)
-    require!(entry_fee >= 10_000_000, GameError::InsufficientEntryFee);
-    
-    // Validate collateral ratio (minimum 110% of entry_fee)
-    let required_collateral = entry_fee
-        .checked_mul(110)
-        .ok_or(GameError::ArithmeticOverflow)?
-        .checked_div(100)
-        .ok_or(GameError::ArithmeticOverflow)?;
+    Game::require_entry_fee(entry_fee, None)?;
+    
+    // SECURITY: Value the collateral and the borrowed SOL, Kamino's fee
+    // included, at oracle prices and hold the loan to the platform's
+    // minimum collateral ratio
+    let now = Clock::get()?.unix_timestamp;
+    let sol_price = oracle::price(
+        &ctx.accounts.sol_oracle,
+        &ctx.accounts.pyth_sol_price,
+        &ctx.accounts.switchboard_sol_price,
+        now,
+    )?;
+    let collateral_price = oracle::price(
+        &ctx.accounts.collateral_oracle,
+        &ctx.accounts.collateral_pyth_price,
+        &ctx.accounts.collateral_switchboard_price,
+        now,
+    )?;
+    oracle::require_collateral_ratio(
+        collateral_amount,
+        ctx.accounts.collateral_mint.decimals,
+        &collateral_price,
+        borrow_amount,
+        native_mint::DECIMALS,
+        &sol_price,
+        ctx.accounts.platform_config.min_collateral_ratio_bps,
+    )?;
     
-    require!(
-        collateral_amount >= required_collateral,
-        GameError::InsufficientCollateral
-    );
+    // SECURITY: The collateral goes into the market's reserve for the
+    // collateral asset and the SOL comes out of its SOL reserve, each with
+    // the supplies and mints that reserve records
+    kamino::ReserveKeys::load(&ctx.accounts.collateral_reserve)?.require_deposit(
+        ctx.accounts.lending_market.key,
+        &ctx.accounts.collateral_oracle.mint,
+        ctx.accounts.collateral_reserve_liquidity_supply.key,
+        ctx.accounts.reserve_collateral_mint.key,
+        ctx.accounts.reserve_collateral_supply.key,
+    )?;
+    kamino::ReserveKeys::load(&ctx.accounts.reserve)?.require_borrow(
+        ctx.accounts.lending_market.key,
+        &ctx.accounts.sol_oracle.mint,
+        ctx.accounts.reserve_liquidity_supply.key,
+        ctx.accounts.reserve_liquidity_fee_receiver.key,
+    )?;
     
     let game = &mut ctx.accounts.game;
     let platform_config = &mut ctx.accounts.platform_config;
     
     msg!("🎮 Creating game with Kamino loan");
     msg!("   Entry fee: {} SOL", entry_fee as f64 / 1e9);
-    msg!("   Collateral: {} SOL ({}%)", collateral_amount as f64 / 1e9, (collateral_amount * 100) / entry_fee);
+    msg!("   Collateral: {} of {}", collateral_amount, ctx.accounts.collateral_mint.key());
     
     // STEP 1: Deposit collateral to Kamino
     msg!("💰 Step 1: Depositing collateral to Kamino...");
     
-    let deposit_ix = build_kamino_deposit_ix(
-        ctx.accounts.lending_market.key(),
-        ctx.accounts.reserve.key(),
-        ctx.accounts.reserve_liquidity_supply.key(),
-        ctx.accounts.reserve_collateral_mint.key(),
-        ctx.accounts.player.key(),
-        ctx.accounts.player_collateral_account.key(),
-        ctx.accounts.player.key(),
-        collateral_amount,
-    );
+    let collateral_before = kamino::obligation_collateral(
+        &ctx.accounts.obligation,
+        ctx.accounts.collateral_reserve.key,
+        ctx.accounts.lending_market.key,
+    )?;
     
-    invoke_signed(
+    let deposit_ix = kamino::DepositReserveLiquidityAndObligationCollateral {
+        owner: ctx.accounts.player.key(),
+        obligation: ctx.accounts.obligation.key(),
+        lending_market: ctx.accounts.lending_market.key(),
+        lending_market_authority: ctx.accounts.lending_market_authority.key(),
+        reserve: ctx.accounts.collateral_reserve.key(),
+        reserve_liquidity_mint: ctx.accounts.collateral_mint.key(),
+        reserve_liquidity_supply: ctx.accounts.collateral_reserve_liquidity_supply.key(),
+        reserve_collateral_mint: ctx.accounts.reserve_collateral_mint.key(),
+        reserve_destination_deposit_collateral: ctx.accounts.reserve_collateral_supply.key(),
+        user_source_liquidity: ctx.accounts.player_collateral_account.key(),
+        collateral_token_program: ctx.accounts.token_program.key(),
+        liquidity_token_program: ctx.accounts.token_program.key(),
+    }
+    .instruction(collateral_amount);
+    
+    invoke(
         &deposit_ix,
         &[
             ctx.accounts.player.to_account_info(),
-            ctx.accounts.reserve.to_account_info(),
-            ctx.accounts.reserve_liquidity_supply.to_account_info(),
-            ctx.accounts.reserve_collateral_mint.to_account_info(),
+            ctx.accounts.obligation.to_account_info(),
             ctx.accounts.lending_market.to_account_info(),
-            ctx.accounts.player.to_account_info(),
+            ctx.accounts.lending_market_authority.to_account_info(),
+            ctx.accounts.collateral_reserve.to_account_info(),
+            ctx.accounts.collateral_mint.to_account_info(),
+            ctx.accounts.collateral_reserve_liquidity_supply.to_account_info(),
+            ctx.accounts.reserve_collateral_mint.to_account_info(),
+            ctx.accounts.reserve_collateral_supply.to_account_info(),
             ctx.accounts.player_collateral_account.to_account_info(),
+            ctx.accounts.kamino_program.to_account_info(),
             ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.instruction_sysvar.to_account_info(),
         ],
-        &[],
     )?;
     
+    // klend withdraws collateral in cTokens, so record how many the deposit added
+    let collateral_ctokens = kamino::obligation_collateral(
+        &ctx.accounts.obligation,
+        ctx.accounts.collateral_reserve.key,
+        ctx.accounts.lending_market.key,
+    )?
+    .checked_sub(collateral_before)
+    .ok_or(GameError::ArithmeticOverflow)?;
+    
     msg!("✅ Collateral deposited to Kamino");
     
-    // STEP 2: Borrow SOL from Kamino
-    msg!("💸 Step 2: Borrowing {} SOL from Kamino...", entry_fee as f64 / 1e9);
-    
-    let borrow_ix = build_kamino_borrow_ix(
-        ctx.accounts.lending_market.key(),
-        ctx.accounts.lending_market_authority.key(),
-        ctx.accounts.obligation.key(),
-        ctx.accounts.reserve.key(),
-        ctx.accounts.reserve_liquidity_supply.key(),
-        ctx.accounts.game_vault.key(),
-        ctx.accounts.player.key(),
-        entry_fee,
-    );
+    // STEP 2: Borrow SOL from Kamino; the vault keeps the entry fee, so
+    // every seat puts the same amount in the pot
+    msg!("💸 Step 2: Borrowing {} SOL from Kamino...", borrow_amount as f64 / 1e9);
+    
+    let wsol = VaultWsol::new(&ctx.accounts.game_vault.key());
+    let borrow_ix = kamino::BorrowObligationLiquidity {
+        owner: ctx.accounts.player.key(),
+        obligation: ctx.accounts.obligation.key(),
+        lending_market: ctx.accounts.lending_market.key(),
+        lending_market_authority: ctx.accounts.lending_market_authority.key(),
+        borrow_reserve: ctx.accounts.reserve.key(),
+        borrow_reserve_liquidity_mint: ctx.accounts.reserve_liquidity_mint.key(),
+        reserve_source_liquidity: ctx.accounts.reserve_liquidity_supply.key(),
+        borrow_reserve_liquidity_fee_receiver: ctx.accounts.reserve_liquidity_fee_receiver.key(),
+        user_destination_liquidity: wsol.account,
+        token_program: ctx.accounts.token_program.key(),
+    }
+    .instruction(borrow_amount);
     
-    invoke_signed(
+    // The obligation may already carry debt, so the loan is what this borrow adds
+    let debt_before = kamino::obligation_debt(
+        &ctx.accounts.obligation,
+        &ctx.accounts.reserve,
+        ctx.accounts.lending_market.key,
+    )?;
+    
+    let game_key = game.key();
+    let vault_seeds: &[&[u8]] = &[Game::VAULT_SEED, game_key.as_ref(), &[ctx.bumps.game_vault]];
+    borrow_into_vault(
         &borrow_ix,
+        entry_fee,
+        &wsol,
+        &ctx.accounts.player.to_account_info(),
+        &ctx.accounts.vault_wsol,
         &[
             ctx.accounts.player.to_account_info(),
             ctx.accounts.obligation.to_account_info(),
             ctx.accounts.lending_market.to_account_info(),
             ctx.accounts.lending_market_authority.to_account_info(),
             ctx.accounts.reserve.to_account_info(),
+            ctx.accounts.reserve_liquidity_mint.to_account_info(),
             ctx.accounts.reserve_liquidity_supply.to_account_info(),
+            ctx.accounts.reserve_liquidity_fee_receiver.to_account_info(),
             ctx.accounts.game_vault.to_account_info(),
+            ctx.accounts.vault_wsol.to_account_info(),
+            ctx.accounts.kamino_program.to_account_info(),
             ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.associated_token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.instruction_sysvar.to_account_info(),
         ],
-        &[],
+        &[vault_seeds],
     )?;
     
+    let loan_amount = kamino::obligation_debt(
+        &ctx.accounts.obligation,
+        &ctx.accounts.reserve,
+        ctx.accounts.lending_market.key,
+    )?
+    .checked_sub(debt_before)
+    .ok_or(GameError::ArithmeticOverflow)?;
+    
     msg!("✅ SOL borrowed from Kamino and transferred to game vault");
     
     // Initialize game
@@ -186,6 +404,7 @@ pub fn create_game_with_loan(
     game.status = GameStatus::WaitingForPlayers;
     game.entry_fee = entry_fee;
     game.total_pot = entry_fee;
+    game.mint = None;
     
     // AI settings (not AI game)
     game.is_ai_game = false;
@@ -193,11 +412,20 @@ pub fn create_game_with_loan(
     game.ai_player = None;
     game.is_practice_mode = false;
     
-    // Kamino loan settings
-    game.has_loan = true;
-    game.loan_obligation = Some(ctx.accounts.obligation.key());
-    game.collateral_amount = collateral_amount;
-    game.loan_amount = entry_fee;
+    let loan_position = &mut ctx.accounts.loan_position;
+    loan_position.game = game.key();
+    loan_position.game_id = game.game_id;
+    loan_position.borrower = game.creator;
+    loan_position.lending_market = ctx.accounts.lending_market.key();
+    loan_position.reserve = ctx.accounts.reserve.key();
+    loan_position.collateral_reserve = ctx.accounts.collateral_reserve.key();
+    loan_position.obligation = ctx.accounts.obligation.key();
+    loan_position.collateral_amount = collateral_amount;
+    loan_position.collateral_ctokens = collateral_ctokens;
+    loan_position.loan_amount = loan_amount;
+    loan_position.status = LoanStatus::Open;
+    loan_position.bump = ctx.bumps.loan_position;
+    loan_position.borrow_rate_bsf = kamino::reserve_borrow_rate(&ctx.accounts.reserve)?;
     
     // Initialize teams
     game.team_a = [ctx.accounts.player.key(), Pubkey::default()];
@@ -205,11 +433,20 @@ pub fn create_game_with_loan(
     game.team_a_count = 1;
     game.team_b_count = 0;
     
+    // Kamino loan settings
+    game.loan_seats = 0;
+    game.record_loan(&ctx.accounts.player.key())?;
+    
     // Initialize game state
     game.bullet_chamber = 0;
     game.current_chamber = 1;
     game.current_turn = 0;
     game.shots_taken = 0;
+    game.turn_order = [0u8; 4];
+    game.turn_order_len = 0;
+    game.turn_timeout_seconds = platform_config.turn_timeout_seconds;
+    game.turn_deadline = None;
+    game.shots_survived = [0; 4];
     
     // VRF
     game.vrf_seed = vrf_seed;
@@ -228,148 +465,28 @@ pub fn create_game_with_loan(
     msg!("✅ Game {} created with loan", game.game_id);
     msg!("   Obligation: {}", ctx.accounts.obligation.key());
     
-    Ok(())
-}
-
-// ============================================================================
-// KAMINO CPI HELPER FUNCTIONS (Ready for production use)
-// ============================================================================
-
-/// Build Kamino DepositReserveLiquidity instruction
-/// Use this to deposit collateral to Kamino
-#[allow(dead_code)]
-pub fn build_kamino_deposit_ix(
-    lending_market: Pubkey,
-    reserve: Pubkey,
-    reserve_liquidity_supply: Pubkey,
-    reserve_collateral_mint: Pubkey,
-    user_source: Pubkey,
-    user_destination: Pubkey,
-    owner: Pubkey,
-    amount: u64,
-) -> Instruction {
-    use anchor_lang::solana_program::instruction::AccountMeta;
-    
-    // Kamino DepositReserveLiquidity instruction discriminator
-    let mut data = vec![0xef, 0x1d, 0x1a, 0x4e, 0x15, 0x4a, 0x9c, 0x7e];
-    data.extend_from_slice(&amount.to_le_bytes());
-    
-    Instruction {
-        program_id: KAMINO_PROGRAM_ID,
-        accounts: vec![
-            AccountMeta::new_readonly(owner, true),
-            AccountMeta::new(reserve, false),
-            AccountMeta::new(reserve_liquidity_supply, false),
-            AccountMeta::new(reserve_collateral_mint, false),
-            AccountMeta::new_readonly(lending_market, false),
-            AccountMeta::new(user_source, false),
-            AccountMeta::new(user_destination, false),
-            AccountMeta::new_readonly(anchor_spl::token::ID, false),
-        ],
-        data,
-    }
-}
-
-/// Build Kamino BorrowObligationLiquidity instruction
-/// Use this to borrow SOL from Kamino
-#[allow(dead_code)]
-pub fn build_kamino_borrow_ix(
-    lending_market: Pubkey,
-    lending_market_authority: Pubkey,
-    obligation: Pubkey,
-    reserve: Pubkey,
-    reserve_liquidity_supply: Pubkey,
-    user_destination: Pubkey,
-    owner: Pubkey,
-    amount: u64,
-) -> Instruction {
-    use anchor_lang::solana_program::instruction::AccountMeta;
-    
-    // Kamino BorrowObligationLiquidity instruction discriminator
-    let mut data = vec![0x0c, 0x7e, 0x9e, 0x3a, 0x17, 0x4d, 0x5e, 0x8f];
-    data.extend_from_slice(&amount.to_le_bytes());
-    
-    Instruction {
-        program_id: KAMINO_PROGRAM_ID,
-        accounts: vec![
-            AccountMeta::new_readonly(owner, true),
-            AccountMeta::new(obligation, false),
-            AccountMeta::new_readonly(lending_market, false),
-            AccountMeta::new_readonly(lending_market_authority, false),
-            AccountMeta::new(reserve, false),
-            AccountMeta::new(reserve_liquidity_supply, false),
-            AccountMeta::new(user_destination, false),
-            AccountMeta::new_readonly(anchor_spl::token::ID, false),
-        ],
-        data,
-    }
-}
-
-/// Example of how to use Kamino CPI in production
-/// Uncomment and integrate when ready for full Kamino integration
-#[allow(dead_code)]
-fn example_kamino_integration(
-    ctx: &Context<CreateGameWithLoan>,
-    collateral_amount: u64,
-    entry_fee: u64,
-) -> Result<()> {
-    // Step 1: Deposit collateral
-    let deposit_ix = build_kamino_deposit_ix(
-        ctx.accounts.lending_market.key(),
-        ctx.accounts.reserve.key(),
-        ctx.accounts.reserve_liquidity_supply.key(),
-        ctx.accounts.reserve_collateral_mint.key(),
-        ctx.accounts.player.key(),
-        ctx.accounts.player_collateral_account.key(),
-        ctx.accounts.player.key(),
+    emit_cpi!(GameCreated {
+        game: game.key(),
+        game_id: game.game_id,
+        creator: game.creator,
+        game_mode: game.game_mode,
+        entry_fee: game.entry_fee,
+        is_ai_game: game.is_ai_game,
+        has_loan: game.has_loan(),
+    });
+    
+    emit_cpi!(LoanOpened {
+        game: game.key(),
+        game_id: game.game_id,
+        borrower: game.creator,
+        obligation: ctx.accounts.obligation.key(),
         collateral_amount,
-    );
-    
-    invoke_signed(
-        &deposit_ix,
-        &[
-            ctx.accounts.player.to_account_info(),
-            ctx.accounts.reserve.to_account_info(),
-            ctx.accounts.reserve_liquidity_supply.to_account_info(),
-            ctx.accounts.reserve_collateral_mint.to_account_info(),
-            ctx.accounts.lending_market.to_account_info(),
-            ctx.accounts.player.to_account_info(),
-            ctx.accounts.player_collateral_account.to_account_info(),
-            ctx.accounts.token_program.to_account_info(),
-        ],
-        &[],
-    )?;
-    
-    msg!("✅ Collateral deposited to Kamino");
-    
-    // Step 2: Borrow SOL
-    let borrow_ix = build_kamino_borrow_ix(
-        ctx.accounts.lending_market.key(),
-        ctx.accounts.lending_market_authority.key(),
-        ctx.accounts.obligation.key(),
-        ctx.accounts.reserve.key(),
-        ctx.accounts.reserve_liquidity_supply.key(),
-        ctx.accounts.game_vault.key(),
-        ctx.accounts.player.key(),
-        entry_fee,
-    );
-    
-    invoke_signed(
-        &borrow_ix,
-        &[
-            ctx.accounts.player.to_account_info(),
-            ctx.accounts.obligation.to_account_info(),
-            ctx.accounts.lending_market.to_account_info(),
-            ctx.accounts.lending_market_authority.to_account_info(),
-            ctx.accounts.reserve.to_account_info(),
-            ctx.accounts.reserve_liquidity_supply.to_account_info(),
-            ctx.accounts.game_vault.to_account_info(),
-            ctx.accounts.token_program.to_account_info(),
-        ],
-        &[],
-    )?;
+        loan_amount,
+    });
     
-    msg!("✅ SOL borrowed from Kamino");
+    // First game for this wallet creates its stats and rewards accounts
+    ctx.accounts.player_stats.ensure_initialized(ctx.accounts.player.key(), ctx.bumps.player_stats);
+    ctx.accounts.player_rewards.ensure_initialized(ctx.accounts.player.key(), ctx.bumps.player_rewards);
     
     Ok(())
 }
@@ -1,26 +1,27 @@
 use anchor_lang::prelude::*;
-use ephemeral_rollups_sdk::anchor::DelegationProgram;
-use crate::{errors::GameError, state::*};
+use ephemeral_rollups_sdk::anchor::{commit, delegate, DelegationProgram};
+use ephemeral_rollups_sdk::cpi::DelegateConfig;
+use ephemeral_rollups_sdk::ephem::{commit_accounts, commit_and_undelegate_accounts};
+use crate::{errors::GameError, events::*, state::*};
 
+#[delegate]
+#[event_cpi]
 #[derive(Accounts)]
 pub struct DelegateGame<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     
     /// Game account to delegate
-    #[account(
-        mut,
-        seeds = [b"game", game.game_id.to_le_bytes().as_ref()],
-        bump = game.bump,
-        constraint = game.status == GameStatus::WaitingForPlayers 
-            @ GameError::InvalidGameStatus
-    )]
-    pub game: Account<'info, Game>,
+    /// CHECK: Deserialized and validated in instruction logic - delegation
+    /// hands ownership to the delegation program, so Anchor must not
+    /// write it back on exit
+    #[account(mut, del)]
+    pub game: AccountInfo<'info>,
     
     /// Platform config for permission check
     #[account(
-        seeds = [b"platform_config"],
-        bump,
+        seeds = [PlatformConfig::SEED],
+        bump = platform_config.bump,
     )]
     pub platform_config: Account<'info, PlatformConfig>,
     
@@ -28,31 +29,28 @@ pub struct DelegateGame<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[commit]
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CommitGame<'info> {
     #[account(
         mut,
-        seeds = [b"game", game.game_id.to_le_bytes().as_ref()],
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
         bump = game.bump
     )]
     pub game: Account<'info, Game>,
     
     #[account(mut)]
     pub payer: Signer<'info>,
-    
-    /// CHECK: MagicBlock program
-    pub magic_program: AccountInfo<'info>,
-    
-    /// CHECK: MagicBlock context
-    #[account(mut)]
-    pub magic_context: AccountInfo<'info>,
 }
 
+#[commit]
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UndelegateGame<'info> {
     #[account(
         mut,
-        seeds = [b"game", game.game_id.to_le_bytes().as_ref()],
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
         bump = game.bump,
         constraint = game.status == GameStatus::Finished @ GameError::GameNotFinished
     )]
@@ -60,11 +58,107 @@ pub struct UndelegateGame<'info> {
     
     #[account(mut)]
     pub payer: Signer<'info>,
+}
+
+pub fn delegate_game(ctx: Context<DelegateGame>) -> Result<()> {
+    // SECURITY: Game must be one of ours, at its canonical PDA
+    require!(
+        ctx.accounts.game.owner == &crate::ID,
+        GameError::InvalidGameStatus
+    );
+    let mut game = {
+        let data = ctx.accounts.game.try_borrow_data()?;
+        Game::try_deserialize(&mut &data[..])?
+    };
+    let game_id_bytes = game.game_id.to_le_bytes();
+    let expected_game = Pubkey::create_program_address(
+        &[Game::SEED, game_id_bytes.as_ref(), &[game.bump]],
+        &crate::ID,
+    ).map_err(|_| GameError::InvalidGameStatus)?;
+    require!(
+        ctx.accounts.game.key() == expected_game,
+        GameError::InvalidGameStatus
+    );
     
-    /// CHECK: MagicBlock program
-    pub magic_program: AccountInfo<'info>,
+    // Only full games, locked in as Ready, can be delegated
+    game.transition(GameStatus::Delegated)?;
     
-    /// CHECK: MagicBlock context
-    #[account(mut)]
-    pub magic_context: AccountInfo<'info>,
+    // Permission check: Only creator or platform authority
+    require!(
+        ctx.accounts.payer.key() == game.creator 
+            || ctx.accounts.payer.key() == ctx.accounts.platform_config.admin(),
+        GameError::Unauthorized
+    );
+    
+    // Persist the status change before the account leaves our ownership
+    {
+        let mut data = ctx.accounts.game.try_borrow_mut_data()?;
+        let mut writer: &mut [u8] = &mut data;
+        game.try_serialize(&mut writer)?;
+    }
+    
+    ctx.accounts.delegate_game(
+        &ctx.accounts.payer,
+        &[Game::SEED, game_id_bytes.as_ref()],
+        DelegateConfig::default(),
+    )?;
+    
+    emit_cpi!(Delegated {
+        game: ctx.accounts.game.key(),
+        game_id: game.game_id,
+        payer: ctx.accounts.payer.key(),
+    });
+    
+    msg!("✅ Game {} delegated to ER", game.game_id);
+    msg!("   Players can now take shots with sub-10ms latency");
+    
+    Ok(())
+}
+
+pub fn commit_game(ctx: Context<CommitGame>) -> Result<()> {
+    let game = &ctx.accounts.game;
+    
+    commit_accounts(
+        &ctx.accounts.payer,
+        vec![&game.to_account_info()],
+        &ctx.accounts.magic_context,
+        &ctx.accounts.magic_program,
+    )?;
+    
+    emit_cpi!(GameCommitted {
+        game: game.key(),
+        game_id: game.game_id,
+        undelegated: false,
+    });
+    
+    msg!("💾 Game {} state committed to base layer", game.game_id);
+    msg!("   Status: {:?}", game.status);
+    msg!("   Shots taken: {}", game.shots_taken);
+    
+    Ok(())
+}
+
+pub fn undelegate_game(ctx: Context<UndelegateGame>) -> Result<()> {
+    let game = &ctx.accounts.game;
+    
+    // Final state is committed and ownership returns to this program on
+    // the base layer; until then finalize cannot load the delegated game
+    commit_and_undelegate_accounts(
+        &ctx.accounts.payer,
+        vec![&game.to_account_info()],
+        &ctx.accounts.magic_context,
+        &ctx.accounts.magic_program,
+    )?;
+    
+    emit_cpi!(GameCommitted {
+        game: game.key(),
+        game_id: game.game_id,
+        undelegated: true,
+    });
+    
+    msg!("✅ Game {} committed and undelegated from Ephemeral Rollup", game.game_id);
+    msg!("   Final status: {:?}", game.status);
+    msg!("   Winner: Team {:?}", game.winner_team);
+    
+    Ok(())
 }
@@ -1,19 +1,21 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_2022::Token2022;
-use crate::state::*;
+use anchor_spl::token_interface::TokenAccount;
+use crate::{errors::GameError, state::*};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct FinalizeGame<'info> {
     #[account(
         mut,
-        seeds = [b"game", game.game_id.to_le_bytes().as_ref()],
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
         bump = game.bump
     )]
     pub game: Box<Account<'info, Game>>,
     
     #[account(
         mut,
-        seeds = [b"platform"],
+        seeds = [PlatformConfig::SEED],
         bump = platform_config.bump
     )]
     pub platform_config: Box<Account<'info, PlatformConfig>>,
@@ -22,37 +24,180 @@ pub struct FinalizeGame<'info> {
     pub payer: Signer<'info>,
     
     // Token accounts
-    /// CHECK: Token-2022 mint
+    /// CHECK: Token-2022 mint, must be the mint the game was created with
+    #[account(constraint = game.mint == Some(mint.key()) @ GameError::InvalidMint)]
     pub mint: AccountInfo<'info>,
     
-    #[account(mut)]
-    /// CHECK: Game vault token account
-    pub game_vault: AccountInfo<'info>,
+    /// Game vault PDA, a token account owned by the game
+    #[account(
+        mut,
+        address = Game::find_vault_address(&game.key()).0 @ GameError::InvalidVaultOwner,
+        constraint = game_vault.owner == game.key() @ GameError::InvalidVaultOwner,
+        constraint = game_vault.mint == mint.key() @ GameError::InvalidMint
+    )]
+    pub game_vault: Box<InterfaceAccount<'info, TokenAccount>>,
     
-    #[account(mut)]
-    /// CHECK: Platform vault token account
-    pub platform_vault: AccountInfo<'info>,
+    /// Platform fee account, owned by the platform authority or Squads vault 0
+    #[account(
+        mut,
+        constraint = platform_vault.owner == platform_config.platform_fee_recipient() @ GameError::InvalidVaultOwner,
+        constraint = platform_vault.mint == mint.key() @ GameError::InvalidMint
+    )]
+    pub platform_vault: Box<InterfaceAccount<'info, TokenAccount>>,
     
-    #[account(mut)]
-    /// CHECK: Treasury vault token account
-    pub treasury_vault: AccountInfo<'info>,
+    /// Treasury fee account, owned by the configured treasury
+    #[account(
+        mut,
+        constraint = treasury_vault.owner == platform_config.treasury @ GameError::InvalidVaultOwner,
+        constraint = treasury_vault.mint == mint.key() @ GameError::InvalidMint
+    )]
+    pub treasury_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Player rewards pool, owned by the platform config PDA.
+    /// Required when the game is played in the platform mint.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = platform_config,
+        associated_token::token_program = token_program
+    )]
+    pub rewards_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     
     // Winner accounts (up to 2 for 2v2)
-    /// CHECK: Winner 1
+    /// CHECK: Winner 1, validated against the winning team in finalize_game
     #[account(mut)]
     pub winner1: AccountInfo<'info>,
     
-    /// CHECK: Winner 1 token account
-    #[account(mut)]
-    pub winner1_token_account: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = winner1_token_account.owner == winner1.key() @ GameError::InvalidWinner,
+        constraint = winner1_token_account.mint == mint.key() @ GameError::InvalidMint
+    )]
+    pub winner1_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
     
-    /// CHECK: Winner 2 (optional for 2v2)
+    /// CHECK: Winner 2 (optional for 2v2), validated in finalize_game
     #[account(mut)]
     pub winner2: AccountInfo<'info>,
     
-    /// CHECK: Winner 2 token account (optional)
-    #[account(mut)]
-    pub winner2_token_account: AccountInfo<'info>,
+    /// Winner 2 token account (only paid out in 2v2)
+    #[account(
+        mut,
+        constraint = winner2_token_account.owner == winner2.key() @ GameError::InvalidWinner,
+        constraint = winner2_token_account.mint == mint.key() @ GameError::InvalidMint
+    )]
+    pub winner2_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
     
     pub token_program: Program<'info, Token2022>,
+    // remaining_accounts: PlayerStats PDAs of every entrant, in seat order,
+    // followed by their TreasuryRewards PDAs for platform-mint games and
+    // then any TransferHook accounts the mint needs
+}
+
+/// Fold a finished game into every entrant's PlayerStats.
+///
+/// `stats_accounts` must follow `Game::entrants` order. Winners are credited
+/// with `per_winner`; practice games never reach this point.
+pub fn record_player_stats(game: &Game, stats_accounts: &[AccountInfo], per_winner: u64) -> Result<()> {
+    let winning_team = game.winner_team.ok_or(GameError::GameNotInProgress)?;
+    let entrants = game.entrants();
+    require!(
+        stats_accounts.len() == entrants.len(),
+        GameError::InvalidPlayerStats
+    );
+
+    for (index, (account, entrant)) in stats_accounts.iter().zip(entrants.iter()).enumerate() {
+        let (team, slot) = if index < game.team_a_count as usize {
+            (0, index)
+        } else {
+            (1, index - game.team_a_count as usize)
+        };
+        let seat = turn_order::seat(team, slot as u8) as usize;
+
+        // SECURITY: Only our own stats account for this entrant may be credited
+        require!(
+            account.owner == &crate::ID && account.is_writable,
+            GameError::InvalidPlayerStats
+        );
+        let mut data = account.try_borrow_mut_data()?;
+        let mut stats = PlayerStats::try_deserialize(&mut &data[..])?;
+        require!(stats.player == *entrant, GameError::InvalidPlayerStats);
+
+        let winnings = (team == winning_team).then_some(per_winner);
+        stats.record_game(game.entry_fee, winnings, game.shots_survived[seat])?;
+        stats.try_serialize(&mut &mut data[..])?;
+    }
+
+    Ok(())
+}
+
+/// Split the accounts a finalize takes after its fixed accounts: every
+/// entrant's PlayerStats, then their TreasuryRewards when `with_rewards`,
+/// both in `Game::entrants` order. Returns `(stats, rewards, rest)`.
+pub fn split_entrant_accounts<'a, 'info>(
+    game: &Game,
+    accounts: &'a [AccountInfo<'info>],
+    with_rewards: bool,
+) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>], &'a [AccountInfo<'info>])> {
+    let entrants = game.entrants().len();
+    require!(accounts.len() >= entrants, GameError::InvalidPlayerStats);
+    let (stats, rest) = accounts.split_at(entrants);
+
+    let rewards_len = if with_rewards { entrants } else { 0 };
+    require!(rest.len() >= rewards_len, GameError::InvalidRewardsAccount);
+    let (rewards, rest) = rest.split_at(rewards_len);
+
+    Ok((stats, rewards, rest))
+}
+
+/// Each entrant's wager-weighted cut of `pool`, and what the cuts of every
+/// entrant add up to; rounding dust is left out of both.
+pub fn treasury_reward_shares(game: &Game, pool: u64) -> Result<(u64, u64)> {
+    // Every entrant paid the same entry fee
+    let entrants = game.entrants().len() as u64;
+    let total_wagered = game.entry_fee
+        .checked_mul(entrants)
+        .ok_or(GameError::ArithmeticOverflow)?;
+    let share = TreasuryRewards::wager_share(pool, game.entry_fee, total_wagered)?;
+    let total = share.checked_mul(entrants).ok_or(GameError::ArithmeticOverflow)?;
+    Ok((share, total))
+}
+
+/// Credit each entrant's TreasuryRewards with a wager-weighted cut of `pool`,
+/// in `asset`.
+///
+/// `rewards_accounts` must follow `Game::entrants` order. Returns the amount
+/// actually credited; rounding dust is left uncredited.
+pub fn credit_treasury_rewards(
+    game: &Game,
+    rewards_accounts: &[AccountInfo],
+    pool: u64,
+    asset: RewardAsset,
+) -> Result<u64> {
+    let entrants = game.entrants();
+    require!(
+        rewards_accounts.len() == entrants.len(),
+        GameError::InvalidRewardsAccount
+    );
+    let (share, _) = treasury_reward_shares(game, pool)?;
+
+    let mut credited = 0u64;
+    for (account, entrant) in rewards_accounts.iter().zip(entrants.iter()) {
+        // SECURITY: Only our own rewards account for this entrant may be credited
+        require!(
+            account.owner == &crate::ID && account.is_writable,
+            GameError::InvalidRewardsAccount
+        );
+        let mut data = account.try_borrow_mut_data()?;
+        let mut rewards = TreasuryRewards::try_deserialize(&mut &data[..])?;
+        require!(rewards.player == *entrant, GameError::InvalidRewardsAccount);
+
+        match asset {
+            RewardAsset::PlatformMint => rewards.credit(share)?,
+            RewardAsset::Sol => rewards.credit_lamports(share)?,
+        }
+        rewards.try_serialize(&mut &mut data[..])?;
+        credited = credited.checked_add(share).ok_or(GameError::ArithmeticOverflow)?;
+    }
+
+    Ok(credited)
 }
@@ -1,19 +1,22 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::{transfer, Transfer};
-use crate::{errors::GameError, state::*};
+use crate::{errors::GameError, settlement::*, state::*, token::GameTreasury};
+use super::finalize::{credit_treasury_rewards, record_player_stats, split_entrant_accounts};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct FinalizeGameSol<'info> {
     #[account(
         mut,
-        seeds = [b"game", game.game_id.to_le_bytes().as_ref()],
-        bump = game.bump
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
+        bump = game.bump,
+        close = creator
     )]
     pub game: Account<'info, Game>,
     
+    /// Platform config, also holds the SOL credited to players as rewards
     #[account(
         mut,
-        seeds = [b"platform"],
+        seeds = [PlatformConfig::SEED],
         bump = platform_config.bump
     )]
     pub platform_config: Account<'info, PlatformConfig>,
@@ -21,20 +24,28 @@ pub struct FinalizeGameSol<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     
+    /// Game creator (receives the vault and game account rent)
+    /// CHECK: Validated against game.creator
+    #[account(
+        mut,
+        constraint = creator.key() == game.creator @ GameError::Unauthorized
+    )]
+    pub creator: AccountInfo<'info>,
+    
     /// Game vault PDA - holds SOL for this game
     /// CHECK: PDA for holding SOL
     #[account(
         mut,
-        seeds = [b"game_vault", game.key().as_ref()],
+        seeds = [Game::VAULT_SEED, game.key().as_ref()],
         bump
     )]
     pub game_vault: AccountInfo<'info>,
     
-    /// Platform authority wallet (receives platform fee)
+    /// Platform fee recipient: the authority wallet, or Squads vault 0
     /// CHECK: Validated against platform_config
     #[account(
         mut,
-        constraint = platform_authority.key() == platform_config.authority @ GameError::Unauthorized
+        constraint = platform_authority.key() == platform_config.platform_fee_recipient() @ GameError::Unauthorized
     )]
     pub platform_authority: AccountInfo<'info>,
     
@@ -56,180 +67,99 @@ pub struct FinalizeGameSol<'info> {
     pub winner2: AccountInfo<'info>,
     
     pub system_program: Program<'info, System>,
+    // remaining_accounts: PlayerStats PDAs of every entrant, in seat order,
+    // followed by their TreasuryRewards PDAs when the platform shares
+    // treasury fees with players
 }
 
 pub fn finalize_game_sol(ctx: Context<FinalizeGameSol>) -> Result<()> {
     let game = &mut ctx.accounts.game;
-    let platform_config = &ctx.accounts.platform_config;
-    
-    // SECURITY: Validate game status
-    require!(
-        game.status == GameStatus::Finished,
-        GameError::GameNotInProgress
-    );
-    
-    require!(
-        game.winner_team.is_some(),
-        GameError::GameNotInProgress
-    );
+    let winners = finished_game_winners(game)?;
+    let winning_team = game.winner_team.unwrap();
     
     // SECURITY: Skip distribution for practice mode (AI games)
     if game.is_practice_mode {
-        game.status = GameStatus::Cancelled;
+        game.transition(GameStatus::Settled)?;
+        emit_cpi!(PayoutPlan::default().finalized_event(game.key(), game.game_id, winning_team));
         msg!("🎮 Practice game finished - no prizes distributed");
-        msg!("Winner: Team {}", game.winner_team.unwrap());
+        msg!("Winner: Team {}", winning_team);
         return Ok(());
     }
     
-    // SECURITY: Validate winner accounts match actual game participants
-    let winning_team = game.winner_team.unwrap();
-    let expected_winner1 = if winning_team == 0 {
-        game.team_a[0]
-    } else {
-        game.team_b[0]
-    };
-    
-    require!(
-        ctx.accounts.winner1.key() == expected_winner1,
-        GameError::InvalidWinner
-    );
+    // SECURITY: Borrowed entry fees are repaid by finalize_game_with_loan
+    require!(!game.has_loan(), GameError::InvalidGameMode);
     
-    // Get winner count
-    let winner_count = if winning_team == 0 {
-        game.team_a_count as usize
-    } else {
-        game.team_b_count as usize
-    };
-    
-    // Validate winner2 for 2v2
-    if winner_count == 2 {
-        let expected_winner2 = if winning_team == 0 {
-            game.team_a[1]
-        } else {
-            game.team_b[1]
-        };
-        
-        require!(
-            ctx.accounts.winner2.key() == expected_winner2,
-            GameError::InvalidWinner
-        );
-    }
+    // SECURITY: Validate winner accounts match actual game participants
+    require_winner_accounts(
+        &winners,
+        &[ctx.accounts.winner1.key(), ctx.accounts.winner2.key()],
+    )?;
     
     // Calculate prize distribution
-    let total_pot = game.total_pot;
-    
-    let platform_fee = (total_pot as u128)
-        .checked_mul(platform_config.platform_fee_bps as u128)
-        .ok_or(GameError::ArithmeticOverflow)?
-        .checked_div(10000)
-        .ok_or(GameError::ArithmeticOverflow)? as u64;
-    
-    let treasury_fee = (total_pot as u128)
-        .checked_mul(platform_config.treasury_fee_bps as u128)
-        .ok_or(GameError::ArithmeticOverflow)?
-        .checked_div(10000)
-        .ok_or(GameError::ArithmeticOverflow)? as u64;
-    
-    let winner_amount = total_pot
-        .checked_sub(platform_fee)
-        .ok_or(GameError::ArithmeticOverflow)?
-        .checked_sub(treasury_fee)
-        .ok_or(GameError::ArithmeticOverflow)?;
-    
-    let per_winner = winner_amount / winner_count as u64;
+    let platform_config = &mut ctx.accounts.platform_config;
+    let mut plan = PayoutPlan::new(
+        game.total_pot,
+        platform_config.platform_fee_bps,
+        platform_config.treasury_fee_bps,
+        winners.len(),
+    )?;
     
     // Game vault PDA signer
     let game_key = game.key();
     let bump = ctx.bumps.game_vault;
     let seeds = &[
-        b"game_vault",
+        Game::VAULT_SEED,
         game_key.as_ref(),
         &[bump],
     ];
     let signer = &[&seeds[..]];
     
-    // SECURITY: Verify vault has enough SOL (including rent exemption)
-    let vault_balance = ctx.accounts.game_vault.lamports();
-    let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
-    
-    require!(
-        vault_balance >= total_pot + rent_exempt_minimum,
-        GameError::InsufficientVaultBalance
+    let treasury = GameTreasury::sol(
+        ctx.accounts.game_vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        signer,
     );
+    treasury.require_funded(plan.total_pot)?;
     
     msg!("💰 Finalizing game {}", game.game_id);
-    msg!("   Total pot: {} SOL", total_pot as f64 / 1_000_000_000.0);
-    msg!("   Platform fee: {} SOL", platform_fee as f64 / 1_000_000_000.0);
-    msg!("   Treasury fee: {} SOL", treasury_fee as f64 / 1_000_000_000.0);
-    msg!("   Winner amount: {} SOL", winner_amount as f64 / 1_000_000_000.0);
-    msg!("   Per winner: {} SOL", per_winner as f64 / 1_000_000_000.0);
+    msg!("   Total pot: {} SOL", plan.total_pot as f64 / 1_000_000_000.0);
+    msg!("   Platform fee: {} SOL", plan.platform_fee as f64 / 1_000_000_000.0);
+    msg!("   Treasury fee: {} SOL", plan.treasury_fee as f64 / 1_000_000_000.0);
+    msg!("   Per winner: {} SOL", plan.per_winner as f64 / 1_000_000_000.0);
     
-    // EFFECTS: Update state before interactions
-    let platform_config = &mut ctx.accounts.platform_config;
-    platform_config.total_volume = platform_config.total_volume
-        .checked_add(total_pot)
-        .ok_or(GameError::ArithmeticOverflow)?;
-    
-    platform_config.treasury_balance = platform_config.treasury_balance
-        .checked_add(treasury_fee)
-        .ok_or(GameError::ArithmeticOverflow)?;
-    
-    game.status = GameStatus::Cancelled;
-    game.finished_at = Some(Clock::get()?.unix_timestamp);
-    
-    // INTERACTIONS: Distribute funds
-    // Distribute to platform
-    transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.game_vault.to_account_info(),
-                to: ctx.accounts.platform_authority.to_account_info(),
-            },
-            signer,
-        ),
-        platform_fee,
-    )?;
+    // Hand part of the treasury fee back to the players, held in lamports
+    // by the platform config until they claim it
+    let rewards_pool = TreasuryRewards::reward_pool(plan.treasury_fee, platform_config.rewards_share_bps)?;
+    let (stats_accounts, rewards_accounts, _) =
+        split_entrant_accounts(game, ctx.remaining_accounts, rewards_pool > 0)?;
     
-    // Distribute to treasury
-    transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.game_vault.to_account_info(),
-                to: ctx.accounts.treasury.to_account_info(),
-            },
-            signer,
-        ),
-        treasury_fee,
+    // EFFECTS: Update state before interactions
+    if rewards_pool > 0 {
+        let credited = credit_treasury_rewards(game, rewards_accounts, rewards_pool, RewardAsset::Sol)?;
+        plan = plan.with_player_rewards(credited)?;
+    }
+    record_settlement(game, platform_config, &plan)?;
+    record_player_stats(game, stats_accounts, plan.per_winner)?;
+    
+    // INTERACTIONS: Fund the credited rewards, then distribute to platform,
+    // treasury and winner(s)
+    let rewards_vault = ctx.accounts.platform_config.to_account_info();
+    let winner_accounts = [
+        ctx.accounts.winner1.to_account_info(),
+        ctx.accounts.winner2.to_account_info(),
+    ];
+    treasury.execute(
+        &plan,
+        &ctx.accounts.platform_authority,
+        &ctx.accounts.treasury,
+        Some(&rewards_vault),
+        &winner_accounts[..winners.len()],
     )?;
     
-    // Distribute to winner(s)
-    transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.game_vault.to_account_info(),
-                to: ctx.accounts.winner1.to_account_info(),
-            },
-            signer,
-        ),
-        per_winner,
-    )?;
+    // Return the vault's rent to the creator; the game account follows on exit
+    treasury.close(&ctx.accounts.creator)?;
     
-    if winner_count == 2 {
-        transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.system_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.game_vault.to_account_info(),
-                    to: ctx.accounts.winner2.to_account_info(),
-                },
-                signer,
-            ),
-            per_winner,
-        )?;
-    }
+    emit_cpi!(plan.finalized_event(game.key(), game.game_id, winning_team));
     
     msg!("✅ Game finalized successfully!");
     msg!("🎉 Prizes distributed!");
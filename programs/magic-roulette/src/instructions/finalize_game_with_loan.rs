@@ -1,66 +1,68 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::{transfer, Transfer};
-use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
-use crate::{errors::GameError, state::*, constants::KAMINO_PROGRAM_ID};
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed, sysvar};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::spl_token::native_mint;
+use crate::{errors::GameError, events::*, kamino, settlement::*, state::*, constants::KAMINO_PROGRAM_ID, token::{GameTreasury, VaultWsol}};
+use super::finalize::{credit_treasury_rewards, record_player_stats, split_entrant_accounts};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct FinalizeGameWithLoan<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
+        bump = game.bump,
+        close = creator
+    )]
     pub game: Account<'info, Game>,
     
+    /// Platform config, also holds the SOL credited to players as rewards
     #[account(
         mut,
-        seeds = [b"platform"],
+        seeds = [PlatformConfig::SEED],
         bump = platform_config.bump
     )]
     pub platform_config: Account<'info, PlatformConfig>,
     
+    /// Game creator (receives the vault and game account rent)
+    /// CHECK: Validated against game.creator
+    #[account(
+        mut,
+        constraint = creator.key() == game.creator @ GameError::Unauthorized
+    )]
+    pub creator: AccountInfo<'info>,
+    
     /// Game vault PDA
     /// CHECK: PDA validated by seeds
     #[account(
         mut,
-        seeds = [b"game_vault", game.key().as_ref()],
+        seeds = [Game::VAULT_SEED, game.key().as_ref()],
         bump
     )]
     pub game_vault: AccountInfo<'info>,
     
-    // Kamino accounts (optional - only if game has loan)
-    /// CHECK: Kamino lending market
-    pub lending_market: AccountInfo<'info>,
-    
-    /// CHECK: Kamino lending market authority
-    pub lending_market_authority: AccountInfo<'info>,
-    
-    /// CHECK: Kamino reserve
-    #[account(mut)]
-    pub reserve: AccountInfo<'info>,
-    
-    /// CHECK: Reserve liquidity supply
-    #[account(mut)]
-    pub reserve_liquidity_supply: AccountInfo<'info>,
-    
-    /// CHECK: Reserve collateral mint
-    #[account(mut)]
-    pub reserve_collateral_mint: AccountInfo<'info>,
-    
-    /// CHECK: Player's obligation
-    #[account(mut)]
-    pub obligation: AccountInfo<'info>,
-    
-    /// CHECK: Obligation collateral account
-    #[account(mut)]
-    pub obligation_collateral: AccountInfo<'info>,
-    
-    /// CHECK: Player's collateral token account
-    #[account(mut)]
-    pub player_collateral_account: AccountInfo<'info>,
+    /// Game vault's wrapped SOL account, open only while loans are repaid
+    /// CHECK: Address derived from the game vault
+    #[account(
+        mut,
+        address = VaultWsol::new(&game_vault.key()).account @ GameError::InvalidVaultOwner
+    )]
+    pub vault_wsol: AccountInfo<'info>,
     
     /// CHECK: Kamino program - validated against known program ID
     #[account(
-        constraint = kamino_program.key() == KAMINO_PROGRAM_ID @ GameError::InvalidKaminoMarket
+        constraint = kamino_program.key() == KAMINO_PROGRAM_ID @ GameError::InvalidKaminoProgram
     )]
     pub kamino_program: AccountInfo<'info>,
     
+    /// CHECK: Liquidity mint of the SOL reserve, wrapped SOL
+    #[account(address = native_mint::ID @ GameError::InvalidMint)]
+    pub reserve_liquidity_mint: AccountInfo<'info>,
+    
+    /// CHECK: Instructions sysvar, where Kamino checks for its refresh instructions
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+    
     // Winner accounts
     /// CHECK: Winner 1 (validated in instruction logic)
     #[account(mut)]
@@ -70,347 +72,326 @@ pub struct FinalizeGameWithLoan<'info> {
     #[account(mut)]
     pub winner2: AccountInfo<'info>,
     
-    // Platform vaults (can be regular PDAs or Squads vaults)
-    /// CHECK: Platform fee vault
-    #[account(mut)]
+    // Fee recipients, the same wallets finalize_game_sol pays
+    /// Platform fee recipient: the authority wallet, or Squads vault 0
+    /// CHECK: Validated against platform_config
+    #[account(
+        mut,
+        constraint = platform_vault.key() == platform_config.platform_fee_recipient() @ GameError::Unauthorized
+    )]
     pub platform_vault: AccountInfo<'info>,
     
-    /// CHECK: Treasury vault
-    #[account(mut)]
+    /// Treasury wallet (Squads vault 1 on multisig platforms)
+    /// CHECK: Validated against platform_config
+    #[account(
+        mut,
+        constraint = treasury_vault.key() == platform_config.treasury @ GameError::Unauthorized
+    )]
     pub treasury_vault: AccountInfo<'info>,
     
     pub token_program: Program<'info, anchor_spl::token::Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
+    // remaining_accounts: PlayerStats PDAs of every entrant, in seat order,
+    // then their TreasuryRewards PDAs when the platform shares treasury fees
+    // with players, then the BorrowerLoan accounts of every borrower, in
+    // seat order
 }
 
-pub fn finalize_game_with_loan(ctx: Context<FinalizeGameWithLoan>) -> Result<()> {
+pub fn finalize_game_with_loan<'info>(
+    ctx: Context<'_, '_, 'info, 'info, FinalizeGameWithLoan<'info>>,
+) -> Result<()> {
     let game = &mut ctx.accounts.game;
-    let platform_config = &ctx.accounts.platform_config;
-    
-    // SECURITY: Validate game status
-    require!(
-        game.status == GameStatus::Finished,
-        GameError::GameNotInProgress
-    );
-    
-    require!(
-        game.winner_team.is_some(),
-        GameError::GameNotInProgress
-    );
+    let winners = finished_game_winners(game)?;
+    let winning_team = game.winner_team.unwrap();
     
     // SECURITY: Skip distribution for practice mode (AI games)
     if game.is_practice_mode {
-        game.status = GameStatus::Cancelled;
+        game.transition(GameStatus::Settled)?;
+        emit_cpi!(PayoutPlan::default().finalized_event(game.key(), game.game_id, winning_team));
         msg!("🎮 Practice game finished - no prizes distributed");
-        msg!("Winner: Team {}", game.winner_team.unwrap());
+        msg!("Winner: Team {}", winning_team);
         return Ok(());
     }
     
     // Validate winner accounts match actual game participants
-    let winning_team = game.winner_team.unwrap();
-    let expected_winner1 = if winning_team == 0 {
-        game.team_a[0]
-    } else {
-        game.team_b[0]
-    };
-    
-    require!(
-        ctx.accounts.winner1.key() == expected_winner1,
-        GameError::InvalidWinner
-    );
+    require_winner_accounts(
+        &winners,
+        &[ctx.accounts.winner1.key(), ctx.accounts.winner2.key()],
+    )?;
     
     // Calculate prize distribution
-    let total_pot = game.total_pot;
-    
-    // Verify vault has enough SOL (including rent exemption)
-    let vault_balance = ctx.accounts.game_vault.lamports();
-    let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
-    
-    require!(
-        vault_balance >= total_pot + rent_exempt_minimum,
-        GameError::InsufficientVaultBalance
-    );
-    
-    let platform_fee = (total_pot as u128)
-        .checked_mul(platform_config.platform_fee_bps as u128)
-        .ok_or(GameError::ArithmeticOverflow)?
-        .checked_div(10000)
-        .ok_or(GameError::ArithmeticOverflow)? as u64;
-    
-    let treasury_fee = (total_pot as u128)
-        .checked_mul(platform_config.treasury_fee_bps as u128)
-        .ok_or(GameError::ArithmeticOverflow)?
-        .checked_div(10000)
-        .ok_or(GameError::ArithmeticOverflow)? as u64;
-    
-    let mut winner_amount = total_pot
-        .checked_sub(platform_fee)
-        .ok_or(GameError::ArithmeticOverflow)?
-        .checked_sub(treasury_fee)
-        .ok_or(GameError::ArithmeticOverflow)?;
-    
-    // Get winner count
-    let winner_count = if winning_team == 0 {
-        game.team_a_count as usize
-    } else {
-        game.team_b_count as usize
-    };
+    let platform_config = &mut ctx.accounts.platform_config;
+    let mut plan = PayoutPlan::new(
+        game.total_pot,
+        platform_config.platform_fee_bps,
+        platform_config.treasury_fee_bps,
+        winners.len(),
+    )?;
     
-    // Validate winner2 for 2v2
-    if winner_count == 2 {
-        let expected_winner2 = if winning_team == 0 {
-            game.team_a[1]
-        } else {
-            game.team_b[1]
-        };
-        
-        require!(
-            ctx.accounts.winner2.key() == expected_winner2,
-            GameError::InvalidWinner
-        );
+    // Part of the treasury fee goes back to the players, held in lamports
+    // by the platform config until they claim it
+    let rewards_pool = TreasuryRewards::reward_pool(plan.treasury_fee, platform_config.rewards_share_bps)?;
+    let (stats_accounts, rewards_accounts, loan_accounts) =
+        split_entrant_accounts(game, ctx.remaining_accounts, rewards_pool > 0)?;
+    let mut loans = BorrowerLoan::load_all(&game.key(), &game.borrowers(), loan_accounts)?;
+    
+    // A winning borrower repays their loan out of their own winnings. A
+    // losing borrower's debt stays on their LoanPosition until they repay it
+    // through settle_loan
+    for loan in loans.iter_mut() {
+        if let Some(winner) = winners.iter().position(|winner| *winner == loan.borrower) {
+            let repaid = loan.cover(plan.winner_payout(winner));
+            plan = plan.with_loan_repayment(winner, repaid)?;
+        }
     }
     
     // Game vault PDA signer
     let game_key = game.key();
     let seeds = &[
-        b"game_vault",
+        Game::VAULT_SEED,
         game_key.as_ref(),
         &[ctx.bumps.game_vault],
     ];
     let signer = &[&seeds[..]];
     
-    // Handle Kamino loan repayment if applicable
-    if game.has_loan {
-        let loan_amount = game.loan_amount;
-        
-        // Calculate interest (simplified - in production, query Kamino obligation)
-        let loan_interest = loan_amount / 100; // 1% interest for demo
-        let total_repayment = loan_amount + loan_interest;
-        
-        // Check if winner is the borrower
-        let winner_is_borrower = (winning_team == 0 && game.team_a[0] == game.creator)
-            || (winning_team == 1 && game.team_b[0] == game.creator);
-        
-        if winner_is_borrower {
-            // Winner borrowed - repay from winnings
-            require!(
-                winner_amount >= total_repayment,
-                GameError::InsufficientWinningsForRepayment
-            );
-            
-            msg!("💰 Repaying Kamino loan...");
-            msg!("   Loan amount: {} SOL", loan_amount as f64 / 1e9);
-            msg!("   Interest: {} SOL", loan_interest as f64 / 1e9);
-            msg!("   Total repayment: {} SOL", total_repayment as f64 / 1e9);
-            
-            // STEP 1: Repay loan to Kamino
-            let repay_ix = build_kamino_repay_ix(
-                ctx.accounts.lending_market.key(),
-                ctx.accounts.reserve.key(),
-                ctx.accounts.reserve_liquidity_supply.key(),
-                ctx.accounts.obligation.key(),
-                ctx.accounts.game_vault.key(),
-                ctx.accounts.winner1.key(),
-                total_repayment,
-            );
-            
-            invoke_signed(
-                &repay_ix,
-                &[
-                    ctx.accounts.winner1.to_account_info(),
-                    ctx.accounts.obligation.to_account_info(),
-                    ctx.accounts.lending_market.to_account_info(),
-                    ctx.accounts.reserve.to_account_info(),
-                    ctx.accounts.reserve_liquidity_supply.to_account_info(),
-                    ctx.accounts.game_vault.to_account_info(),
-                    ctx.accounts.token_program.to_account_info(),
-                ],
-                signer,
-            )?;
-            
-            msg!("✅ Loan repaid to Kamino");
-            
-            // STEP 2: Withdraw collateral back to winner
-            let withdraw_ix = build_kamino_withdraw_collateral_ix(
-                ctx.accounts.lending_market.key(),
-                ctx.accounts.reserve.key(),
-                ctx.accounts.obligation.key(),
-                ctx.accounts.obligation_collateral.key(),
-                ctx.accounts.reserve_collateral_mint.key(),
-                ctx.accounts.player_collateral_account.key(),
-                ctx.accounts.winner1.key(),
-                game.collateral_amount,
-            );
-            
-            invoke_signed(
-                &withdraw_ix,
-                &[
-                    ctx.accounts.winner1.to_account_info(),
-                    ctx.accounts.obligation.to_account_info(),
-                    ctx.accounts.lending_market.to_account_info(),
-                    ctx.accounts.reserve.to_account_info(),
-                    ctx.accounts.obligation_collateral.to_account_info(),
-                    ctx.accounts.reserve_collateral_mint.to_account_info(),
-                    ctx.accounts.player_collateral_account.to_account_info(),
-                    ctx.accounts.token_program.to_account_info(),
-                ],
-                &[],
-            )?;
-            
-            msg!("✅ Collateral returned to winner");
+    let treasury = GameTreasury::sol(
+        ctx.accounts.game_vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        signer,
+    );
+    treasury.require_funded(plan.total_pot)?;
+    
+    // EFFECTS: Update state before interactions
+    if rewards_pool > 0 {
+        let credited = credit_treasury_rewards(game, rewards_accounts, rewards_pool, RewardAsset::Sol)?;
+        plan = plan.with_player_rewards(credited)?;
+    }
+    record_settlement(game, platform_config, &plan)?;
+    record_player_stats(game, stats_accounts, plan.per_winner)?;
+    for loan in loans.iter_mut() {
+        loan.record()?;
+    }
+    
+    // INTERACTIONS: Settle every Kamino loan first, then distribute the rest
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let associated_token_program = ctx.accounts.associated_token_program.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let repay_accounts = VaultRepayAccounts {
+        game_vault: &ctx.accounts.game_vault,
+        vault_wsol: &ctx.accounts.vault_wsol,
+        reserve_liquidity_mint: &ctx.accounts.reserve_liquidity_mint,
+        kamino_program: &ctx.accounts.kamino_program,
+        token_program: &token_program,
+        associated_token_program: &associated_token_program,
+        system_program: &system_program,
+        instruction_sysvar: &ctx.accounts.instruction_sysvar,
+    };
+    for loan in &loans {
+        if loan.repaid > 0 {
+            msg!("💰 Repaying {} SOL of {}'s Kamino loan from their winnings", loan.repaid as f64 / 1e9, loan.borrower);
+            loan.repay_from_vault(&repay_accounts, signer)?;
             
-            // Reduce winner amount by repayment
-            winner_amount = winner_amount
-                .checked_sub(total_repayment)
-                .ok_or(GameError::ArithmeticOverflow)?;
+            emit_cpi!(LoanSettled {
+                game: game_key,
+                game_id: game.game_id,
+                borrower: loan.borrower,
+                amount_repaid: loan.repaid,
+                collateral_returned: false,
+            });
+        }
+        if loan.outstanding() > 0 {
+            msg!("❌ Borrower {} owes {} SOL to Kamino until settle_loan", loan.borrower, loan.outstanding() as f64 / 1e9);
             
-            msg!("💰 Net winnings: {} SOL", winner_amount as f64 / 1e9);
-        } else {
-            // Borrower lost - collateral liquidated by Kamino
-            msg!("❌ Borrower lost - collateral liquidated by Kamino");
-            msg!("   Collateral amount: {} SOL", game.collateral_amount as f64 / 1e9);
+            emit_cpi!(LoanOutstanding {
+                game: game_key,
+                game_id: game.game_id,
+                borrower: loan.borrower,
+                obligation: loan.obligation.key(),
+                debt: loan.outstanding(),
+            });
         }
     }
     
-    let per_winner = winner_amount / winner_count as u64;
-    
-    // Distribute to platform vault
-    transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.game_vault.to_account_info(),
-                to: ctx.accounts.platform_vault.to_account_info(),
-            },
-            signer,
-        ),
-        platform_fee,
-    )?;
-    
-    // Distribute to treasury vault
-    transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.game_vault.to_account_info(),
-                to: ctx.accounts.treasury_vault.to_account_info(),
-            },
-            signer,
-        ),
-        treasury_fee,
-    )?;
-    
-    // Distribute to winner(s)
-    transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.game_vault.to_account_info(),
-                to: ctx.accounts.winner1.to_account_info(),
-            },
-            signer,
-        ),
-        per_winner,
+    let rewards_vault = ctx.accounts.platform_config.to_account_info();
+    let winner_accounts = [
+        ctx.accounts.winner1.to_account_info(),
+        ctx.accounts.winner2.to_account_info(),
+    ];
+    treasury.execute(
+        &plan,
+        &ctx.accounts.platform_vault,
+        &ctx.accounts.treasury_vault,
+        Some(&rewards_vault),
+        &winner_accounts[..winners.len()],
     )?;
     
-    if winner_count == 2 {
-        transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.system_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.game_vault.to_account_info(),
-                    to: ctx.accounts.winner2.to_account_info(),
-                },
-                signer,
-            ),
-            per_winner,
-        )?;
-    }
-    
-    // Update platform stats
-    platform_config.total_volume
-        .checked_add(total_pot)
-        .ok_or(GameError::ArithmeticOverflow)?;
+    // Return the vault's rent to the creator; the game account follows on exit
+    treasury.close(&ctx.accounts.creator)?;
     
-    platform_config.treasury_balance
-        .checked_add(treasury_fee)
-        .ok_or(GameError::ArithmeticOverflow)?;
-    
-    // Mark game as processed
-    game.status = GameStatus::Cancelled;
+    emit_cpi!(plan.finalized_event(game.key(), game.game_id, winning_team));
     
     msg!("🏆 Game {} finalized", game.game_id);
-    msg!("   Each winner receives: {} SOL", per_winner as f64 / 1e9);
-    msg!("   Platform fee: {} SOL", platform_fee as f64 / 1e9);
-    msg!("   Treasury fee: {} SOL", treasury_fee as f64 / 1e9);
+    msg!("   Each winner receives: {} SOL", plan.per_winner as f64 / 1e9);
+    msg!("   Loans repaid: {} SOL", plan.loan_repayment as f64 / 1e9);
+    msg!("   Platform fee: {} SOL", plan.platform_fee as f64 / 1e9);
+    msg!("   Treasury fee: {} SOL", plan.treasury_fee as f64 / 1e9);
     
     Ok(())
 }
 
-// ============================================================================
-// KAMINO CPI HELPER FUNCTIONS
-// ============================================================================
+/// One borrower's loan, passed in remaining_accounts as
+/// `[loan_position, lending_market, reserve, reserve_liquidity_supply, obligation]`.
+///
+/// The reserve must be refreshed in the same transaction. Settling a game
+/// repays what it can of `debt` out of the vault; whatever is left stays on
+/// the position for `settle_loan`.
+pub struct BorrowerLoan<'info> {
+    pub borrower: Pubkey,
+    /// What this loan owes now, principal plus accrued interest, never
+    /// counting other debt the obligation carries
+    pub debt: u64,
+    /// Reserve's cumulative borrow rate `debt` was accrued to
+    pub borrow_rate_bsf: u128,
+    /// Part of `debt` repaid out of the game vault
+    pub repaid: u64,
+    pub position: Account<'info, LoanPosition>,
+    pub lending_market: &'info AccountInfo<'info>,
+    pub reserve: &'info AccountInfo<'info>,
+    pub reserve_liquidity_supply: &'info AccountInfo<'info>,
+    pub obligation: &'info AccountInfo<'info>,
+}
 
-/// Build Kamino RepayObligationLiquidity instruction
-fn build_kamino_repay_ix(
-    lending_market: Pubkey,
-    reserve: Pubkey,
-    reserve_liquidity_supply: Pubkey,
-    obligation: Pubkey,
-    user_source: Pubkey,
-    owner: Pubkey,
-    amount: u64,
-) -> Instruction {
-    use anchor_lang::solana_program::instruction::AccountMeta;
-    
-    // Kamino RepayObligationLiquidity instruction discriminator
-    let mut data = vec![0x84, 0x3e, 0x4c, 0x8b, 0x1f, 0x2d, 0x6a, 0x9f];
-    data.extend_from_slice(&amount.to_le_bytes());
-    
-    Instruction {
-        program_id: KAMINO_PROGRAM_ID,
-        accounts: vec![
-            AccountMeta::new_readonly(owner, true),
-            AccountMeta::new(obligation, false),
-            AccountMeta::new_readonly(lending_market, false),
-            AccountMeta::new(reserve, false),
-            AccountMeta::new(reserve_liquidity_supply, false),
-            AccountMeta::new(user_source, false),
-            AccountMeta::new_readonly(anchor_spl::token::ID, false),
-        ],
-        data,
+impl<'info> BorrowerLoan<'info> {
+    pub const ACCOUNTS: usize = 5;
+    
+    /// Open loans of `borrowers` in `game`, one group of accounts each
+    pub fn load_all(
+        game: &Pubkey,
+        borrowers: &[Pubkey],
+        accounts: &'info [AccountInfo<'info>],
+    ) -> Result<Vec<Self>> {
+        require!(
+            accounts.len() == borrowers.len() * Self::ACCOUNTS,
+            GameError::InvalidLoanPosition
+        );
+        
+        borrowers
+            .iter()
+            .zip(accounts.chunks_exact(Self::ACCOUNTS))
+            .map(|(borrower, accounts)| Self::load(game, borrower, accounts))
+            .collect()
+    }
+    
+    fn load(game: &Pubkey, borrower: &Pubkey, accounts: &'info [AccountInfo<'info>]) -> Result<Self> {
+        // SECURITY: Only this game's open position for this borrower, and
+        // the Kamino accounts it was opened with
+        require!(accounts[0].is_writable, GameError::InvalidLoanPosition);
+        let position = Account::<LoanPosition>::try_from(&accounts[0])?;
+        require!(
+            position.game == *game
+                && position.borrower == *borrower
+                && position.status == LoanStatus::Open,
+            GameError::InvalidLoanPosition
+        );
+        position.require_kamino_accounts(accounts[1].key, accounts[2].key, accounts[4].key)?;
+        let (debt, borrow_rate_bsf) = position.debt(&accounts[4], &accounts[2])?;
+        
+        Ok(Self {
+            borrower: *borrower,
+            debt,
+            borrow_rate_bsf,
+            repaid: 0,
+            position,
+            lending_market: &accounts[1],
+            reserve: &accounts[2],
+            reserve_liquidity_supply: &accounts[3],
+            obligation: &accounts[4],
+        })
+    }
+    
+    /// Repay as much of the debt as `available` covers, returning that amount
+    pub fn cover(&mut self, available: u64) -> u64 {
+        self.repaid = self.debt.min(available);
+        self.repaid
+    }
+    
+    /// Debt left for the borrower to repay through settle_loan
+    pub fn outstanding(&self) -> u64 {
+        self.debt - self.repaid
+    }
+    
+    /// EFFECTS: Mark the position Repaid, or Outstanding if any debt is
+    /// left, and keep what is left as its principal for settle_loan
+    pub fn record(&mut self) -> Result<()> {
+        self.position.loan_amount = self.outstanding();
+        self.position.borrow_rate_bsf = self.borrow_rate_bsf;
+        self.position.status = if self.outstanding() == 0 {
+            LoanStatus::Repaid
+        } else {
+            LoanStatus::Outstanding
+        };
+        self.position.exit(&crate::ID)
+    }
+    
+    /// Kamino instructions repaying the covered part of the debt out of the
+    /// game vault, through its wSOL account
+    pub fn repay_instructions(
+        &self,
+        wsol: &VaultWsol,
+        reserve_liquidity_mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Result<Vec<Instruction>> {
+        if self.repaid == 0 {
+            return Ok(Vec::new());
+        }
+        
+        let repay_ix = kamino::RepayObligationLiquidity {
+            owner: wsol.vault,
+            obligation: self.obligation.key(),
+            lending_market: self.lending_market.key(),
+            repay_reserve: self.reserve.key(),
+            reserve_liquidity_mint: *reserve_liquidity_mint,
+            reserve_destination_liquidity: self.reserve_liquidity_supply.key(),
+            user_source_liquidity: wsol.account,
+            token_program: *token_program,
+        }
+        .instruction(self.repaid);
+        wsol.pay_with(self.repaid, repay_ix)
+    }
+    
+    /// INTERACTIONS: Pay the covered part of the debt out of the game vault
+    pub fn repay_from_vault(&self, accounts: &VaultRepayAccounts<'_, 'info>, signer: &[&[&[u8]]]) -> Result<()> {
+        let wsol = VaultWsol::new(accounts.game_vault.key);
+        let infos = [
+            accounts.game_vault.clone(),
+            accounts.vault_wsol.clone(),
+            self.obligation.clone(),
+            self.lending_market.clone(),
+            self.reserve.clone(),
+            accounts.reserve_liquidity_mint.clone(),
+            self.reserve_liquidity_supply.clone(),
+            accounts.kamino_program.clone(),
+            accounts.token_program.clone(),
+            accounts.associated_token_program.clone(),
+            accounts.system_program.clone(),
+            accounts.instruction_sysvar.clone(),
+        ];
+        for ix in self.repay_instructions(&wsol, accounts.reserve_liquidity_mint.key, accounts.token_program.key)? {
+            invoke_signed(&ix, &infos, signer)?;
+        }
+        Ok(())
     }
 }
 
-/// Build Kamino WithdrawObligationCollateral instruction
-fn build_kamino_withdraw_collateral_ix(
-    lending_market: Pubkey,
-    reserve: Pubkey,
-    obligation: Pubkey,
-    obligation_collateral: Pubkey,
-    reserve_collateral_mint: Pubkey,
-    user_destination: Pubkey,
-    owner: Pubkey,
-    amount: u64,
-) -> Instruction {
-    use anchor_lang::solana_program::instruction::AccountMeta;
-    
-    // Kamino WithdrawObligationCollateral instruction discriminator
-    let mut data = vec![0x3a, 0x7f, 0x1d, 0x9e, 0x4b, 0x2c, 0x8f, 0x6d];
-    data.extend_from_slice(&amount.to_le_bytes());
-    
-    Instruction {
-        program_id: KAMINO_PROGRAM_ID,
-        accounts: vec![
-            AccountMeta::new_readonly(owner, true),
-            AccountMeta::new(obligation, false),
-            AccountMeta::new_readonly(lending_market, false),
-            AccountMeta::new(reserve, false),
-            AccountMeta::new(obligation_collateral, false),
-            AccountMeta::new(reserve_collateral_mint, false),
-            AccountMeta::new(user_destination, false),
-            AccountMeta::new_readonly(anchor_spl::token::ID, false),
-        ],
-        data,
-    }
+/// Accounts a game vault repays Kamino loans with
+pub struct VaultRepayAccounts<'a, 'info> {
+    pub game_vault: &'a AccountInfo<'info>,
+    pub vault_wsol: &'a AccountInfo<'info>,
+    pub reserve_liquidity_mint: &'a AccountInfo<'info>,
+    pub kamino_program: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub instruction_sysvar: &'a AccountInfo<'info>,
 }
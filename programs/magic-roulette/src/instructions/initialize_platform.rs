@@ -1,15 +1,14 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_2022::spl_token_2022::extension::StateWithExtensions;
-use anchor_spl::token_2022::spl_token_2022::state::Mint as MintState;
-use crate::{errors::GameError, state::PlatformConfig};
+use crate::{errors::GameError, events::PlatformInitialized, state::PlatformConfig, token, constants::{DEFAULT_TURN_TIMEOUT_SECONDS, DEFAULT_REWARDS_SHARE_BPS, DEFAULT_MIN_COLLATERAL_RATIO_BPS}};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct InitializePlatform<'info> {
     #[account(
         init,
         payer = authority,
         space = PlatformConfig::LEN,
-        seeds = [b"platform"],
+        seeds = [PlatformConfig::SEED],
         bump
     )]
     pub platform_config: Account<'info, PlatformConfig>,
@@ -37,8 +36,14 @@ pub fn initialize_platform(
         GameError::InvalidFeeConfig
     );
     
+    // SECURITY: Platform-token games and rewards must always be payable
+    if ctx.accounts.platform_mint.key() != Pubkey::default() {
+        token::mint_decimals(&ctx.accounts.platform_mint)?;
+    }
+    
     let platform_config = &mut ctx.accounts.platform_config;
     platform_config.authority = ctx.accounts.authority.key();
+    platform_config.pending_authority = None;
     platform_config.treasury = ctx.accounts.treasury.key();
     platform_config.platform_mint = ctx.accounts.platform_mint.key();
     platform_config.platform_fee_bps = platform_fee_bps;
@@ -47,8 +52,20 @@ pub fn initialize_platform(
     platform_config.total_volume = 0;
     platform_config.treasury_balance = 0;
     platform_config.paused = false;
+    platform_config.turn_timeout_seconds = DEFAULT_TURN_TIMEOUT_SECONDS;
+    platform_config.rewards_share_bps = DEFAULT_REWARDS_SHARE_BPS;
+    platform_config.min_collateral_ratio_bps = DEFAULT_MIN_COLLATERAL_RATIO_BPS;
     platform_config.bump = ctx.bumps.platform_config;
     
+    emit_cpi!(PlatformInitialized {
+        authority: platform_config.authority,
+        treasury: platform_config.treasury,
+        platform_mint: platform_config.platform_mint,
+        platform_fee_bps,
+        treasury_fee_bps,
+        multisig_authority: platform_config.multisig_authority,
+    });
+    
     msg!("Platform initialized with {}% platform fee, {}% treasury fee",
         platform_fee_bps / 100,
         treasury_fee_bps / 100
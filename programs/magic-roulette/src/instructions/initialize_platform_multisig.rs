@@ -1,13 +1,14 @@
 use anchor_lang::prelude::*;
-use crate::{errors::GameError, state::*};
+use crate::{errors::GameError, events::PlatformInitialized, squads, state::*, token, constants::{DEFAULT_TURN_TIMEOUT_SECONDS, DEFAULT_REWARDS_SHARE_BPS, DEFAULT_MIN_COLLATERAL_RATIO_BPS, MAX_TOTAL_FEE_BPS, SQUADS_PLATFORM_VAULT_INDEX, SQUADS_TREASURY_VAULT_INDEX}};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct InitializePlatformWithMultisig<'info> {
     #[account(
         init,
         payer = payer,
         space = PlatformConfig::LEN,
-        seeds = [b"platform"],
+        seeds = [PlatformConfig::SEED],
         bump
     )]
     pub platform_config: Account<'info, PlatformConfig>,
@@ -16,15 +17,16 @@ pub struct InitializePlatformWithMultisig<'info> {
     pub payer: Signer<'info>,
     
     /// CHECK: Squads multisig PDA (will be authority)
-    /// This should be created separately using Squads SDK
+    /// This should be created separately using Squads SDK; checked to be
+    /// a Squads v4 multisig
     pub multisig: AccountInfo<'info>,
     
     /// CHECK: Squads vault 0 (platform fees)
-    /// Derived from multisig with index 0
+    /// Checked to derive from multisig with index 0
     pub platform_vault: AccountInfo<'info>,
     
     /// CHECK: Squads vault 1 (treasury)
-    /// Derived from multisig with index 1
+    /// Checked to derive from multisig with index 1
     pub treasury_vault: AccountInfo<'info>,
     
     /// Platform mint (for token-based games)
@@ -44,12 +46,32 @@ pub fn initialize_platform_with_multisig(
         .checked_add(treasury_fee_bps)
         .ok_or(GameError::ArithmeticOverflow)?;
     
-    require!(total_fees <= 2000, GameError::InvalidFeeConfig);
+    require!(total_fees <= MAX_TOTAL_FEE_BPS, GameError::InvalidFeeConfig);
+    
+    // SECURITY: Platform-token games and rewards must always be payable
+    if ctx.accounts.platform_mint.key() != Pubkey::default() {
+        token::mint_decimals(&ctx.accounts.platform_mint)?;
+    }
+    
+    // SECURITY: Fees can only be routed to the multisig's own Squads vaults
+    squads::require_multisig(&ctx.accounts.multisig)?;
+    let multisig = ctx.accounts.multisig.key();
+    require_keys_eq!(
+        ctx.accounts.platform_vault.key(),
+        squads::vault_address(&multisig, SQUADS_PLATFORM_VAULT_INDEX).0,
+        GameError::InvalidMultisig
+    );
+    require_keys_eq!(
+        ctx.accounts.treasury_vault.key(),
+        squads::vault_address(&multisig, SQUADS_TREASURY_VAULT_INDEX).0,
+        GameError::InvalidMultisig
+    );
     
     let config = &mut ctx.accounts.platform_config;
     
     // Set multisig as authority (not a single wallet)
     config.authority = ctx.accounts.multisig.key();
+    config.pending_authority = None;
     config.treasury = ctx.accounts.treasury_vault.key();
     config.platform_mint = ctx.accounts.platform_mint.key();
     config.platform_fee_bps = platform_fee_bps;
@@ -58,6 +80,9 @@ pub fn initialize_platform_with_multisig(
     config.total_volume = 0;
     config.treasury_balance = 0;
     config.paused = false;
+    config.turn_timeout_seconds = DEFAULT_TURN_TIMEOUT_SECONDS;
+    config.rewards_share_bps = DEFAULT_REWARDS_SHARE_BPS;
+    config.min_collateral_ratio_bps = DEFAULT_MIN_COLLATERAL_RATIO_BPS;
     
     // Squads multisig integration
     config.multisig_authority = Some(ctx.accounts.multisig.key());
@@ -66,6 +91,15 @@ pub fn initialize_platform_with_multisig(
     
     config.bump = ctx.bumps.platform_config;
     
+    emit_cpi!(PlatformInitialized {
+        authority: config.authority,
+        treasury: config.treasury,
+        platform_mint: config.platform_mint,
+        platform_fee_bps,
+        treasury_fee_bps,
+        multisig_authority: config.multisig_authority,
+    });
+    
     msg!("🏛️ Platform initialized with Squads multisig");
     msg!("   Authority: {} (Squads Multisig)", config.authority);
     msg!("   Platform vault: {}", ctx.accounts.platform_vault.key());
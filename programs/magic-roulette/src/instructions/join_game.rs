@@ -1,28 +1,14 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::program_pack::Pack;
-use anchor_spl::token_2022::{Token2022, transfer_checked, TransferChecked};
-use anchor_spl::token_2022::spl_token_2022::state::Mint as MintState;
-use anchor_spl::token_2022::spl_token_2022::ID as TOKEN_2022_PROGRAM_ID;
-use crate::{errors::GameError, state::*};
-
-// Helper function to get mint decimals with ownership validation
-fn get_mint_decimals(mint_account: &AccountInfo) -> Result<u8> {
-    // SECURITY: Validate mint is owned by Token-2022 program
-    require!(
-        mint_account.owner == &TOKEN_2022_PROGRAM_ID,
-        GameError::InvalidMint
-    );
-    
-    let mint_data = mint_account.try_borrow_data()?;
-    let mint = MintState::unpack(&mint_data)?;
-    Ok(mint.decimals)
-}
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::TokenAccount;
+use crate::{errors::GameError, events::*, state::*, token::GameTreasury};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct JoinGame<'info> {
     #[account(
         mut,
-        seeds = [b"game", game.game_id.to_le_bytes().as_ref()],
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
         bump = game.bump
     )]
     pub game: Account<'info, Game>,
@@ -30,29 +16,56 @@ pub struct JoinGame<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
     
+    /// Player's lifetime stats, created on their first game
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = PlayerStats::LEN,
+        seeds = [PlayerStats::SEED, player.key().as_ref()],
+        bump
+    )]
+    pub player_stats: Account<'info, PlayerStats>,
+    
+    /// Player's treasury rewards, credited when games settle
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = TreasuryRewards::LEN,
+        seeds = [TreasuryRewards::SEED, player.key().as_ref()],
+        bump
+    )]
+    pub player_rewards: Account<'info, TreasuryRewards>,
+    
     #[account(
-        seeds = [b"platform"],
+        seeds = [PlatformConfig::SEED],
         bump = platform_config.bump,
         constraint = !platform_config.paused @ GameError::PlatformPaused
     )]
     pub platform_config: Account<'info, PlatformConfig>,
     
     // Token-2022 accounts
-    /// CHECK: Token-2022 mint
+    /// CHECK: Token-2022 mint, must be the mint the game was created with
+    #[account(constraint = game.mint == Some(mint.key()) @ GameError::InvalidMint)]
     pub mint: AccountInfo<'info>,
     
     #[account(mut)]
     /// CHECK: Player's token account
     pub player_token_account: AccountInfo<'info>,
     
-    #[account(mut)]
-    /// CHECK: Game vault token account
-    pub game_vault: AccountInfo<'info>,
+    /// Game vault PDA created with the game
+    #[account(
+        mut,
+        address = Game::find_vault_address(&game.key()).0 @ GameError::InvalidVaultOwner,
+        constraint = game_vault.owner == game.key() @ GameError::InvalidVaultOwner
+    )]
+    pub game_vault: Box<InterfaceAccount<'info, TokenAccount>>,
     
     pub token_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: TransferHook accounts, if the mint has a hook
 }
 
-pub fn join_game(ctx: Context<JoinGame>) -> Result<()> {
+pub fn join_game<'info>(ctx: Context<'_, '_, 'info, 'info, JoinGame<'info>>) -> Result<()> {
     let game = &mut ctx.accounts.game;
     let player = ctx.accounts.player.key();
     
@@ -61,59 +74,30 @@ pub fn join_game(ctx: Context<JoinGame>) -> Result<()> {
         GameError::GameNotReady
     );
     
-    require!(
-        player != game.creator,
-        GameError::CannotJoinOwnGame
-    );
-    
-    // SECURITY: Check if player already in game
-    require!(
-        !game.team_a.contains(&player) && !game.team_b.contains(&player),
-        GameError::PlayerAlreadyInGame
-    );
-    
-    // Add player to appropriate team
-    let required_per_team = match game.game_mode {
-        GameMode::OneVsOne => 1,
-        GameMode::TwoVsTwo => 2,
-        GameMode::HumanVsAi => {
-            return Err(GameError::CannotJoinAiGame.into());
-        }
-    };
-    
-    // Store counts before mutation
-    let team_a_count = game.team_a_count;
-    let team_b_count = game.team_b_count;
-    
-    if team_a_count < required_per_team {
-        game.team_a[team_a_count as usize] = player;
-        game.team_a_count += 1;
-    } else if team_b_count < required_per_team {
-        game.team_b[team_b_count as usize] = player;
-        game.team_b_count += 1;
-    }
+    // Seat the player in turn order: A1 (creator), B1, A2, B2. Rejects the
+    // creator, players already seated and full games
+    game.add_joiner(player)?;
     
     let entry_fee = game.entry_fee;
     let game_id = game.game_id;
     
-    // Transfer entry fee
-    transfer_checked(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            TransferChecked {
-                from: ctx.accounts.player_token_account.to_account_info(),
-                to: ctx.accounts.game_vault.to_account_info(),
-                authority: ctx.accounts.player.to_account_info(),
-                mint: ctx.accounts.mint.to_account_info(),
-            },
-        ),
+    // Transfer entry fee, crediting the pot with what the vault received
+    // net of any transfer fee
+    let treasury = GameTreasury::token(
+        ctx.accounts.game_vault.to_account_info(),
+        game.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.remaining_accounts,
+        &[],
+    )?;
+    let received = treasury.deposit(
+        &ctx.accounts.player.to_account_info(),
+        &ctx.accounts.player_token_account,
         entry_fee,
-        get_mint_decimals(&ctx.accounts.mint)?,
     )?;
     
-    game.total_pot = game.total_pot
-        .checked_add(entry_fee)
-        .ok_or(GameError::ArithmeticOverflow)?;
+    game.add_to_pot(received)?;
     
     let is_full = game.is_full();
     
@@ -121,8 +105,23 @@ pub fn join_game(ctx: Context<JoinGame>) -> Result<()> {
     
     // Check if game is ready to start
     if is_full {
+        game.lock_turn_order();
+        game.transition(GameStatus::Ready)?;
         msg!("Game {} is full and ready to delegate", game_id);
     }
     
+    emit_cpi!(PlayerJoined {
+        game: game.key(),
+        game_id: game.game_id,
+        player,
+        team: if game.team_a.contains(&player) { 0 } else { 1 },
+        total_pot: game.total_pot,
+        is_full: game.is_full(),
+    });
+    
+    // First game for this wallet creates its stats and rewards accounts
+    ctx.accounts.player_stats.ensure_initialized(player, ctx.bumps.player_stats);
+    ctx.accounts.player_rewards.ensure_initialized(player, ctx.bumps.player_rewards);
+    
     Ok(())
 }
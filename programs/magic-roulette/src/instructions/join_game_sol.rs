@@ -1,12 +1,12 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::{transfer, Transfer};
-use crate::{errors::GameError, state::*};
+use crate::{errors::GameError, events::*, state::*, token::GameTreasury};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct JoinGameSol<'info> {
     #[account(
         mut,
-        seeds = [b"game", game.game_id.to_le_bytes().as_ref()],
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
         bump = game.bump
     )]
     pub game: Account<'info, Game>,
@@ -14,8 +14,28 @@ pub struct JoinGameSol<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
     
+    /// Player's lifetime stats, created on their first game
     #[account(
-        seeds = [b"platform"],
+        init_if_needed,
+        payer = player,
+        space = PlayerStats::LEN,
+        seeds = [PlayerStats::SEED, player.key().as_ref()],
+        bump
+    )]
+    pub player_stats: Account<'info, PlayerStats>,
+    
+    /// Player's treasury rewards, credited when games settle
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = TreasuryRewards::LEN,
+        seeds = [TreasuryRewards::SEED, player.key().as_ref()],
+        bump
+    )]
+    pub player_rewards: Account<'info, TreasuryRewards>,
+    
+    #[account(
+        seeds = [PlatformConfig::SEED],
         bump = platform_config.bump,
         constraint = !platform_config.paused @ GameError::PlatformPaused
     )]
@@ -25,7 +45,7 @@ pub struct JoinGameSol<'info> {
     /// CHECK: PDA for holding SOL
     #[account(
         mut,
-        seeds = [b"game_vault", game.key().as_ref()],
+        seeds = [Game::VAULT_SEED, game.key().as_ref()],
         bump
     )]
     pub game_vault: AccountInfo<'info>,
@@ -46,61 +66,21 @@ pub fn join_game_sol(ctx: Context<JoinGameSol>) -> Result<()> {
     // SECURITY: Cannot join AI game
     require!(!game.is_ai_game, GameError::CannotJoinAiGame);
     
-    // SECURITY: Cannot join own game
-    require!(game.creator != player, GameError::CannotJoinOwnGame);
-    
-    // SECURITY: Check if game is full
-    require!(!game.is_full(), GameError::GameFull);
-    
-    // SECURITY: Check if player already in game
-    for i in 0..game.team_a_count as usize {
-        require!(game.team_a[i] != player, GameError::PlayerAlreadyInGame);
-    }
-    for i in 0..game.team_b_count as usize {
-        require!(game.team_b[i] != player, GameError::PlayerAlreadyInGame);
-    }
-    
-    // Add player to appropriate team
-    match game.game_mode {
-        GameMode::OneVsOne => {
-            // Join team B (team A already has creator)
-            game.team_b[0] = player;
-            game.team_b_count = 1;
-        }
-        GameMode::TwoVsTwo => {
-            // Fill teams in order: A1, B1, A2, B2
-            if game.team_a_count == 1 && game.team_b_count == 0 {
-                game.team_b[0] = player;
-                game.team_b_count = 1;
-            } else if game.team_a_count == 1 && game.team_b_count == 1 {
-                game.team_a[1] = player;
-                game.team_a_count = 2;
-            } else if game.team_a_count == 2 && game.team_b_count == 1 {
-                game.team_b[1] = player;
-                game.team_b_count = 2;
-            }
-        }
-        GameMode::HumanVsAi => {
-            return Err(GameError::CannotJoinAiGame.into());
-        }
-    }
+    // SECURITY: add_joiner rejects the creator, players already seated and
+    // full games
+    game.add_joiner(player)?;
     
     // Transfer entry fee (SOL) to game vault
-    transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.player.to_account_info(),
-                to: ctx.accounts.game_vault.to_account_info(),
-            },
-        ),
-        game.entry_fee,
-    )?;
+    let treasury = GameTreasury::sol(
+        ctx.accounts.game_vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        &[],
+    );
+    let payer = ctx.accounts.player.to_account_info();
+    let received = treasury.deposit(&payer, &payer, game.entry_fee)?;
     
     // Update total pot
-    game.total_pot = game.total_pot
-        .checked_add(game.entry_fee)
-        .ok_or(GameError::ArithmeticOverflow)?;
+    game.add_to_pot(received)?;
     
     msg!("👥 Player joined game {}", game.game_id);
     msg!("   Player: {}", player);
@@ -109,8 +89,23 @@ pub fn join_game_sol(ctx: Context<JoinGameSol>) -> Result<()> {
     msg!("   Team A: {}, Team B: {}", game.team_a_count, game.team_b_count);
     
     if game.is_full() {
+        game.lock_turn_order();
+        game.transition(GameStatus::Ready)?;
         msg!("✅ Game is now FULL and ready to start!");
     }
     
+    emit_cpi!(PlayerJoined {
+        game: game.key(),
+        game_id: game.game_id,
+        player,
+        team: if game.team_a.contains(&player) { 0 } else { 1 },
+        total_pot: game.total_pot,
+        is_full: game.is_full(),
+    });
+    
+    // First game for this wallet creates its stats and rewards accounts
+    ctx.accounts.player_stats.ensure_initialized(player, ctx.bumps.player_stats);
+    ctx.accounts.player_rewards.ensure_initialized(player, ctx.bumps.player_rewards);
+    
     Ok(())
 }
@@ -0,0 +1,410 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program::invoke, sysvar};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use crate::{errors::GameError, events::*, kamino, oracle, state::*, constants::KAMINO_PROGRAM_ID, token::VaultWsol};
+use super::create_game_with_loan::borrow_into_vault;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct JoinGameWithLoan<'info> {
+    #[account(
+        mut,
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
+        bump = game.bump
+    )]
+    pub game: Box<Account<'info, Game>>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// Player's lifetime stats, created on their first game
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = PlayerStats::LEN,
+        seeds = [PlayerStats::SEED, player.key().as_ref()],
+        bump
+    )]
+    pub player_stats: Box<Account<'info, PlayerStats>>,
+
+    /// Player's treasury rewards, credited when games settle
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = TreasuryRewards::LEN,
+        seeds = [TreasuryRewards::SEED, player.key().as_ref()],
+        bump
+    )]
+    pub player_rewards: Box<Account<'info, TreasuryRewards>>,
+
+    /// Tracks the loan until its debt is repaid
+    #[account(
+        init,
+        payer = player,
+        space = LoanPosition::LEN,
+        seeds = [LoanPosition::SEED, game.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub loan_position: Box<Account<'info, LoanPosition>>,
+
+    #[account(
+        seeds = [PlatformConfig::SEED],
+        bump = platform_config.bump,
+        constraint = !platform_config.paused @ GameError::PlatformPaused
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Game vault PDA - holds SOL for this game
+    /// CHECK: PDA for holding SOL
+    #[account(
+        mut,
+        seeds = [Game::VAULT_SEED, game.key().as_ref()],
+        bump
+    )]
+    pub game_vault: AccountInfo<'info>,
+
+    /// Game vault's wrapped SOL account, open only while the loan is paid in
+    /// CHECK: Address derived from the game vault
+    #[account(
+        mut,
+        address = VaultWsol::new(&game_vault.key()).account @ GameError::InvalidVaultOwner
+    )]
+    pub vault_wsol: AccountInfo<'info>,
+
+    // Kamino Lend accounts
+    /// CHECK: Kamino lending market (validated by Kamino program)
+    pub lending_market: AccountInfo<'info>,
+
+    /// CHECK: Kamino lending market authority (validated by Kamino program)
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// CHECK: Reserve the SOL is borrowed from, checked against sol_oracle
+    #[account(mut)]
+    pub reserve: AccountInfo<'info>,
+
+    /// CHECK: Liquidity mint of the SOL reserve, wrapped SOL
+    #[account(address = native_mint::ID @ GameError::InvalidMint)]
+    pub reserve_liquidity_mint: AccountInfo<'info>,
+
+    /// CHECK: Liquidity supply of the SOL reserve, checked against it
+    #[account(mut)]
+    pub reserve_liquidity_supply: AccountInfo<'info>,
+
+    /// CHECK: Fee vault of the SOL reserve, receives the borrow fee; checked against it
+    #[account(mut)]
+    pub reserve_liquidity_fee_receiver: AccountInfo<'info>,
+
+    /// CHECK: Reserve the collateral is deposited into, checked against collateral_oracle
+    #[account(mut)]
+    pub collateral_reserve: AccountInfo<'info>,
+
+    /// CHECK: Liquidity supply of the collateral reserve, checked against it
+    #[account(mut)]
+    pub collateral_reserve_liquidity_supply: AccountInfo<'info>,
+
+    /// CHECK: cToken mint of the collateral reserve, checked against it
+    #[account(mut)]
+    pub reserve_collateral_mint: AccountInfo<'info>,
+
+    /// CHECK: cToken supply of the collateral reserve, checked against it
+    #[account(mut)]
+    pub reserve_collateral_supply: AccountInfo<'info>,
+
+    /// CHECK: Player's obligation account (loan account)
+    #[account(mut)]
+    pub obligation: AccountInfo<'info>,
+
+    /// Player's collateral token account, the deposit source; its mint is
+    /// the one the collateral is priced as
+    #[account(
+        mut,
+        constraint = player_collateral_account.mint == collateral_mint.key() @ GameError::InvalidMint
+    )]
+    pub player_collateral_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Kamino lending program - validated against known program ID
+    #[account(
+        constraint = kamino_program.key() == KAMINO_PROGRAM_ID @ GameError::InvalidKaminoProgram
+    )]
+    pub kamino_program: AccountInfo<'info>,
+
+    /// CHECK: Instructions sysvar, where Kamino checks for its refresh instructions
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+
+    // Oracle accounts
+    /// Price feeds registered for SOL, the borrowed asset
+    #[account(
+        seeds = [AssetOracle::SEED, native_mint::ID.as_ref()],
+        bump = sol_oracle.bump
+    )]
+    pub sol_oracle: Box<Account<'info, AssetOracle>>,
+
+    /// CHECK: Pyth SOL price update, checked against sol_oracle
+    pub pyth_sol_price: AccountInfo<'info>,
+
+    /// CHECK: Switchboard SOL pull feed, checked against sol_oracle
+    pub switchboard_sol_price: AccountInfo<'info>,
+
+    /// Mint of the deposited collateral
+    pub collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Price feeds registered for the collateral mint
+    #[account(
+        seeds = [AssetOracle::SEED, collateral_mint.key().as_ref()],
+        bump = collateral_oracle.bump
+    )]
+    pub collateral_oracle: Box<Account<'info, AssetOracle>>,
+
+    /// CHECK: Pyth collateral price update, checked against collateral_oracle
+    pub collateral_pyth_price: AccountInfo<'info>,
+
+    /// CHECK: Switchboard collateral pull feed, checked against collateral_oracle
+    pub collateral_switchboard_price: AccountInfo<'info>,
+
+    pub token_program: Program<'info, anchor_spl::token::Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn join_game_with_loan(
+    ctx: Context<JoinGameWithLoan>,
+    collateral_amount: u64,
+    borrow_amount: u64,
+) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+    let player = ctx.accounts.player.key();
+
+    // SECURITY: Validate game status
+    require!(
+        game.status == GameStatus::WaitingForPlayers,
+        GameError::GameNotReady
+    );
+
+    // SECURITY: Cannot join AI game
+    require!(!game.is_ai_game, GameError::CannotJoinAiGame);
+
+    // SECURITY: Kamino lends SOL, so only SOL games can be joined on credit
+    require!(game.mint.is_none(), GameError::InvalidMint);
+
+    // SECURITY: Value the collateral and the borrowed SOL, Kamino's fee
+    // included, at oracle prices and hold the loan to the platform's
+    // minimum collateral ratio
+    let entry_fee = game.entry_fee;
+    let now = Clock::get()?.unix_timestamp;
+    let sol_price = oracle::price(
+        &ctx.accounts.sol_oracle,
+        &ctx.accounts.pyth_sol_price,
+        &ctx.accounts.switchboard_sol_price,
+        now,
+    )?;
+    let collateral_price = oracle::price(
+        &ctx.accounts.collateral_oracle,
+        &ctx.accounts.collateral_pyth_price,
+        &ctx.accounts.collateral_switchboard_price,
+        now,
+    )?;
+    oracle::require_collateral_ratio(
+        collateral_amount,
+        ctx.accounts.collateral_mint.decimals,
+        &collateral_price,
+        borrow_amount,
+        native_mint::DECIMALS,
+        &sol_price,
+        ctx.accounts.platform_config.min_collateral_ratio_bps,
+    )?;
+
+    // SECURITY: The collateral goes into the market's reserve for the
+    // collateral asset and the SOL comes out of its SOL reserve, each with
+    // the supplies and mints that reserve records
+    kamino::ReserveKeys::load(&ctx.accounts.collateral_reserve)?.require_deposit(
+        ctx.accounts.lending_market.key,
+        &ctx.accounts.collateral_oracle.mint,
+        ctx.accounts.collateral_reserve_liquidity_supply.key,
+        ctx.accounts.reserve_collateral_mint.key,
+        ctx.accounts.reserve_collateral_supply.key,
+    )?;
+    kamino::ReserveKeys::load(&ctx.accounts.reserve)?.require_borrow(
+        ctx.accounts.lending_market.key,
+        &ctx.accounts.sol_oracle.mint,
+        ctx.accounts.reserve_liquidity_supply.key,
+        ctx.accounts.reserve_liquidity_fee_receiver.key,
+    )?;
+
+    // SECURITY: add_joiner rejects the creator, players already seated and
+    // full games
+    game.add_joiner(player)?;
+    game.record_loan(&player)?;
+
+    msg!("🎮 Joining game {} with Kamino loan", game.game_id);
+    msg!("   Entry fee: {} SOL", entry_fee as f64 / 1e9);
+    msg!("   Collateral: {} of {}", collateral_amount, ctx.accounts.collateral_mint.key());
+
+    // STEP 1: Deposit collateral to Kamino
+    let collateral_before = kamino::obligation_collateral(
+        &ctx.accounts.obligation,
+        ctx.accounts.collateral_reserve.key,
+        ctx.accounts.lending_market.key,
+    )?;
+
+    let deposit_ix = kamino::DepositReserveLiquidityAndObligationCollateral {
+        owner: player,
+        obligation: ctx.accounts.obligation.key(),
+        lending_market: ctx.accounts.lending_market.key(),
+        lending_market_authority: ctx.accounts.lending_market_authority.key(),
+        reserve: ctx.accounts.collateral_reserve.key(),
+        reserve_liquidity_mint: ctx.accounts.collateral_mint.key(),
+        reserve_liquidity_supply: ctx.accounts.collateral_reserve_liquidity_supply.key(),
+        reserve_collateral_mint: ctx.accounts.reserve_collateral_mint.key(),
+        reserve_destination_deposit_collateral: ctx.accounts.reserve_collateral_supply.key(),
+        user_source_liquidity: ctx.accounts.player_collateral_account.key(),
+        collateral_token_program: ctx.accounts.token_program.key(),
+        liquidity_token_program: ctx.accounts.token_program.key(),
+    }
+    .instruction(collateral_amount);
+
+    invoke(
+        &deposit_ix,
+        &[
+            ctx.accounts.player.to_account_info(),
+            ctx.accounts.obligation.to_account_info(),
+            ctx.accounts.lending_market.to_account_info(),
+            ctx.accounts.lending_market_authority.to_account_info(),
+            ctx.accounts.collateral_reserve.to_account_info(),
+            ctx.accounts.collateral_mint.to_account_info(),
+            ctx.accounts.collateral_reserve_liquidity_supply.to_account_info(),
+            ctx.accounts.reserve_collateral_mint.to_account_info(),
+            ctx.accounts.reserve_collateral_supply.to_account_info(),
+            ctx.accounts.player_collateral_account.to_account_info(),
+            ctx.accounts.kamino_program.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.instruction_sysvar.to_account_info(),
+        ],
+    )?;
+
+    // klend withdraws collateral in cTokens, so record how many the deposit added
+    let collateral_ctokens = kamino::obligation_collateral(
+        &ctx.accounts.obligation,
+        ctx.accounts.collateral_reserve.key,
+        ctx.accounts.lending_market.key,
+    )?
+    .checked_sub(collateral_before)
+    .ok_or(GameError::ArithmeticOverflow)?;
+
+    msg!("✅ Collateral deposited to Kamino");
+
+    // STEP 2: Borrow straight into the game vault, which keeps the entry
+    // fee, so every seat puts the same amount in the pot
+    let wsol = VaultWsol::new(&ctx.accounts.game_vault.key());
+    let borrow_ix = kamino::BorrowObligationLiquidity {
+        owner: player,
+        obligation: ctx.accounts.obligation.key(),
+        lending_market: ctx.accounts.lending_market.key(),
+        lending_market_authority: ctx.accounts.lending_market_authority.key(),
+        borrow_reserve: ctx.accounts.reserve.key(),
+        borrow_reserve_liquidity_mint: ctx.accounts.reserve_liquidity_mint.key(),
+        reserve_source_liquidity: ctx.accounts.reserve_liquidity_supply.key(),
+        borrow_reserve_liquidity_fee_receiver: ctx.accounts.reserve_liquidity_fee_receiver.key(),
+        user_destination_liquidity: wsol.account,
+        token_program: ctx.accounts.token_program.key(),
+    }
+    .instruction(borrow_amount);
+
+    // The obligation may already carry debt, so the loan is what this borrow adds
+    let debt_before = kamino::obligation_debt(
+        &ctx.accounts.obligation,
+        &ctx.accounts.reserve,
+        ctx.accounts.lending_market.key,
+    )?;
+
+    let game_key = game.key();
+    let vault_seeds: &[&[u8]] = &[Game::VAULT_SEED, game_key.as_ref(), &[ctx.bumps.game_vault]];
+    borrow_into_vault(
+        &borrow_ix,
+        entry_fee,
+        &wsol,
+        &ctx.accounts.player.to_account_info(),
+        &ctx.accounts.vault_wsol,
+        &[
+            ctx.accounts.player.to_account_info(),
+            ctx.accounts.obligation.to_account_info(),
+            ctx.accounts.lending_market.to_account_info(),
+            ctx.accounts.lending_market_authority.to_account_info(),
+            ctx.accounts.reserve.to_account_info(),
+            ctx.accounts.reserve_liquidity_mint.to_account_info(),
+            ctx.accounts.reserve_liquidity_supply.to_account_info(),
+            ctx.accounts.reserve_liquidity_fee_receiver.to_account_info(),
+            ctx.accounts.game_vault.to_account_info(),
+            ctx.accounts.vault_wsol.to_account_info(),
+            ctx.accounts.kamino_program.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.associated_token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.instruction_sysvar.to_account_info(),
+        ],
+        &[vault_seeds],
+    )?;
+
+    let loan_amount = kamino::obligation_debt(
+        &ctx.accounts.obligation,
+        &ctx.accounts.reserve,
+        ctx.accounts.lending_market.key,
+    )?
+    .checked_sub(debt_before)
+    .ok_or(GameError::ArithmeticOverflow)?;
+    game.add_to_pot(entry_fee)?;
+
+    msg!("✅ SOL borrowed from Kamino and transferred to game vault");
+
+    let loan_position = &mut ctx.accounts.loan_position;
+    loan_position.game = game.key();
+    loan_position.game_id = game.game_id;
+    loan_position.borrower = player;
+    loan_position.lending_market = ctx.accounts.lending_market.key();
+    loan_position.reserve = ctx.accounts.reserve.key();
+    loan_position.collateral_reserve = ctx.accounts.collateral_reserve.key();
+    loan_position.obligation = ctx.accounts.obligation.key();
+    loan_position.collateral_amount = collateral_amount;
+    loan_position.collateral_ctokens = collateral_ctokens;
+    loan_position.loan_amount = loan_amount;
+    loan_position.status = LoanStatus::Open;
+    loan_position.bump = ctx.bumps.loan_position;
+    loan_position.borrow_rate_bsf = kamino::reserve_borrow_rate(&ctx.accounts.reserve)?;
+
+    msg!("👥 Player joined game {}", game.game_id);
+    msg!("   Player: {}", player);
+    msg!("   Total pot: {} SOL", game.total_pot as f64 / 1e9);
+    msg!("   Team A: {}, Team B: {}", game.team_a_count, game.team_b_count);
+
+    if game.is_full() {
+        game.lock_turn_order();
+        game.transition(GameStatus::Ready)?;
+        msg!("✅ Game is now FULL and ready to start!");
+    }
+
+    emit_cpi!(PlayerJoined {
+        game: game.key(),
+        game_id: game.game_id,
+        player,
+        team: if game.team_a.contains(&player) { 0 } else { 1 },
+        total_pot: game.total_pot,
+        is_full: game.is_full(),
+    });
+
+    emit_cpi!(LoanOpened {
+        game: game.key(),
+        game_id: game.game_id,
+        borrower: player,
+        obligation: ctx.accounts.obligation.key(),
+        collateral_amount,
+        loan_amount,
+    });
+
+    // First game for this wallet creates its stats and rewards accounts
+    ctx.accounts.player_stats.ensure_initialized(player, ctx.bumps.player_stats);
+    ctx.accounts.player_rewards.ensure_initialized(player, ctx.bumps.player_rewards);
+
+    Ok(())
+}
@@ -1,13 +1,16 @@
 pub mod initialize_platform;
 pub mod create_game;
 pub mod join_game;
+pub mod cancel_game;
 pub mod delegate;
-pub mod process_vrf_result;
 pub mod take_shot;
+pub mod claim_timeout_forfeit;
 pub mod finalize;
+pub mod close_game;
 pub mod claim_rewards;
 pub mod create_ai_game;
 pub mod ai_take_shot;
+pub mod admin;
 
 // MagicBlock VRF
 pub mod vrf_instructions;
@@ -16,24 +19,32 @@ pub mod vrf_instructions;
 pub mod create_game_sol;
 pub mod join_game_sol;
 pub mod finalize_game_sol;
+pub mod cancel_game_sol;
 
 // Kamino integration
 pub mod create_game_with_loan;
+pub mod join_game_with_loan;
 pub mod finalize_game_with_loan;
+pub mod cancel_game_with_loan;
+pub mod settle_loan;
 
 // Squads integration
 pub mod initialize_platform_multisig;
+pub mod withdraw_treasury;
 
 pub use initialize_platform::*;
 pub use create_game::*;
 pub use join_game::*;
+pub use cancel_game::*;
 pub use delegate::*;
-pub use process_vrf_result::*;
 pub use take_shot::*;
+pub use claim_timeout_forfeit::*;
 pub use finalize::*;
+pub use close_game::*;
 pub use claim_rewards::*;
 pub use create_ai_game::*;
 pub use ai_take_shot::*;
+pub use admin::*;
 
 // MagicBlock exports
 pub use vrf_instructions::*;
@@ -42,10 +53,15 @@ pub use vrf_instructions::*;
 pub use create_game_sol::*;
 pub use join_game_sol::*;
 pub use finalize_game_sol::*;
+pub use cancel_game_sol::*;
 
 // Kamino exports
 pub use create_game_with_loan::*;
+pub use join_game_with_loan::*;
 pub use finalize_game_with_loan::*;
+pub use cancel_game_with_loan::*;
+pub use settle_loan::*;
 
 // Squads exports
 pub use initialize_platform_multisig::*;
+pub use withdraw_treasury::*;
@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program::invoke, sysvar};
+use anchor_spl::token::spl_token::native_mint;
+use crate::{errors::GameError, events::*, kamino, state::*, constants::KAMINO_PROGRAM_ID};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleLoan<'info> {
+    /// Loan of a settled or cancelled game; closed once repaid
+    #[account(
+        mut,
+        seeds = [LoanPosition::SEED, loan_position.game.as_ref(), borrower.key().as_ref()],
+        bump = loan_position.bump,
+        has_one = borrower @ GameError::Unauthorized,
+        close = borrower
+    )]
+    pub loan_position: Account<'info, LoanPosition>,
+
+    /// Borrower, repays the debt and receives their collateral and the rent
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+
+    // Kamino accounts, pinned to the ones the loan was opened with
+    /// CHECK: Kamino lending market
+    #[account(
+        constraint = lending_market.key() == loan_position.lending_market @ GameError::InvalidKaminoMarket
+    )]
+    pub lending_market: AccountInfo<'info>,
+
+    /// CHECK: Kamino lending market authority (validated by Kamino program)
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// CHECK: Kamino reserve the loan was borrowed from, refreshed in this
+    /// transaction; read for the accrued borrow rate
+    #[account(
+        mut,
+        constraint = reserve.key() == loan_position.reserve @ GameError::InvalidKaminoReserve
+    )]
+    pub reserve: AccountInfo<'info>,
+
+    /// CHECK: Liquidity mint of the reserve, wrapped SOL
+    #[account(address = native_mint::ID @ GameError::InvalidMint)]
+    pub reserve_liquidity_mint: AccountInfo<'info>,
+
+    /// CHECK: Reserve liquidity supply
+    #[account(mut)]
+    pub reserve_liquidity_supply: AccountInfo<'info>,
+
+    /// CHECK: Borrower's obligation
+    #[account(
+        mut,
+        constraint = obligation.key() == loan_position.obligation @ GameError::InvalidKaminoObligation
+    )]
+    pub obligation: AccountInfo<'info>,
+
+    /// CHECK: Kamino reserve the collateral was deposited into
+    #[account(
+        mut,
+        constraint = collateral_reserve.key() == loan_position.collateral_reserve @ GameError::InvalidKaminoReserve
+    )]
+    pub collateral_reserve: AccountInfo<'info>,
+
+    /// CHECK: cToken supply of the collateral reserve, where the obligation's
+    /// collateral is held; checked against it
+    #[account(mut)]
+    pub obligation_collateral: AccountInfo<'info>,
+
+    /// CHECK: Borrower's liquidity account the debt is repaid from
+    #[account(mut)]
+    pub borrower_liquidity: AccountInfo<'info>,
+
+    /// CHECK: Borrower's collateral token account
+    #[account(mut)]
+    pub borrower_collateral_account: AccountInfo<'info>,
+
+    /// CHECK: Kamino program - validated against known program ID
+    #[account(
+        constraint = kamino_program.key() == KAMINO_PROGRAM_ID @ GameError::InvalidKaminoProgram
+    )]
+    pub kamino_program: AccountInfo<'info>,
+
+    /// CHECK: Instructions sysvar, where Kamino checks for its refresh instructions
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+
+    pub token_program: Program<'info, anchor_spl::token::Token>,
+}
+
+pub fn settle_loan(ctx: Context<SettleLoan>) -> Result<()> {
+    let loan_position = &ctx.accounts.loan_position;
+
+    // SECURITY: Open positions are settled by finalize or cancel first
+    require!(
+        loan_position.status != LoanStatus::Open,
+        GameError::InvalidGameStatus
+    );
+
+    // SECURITY: Collateral comes back out of the reserve it was deposited into
+    require!(
+        kamino::ReserveKeys::load(&ctx.accounts.collateral_reserve)?.collateral_supply
+            == ctx.accounts.obligation_collateral.key(),
+        GameError::InvalidKaminoReserve
+    );
+
+    // What is left of this loan, interest included; other debt on the
+    // obligation stays the borrower's to repay
+    let (debt, _) = loan_position.debt(&ctx.accounts.obligation, &ctx.accounts.reserve)?;
+
+    // INTERACTIONS: Repay what is left of the debt from the borrower's own
+    // funds, then withdraw their collateral; the position is closed on exit
+    if debt > 0 {
+        msg!("💰 Repaying Kamino loan: {} SOL", debt as f64 / 1e9);
+
+        let repay_ix = kamino::RepayObligationLiquidity {
+            owner: ctx.accounts.borrower.key(),
+            obligation: ctx.accounts.obligation.key(),
+            lending_market: ctx.accounts.lending_market.key(),
+            repay_reserve: ctx.accounts.reserve.key(),
+            reserve_liquidity_mint: ctx.accounts.reserve_liquidity_mint.key(),
+            reserve_destination_liquidity: ctx.accounts.reserve_liquidity_supply.key(),
+            user_source_liquidity: ctx.accounts.borrower_liquidity.key(),
+            token_program: ctx.accounts.token_program.key(),
+        }
+        .instruction(debt);
+
+        invoke(
+            &repay_ix,
+            &[
+                ctx.accounts.borrower.to_account_info(),
+                ctx.accounts.obligation.to_account_info(),
+                ctx.accounts.lending_market.to_account_info(),
+                ctx.accounts.reserve.to_account_info(),
+                ctx.accounts.reserve_liquidity_mint.to_account_info(),
+                ctx.accounts.reserve_liquidity_supply.to_account_info(),
+                ctx.accounts.borrower_liquidity.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.instruction_sysvar.to_account_info(),
+            ],
+        )?;
+    }
+
+    let withdraw_ix = kamino::WithdrawObligationCollateral {
+        owner: ctx.accounts.borrower.key(),
+        obligation: ctx.accounts.obligation.key(),
+        lending_market: ctx.accounts.lending_market.key(),
+        lending_market_authority: ctx.accounts.lending_market_authority.key(),
+        withdraw_reserve: ctx.accounts.collateral_reserve.key(),
+        reserve_source_collateral: ctx.accounts.obligation_collateral.key(),
+        user_destination_collateral: ctx.accounts.borrower_collateral_account.key(),
+        token_program: ctx.accounts.token_program.key(),
+    }
+    .instruction(loan_position.collateral_ctokens);
+
+    invoke(
+        &withdraw_ix,
+        &[
+            ctx.accounts.borrower.to_account_info(),
+            ctx.accounts.obligation.to_account_info(),
+            ctx.accounts.lending_market.to_account_info(),
+            ctx.accounts.lending_market_authority.to_account_info(),
+            ctx.accounts.collateral_reserve.to_account_info(),
+            ctx.accounts.obligation_collateral.to_account_info(),
+            ctx.accounts.borrower_collateral_account.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.instruction_sysvar.to_account_info(),
+        ],
+    )?;
+
+    emit_cpi!(LoanSettled {
+        game: loan_position.game,
+        game_id: loan_position.game_id,
+        borrower: loan_position.borrower,
+        amount_repaid: debt,
+        collateral_returned: true,
+    });
+
+    msg!("✅ Loan for game {} settled, collateral returned", loan_position.game_id);
+
+    Ok(())
+}
@@ -1,63 +1,153 @@
 use anchor_lang::prelude::*;
-use crate::{errors::GameError, state::*};
+use anchor_lang::Discriminator;
+use ephemeral_vrf_sdk::anchor::vrf;
+use ephemeral_vrf_sdk::consts::DEFAULT_EPHEMERAL_QUEUE;
+use crate::{errors::GameError, events::*, state::*};
+use super::vrf_instructions::{game_randomness_request, VrfCallback};
 
+#[vrf]
+#[event_cpi]
 #[derive(Accounts)]
 pub struct TakeShot<'info> {
     #[account(
         mut,
-        seeds = [b"game", game.game_id.to_le_bytes().as_ref()],
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
         bump = game.bump
     )]
     pub game: Account<'info, Game>,
     
+    /// Shooter, also pays for the shot's VRF request
+    #[account(mut)]
     pub player: Signer<'info>,
+    
+    /// CHECK: MagicBlock VRF oracle queue (ephemeral rollup queue)
+    #[account(mut, address = DEFAULT_EPHEMERAL_QUEUE)]
+    pub oracle_queue: AccountInfo<'info>,
 }
 
 pub fn take_shot(ctx: Context<TakeShot>) -> Result<()> {
     let game = &mut ctx.accounts.game;
     let player = ctx.accounts.player.key();
     
+    // Verify it's player's turn
+    let current_player = game.get_current_player()?;
     require!(
-        game.status == GameStatus::InProgress,
-        GameError::GameNotInProgress
+        player == current_player,
+        GameError::NotYourTurn
     );
     
-    require!(
-        game.vrf_fulfilled,
-        GameError::VrfNotReady
+    // SECURITY: Pull the trigger - the shot is resolved by
+    // resolve_shot_callback from randomness drawn for this shot alone
+    let caller_seed = game.pull_trigger(Clock::get()?.unix_timestamp)?;
+    let ix = game_randomness_request(
+        &player,
+        &ctx.accounts.oracle_queue.key(),
+        &game.key(),
+        &ctx.accounts.event_authority.key(),
+        crate::instruction::ResolveShotCallback::DISCRIMINATOR,
+        caller_seed,
     );
     
-    // Verify it's player's turn
-    let current_player = game.get_current_player();
-    require!(
-        player == current_player,
-        GameError::NotYourTurn
+    ctx.accounts
+        .invoke_signed_vrf(&ctx.accounts.player.to_account_info(), &ix)?;
+    
+    emit_cpi!(TriggerPulled {
+        game: ctx.accounts.game.key(),
+        game_id: ctx.accounts.game.game_id,
+        player,
+        shot_number: ctx.accounts.game.shots_taken + 1,
+    });
+    
+    msg!("Player {} pulls the trigger for shot #{}", player, ctx.accounts.game.shots_taken + 1);
+    
+    Ok(())
+}
+
+/// Re-request the randomness of a pulled trigger (permissionless)
+#[vrf]
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RetryShot<'info> {
+    #[account(
+        mut,
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
+        bump = game.bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    /// Pays for the replacement VRF request
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// CHECK: MagicBlock VRF oracle queue (ephemeral rollup queue)
+    #[account(mut, address = DEFAULT_EPHEMERAL_QUEUE)]
+    pub oracle_queue: AccountInfo<'info>,
+}
+
+pub fn retry_shot(ctx: Context<RetryShot>) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+    let player = game.get_current_player()?;
+    
+    // SECURITY: Only once the pending shot's callback is overdue - the new
+    // request carries the same seed and resolves the same shot
+    let caller_seed = game.retry_trigger(Clock::get()?.unix_timestamp)?;
+    let ix = game_randomness_request(
+        &ctx.accounts.payer.key(),
+        &ctx.accounts.oracle_queue.key(),
+        &game.key(),
+        &ctx.accounts.event_authority.key(),
+        crate::instruction::ResolveShotCallback::DISCRIMINATOR,
+        caller_seed,
     );
     
-    // Take shot
-    game.shots_taken += 1;
+    ctx.accounts
+        .invoke_signed_vrf(&ctx.accounts.payer.to_account_info(), &ix)?;
     
-    msg!("Player {} takes shot #{}", player, game.shots_taken);
+    emit_cpi!(TriggerPulled {
+        game: ctx.accounts.game.key(),
+        game_id: ctx.accounts.game.game_id,
+        player,
+        shot_number: ctx.accounts.game.shots_taken + 1,
+    });
     
-    // Check if bullet fired
-    if game.current_chamber == game.bullet_chamber {
+    msg!("🔁 Randomness re-requested for shot #{} of game {}", ctx.accounts.game.shots_taken + 1, ctx.accounts.game.game_id);
+    
+    Ok(())
+}
+
+/// Resolve the pending shot with the randomness drawn for it
+pub fn resolve_shot_callback(ctx: Context<VrfCallback>, randomness: [u8; 32]) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+    let player = game.get_current_player()?;
+    let chamber = game.current_chamber;
+    
+    let fired = game.resolve_shot(randomness, Clock::get()?.unix_timestamp)?;
+    
+    emit_cpi!(ShotTaken {
+        game: game.key(),
+        game_id: game.game_id,
+        player,
+        shot_number: game.shots_taken,
+        chamber,
+        fired,
+        randomness,
+    });
+    
+    if fired {
         // Player hit the bullet - opposing team wins
-        let losing_team = game.current_turn % 2;
-        game.winner_team = Some(1 - losing_team);
-        game.status = GameStatus::Finished;
-        game.finished_at = Some(Clock::get()?.unix_timestamp);
+        let winner_team = game.winner_team.unwrap();
+        
+        emit_cpi!(GameFinished {
+            game: game.key(),
+            game_id: game.game_id,
+            winner_team,
+            timed_out: false,
+            shots_taken: game.shots_taken,
+        });
         
         msg!("BANG! Player {} hit the bullet!", player);
-        msg!("Team {} wins!", 1 - losing_team);
+        msg!("Team {} wins!", winner_team);
     } else {
-        // Safe - advance to next player
-        game.current_chamber += 1;
-        if game.current_chamber > 6 {
-            game.current_chamber = 1;
-        }
-        
-        game.current_turn += 1;
-        
         msg!("Click. Player {} survived", player);
     }
     
@@ -1,19 +1,147 @@
-use anchor_lang::prelude::*;
-use crate::state::Game;
-
-/// Request VRF randomness
-#[derive(Accounts)]
-pub struct RequestVrfRandomness<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    
-    #[account(mut)]
-    pub game: Account<'info, Game>,
-}
-
-/// VRF callback context
-#[derive(Accounts)]
-pub struct VrfCallback<'info> {
-    #[account(mut)]
-    pub game: Account<'info, Game>,
-}
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::Discriminator;
+use ephemeral_vrf_sdk::anchor::vrf;
+use ephemeral_vrf_sdk::consts::DEFAULT_EPHEMERAL_QUEUE;
+use ephemeral_vrf_sdk::instructions::{create_request_randomness_ix, RequestRandomnessParams};
+use ephemeral_vrf_sdk::types::SerializableAccountMeta;
+use crate::{errors::GameError, events::*, state::*, constants::MAGICBLOCK_VRF_PROGRAM_IDENTITY};
+
+/// Request VRF randomness
+#[vrf]
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RequestVrfRandomness<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
+        bump = game.bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    /// CHECK: MagicBlock VRF oracle queue (ephemeral rollup queue)
+    #[account(mut, address = DEFAULT_EPHEMERAL_QUEUE)]
+    pub oracle_queue: AccountInfo<'info>,
+}
+
+/// VRF callback context
+#[event_cpi]
+#[derive(Accounts)]
+pub struct VrfCallback<'info> {
+    /// VRF program identity PDA - only signs when the VRF program
+    /// invokes the callback through CPI
+    #[account(address = MAGICBLOCK_VRF_PROGRAM_IDENTITY @ GameError::InvalidVrfAuthority)]
+    pub vrf_program_identity: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [Game::SEED, game.game_id.to_le_bytes().as_ref()],
+        bump = game.bump
+    )]
+    pub game: Account<'info, Game>,
+}
+
+pub fn request_vrf_randomness(ctx: Context<RequestVrfRandomness>) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+    require!(!game.vrf_pending, GameError::VrfRequestPending);
+    
+    game.transition(GameStatus::AwaitingRandomness)?;
+    game.vrf_pending = true;
+    
+    // Callback receives the game account back, seeded with the game's VRF seed
+    let ix = game_randomness_request(
+        &ctx.accounts.payer.key(),
+        &ctx.accounts.oracle_queue.key(),
+        &game.key(),
+        &ctx.accounts.event_authority.key(),
+        crate::instruction::RequestVrfRandomnessCallback::DISCRIMINATOR,
+        game.vrf_seed,
+    );
+    
+    ctx.accounts
+        .invoke_signed_vrf(&ctx.accounts.payer.to_account_info(), &ix)?;
+    
+    emit_cpi!(RandomnessRequested {
+        game: ctx.accounts.game.key(),
+        game_id: ctx.accounts.game.game_id,
+    });
+    
+    msg!("VRF randomness requested for game {}", ctx.accounts.game.game_id);
+    
+    Ok(())
+}
+
+pub fn request_vrf_randomness_callback(ctx: Context<VrfCallback>, randomness: [u8; 32]) -> Result<()> {
+    fulfill_vrf_randomness(&mut ctx.accounts.game, randomness)?;
+    
+    emit_cpi!(RandomnessFulfilled {
+        game: ctx.accounts.game.key(),
+        game_id: ctx.accounts.game.game_id,
+        turn_deadline: ctx.accounts.game.turn_deadline,
+    });
+    
+    Ok(())
+}
+
+/// VRF request whose callback receives the game account back, plus the
+/// event authority and this program so it can emit_cpi!
+pub fn game_randomness_request(
+    payer: &Pubkey,
+    oracle_queue: &Pubkey,
+    game: &Pubkey,
+    event_authority: &Pubkey,
+    callback_discriminator: &[u8],
+    caller_seed: [u8; 32],
+) -> Instruction {
+    create_request_randomness_ix(RequestRandomnessParams {
+        payer: *payer,
+        oracle_queue: *oracle_queue,
+        callback_program_id: crate::ID,
+        callback_discriminator: callback_discriminator.to_vec(),
+        caller_seed,
+        accounts_metas: Some(vec![
+            SerializableAccountMeta {
+                pubkey: *game,
+                is_signer: false,
+                is_writable: true,
+            },
+            SerializableAccountMeta {
+                pubkey: *event_authority,
+                is_signer: false,
+                is_writable: false,
+            },
+            SerializableAccountMeta {
+                pubkey: crate::ID,
+                is_signer: false,
+                is_writable: false,
+            },
+        ]),
+        ..Default::default()
+    })
+}
+
+/// Single path from AwaitingRandomness to InProgress once randomness arrives
+pub fn fulfill_vrf_randomness(game: &mut Game, randomness: [u8; 32]) -> Result<()> {
+    require!(game.vrf_pending, GameError::VrfNotReady);
+    
+    // SECURITY: Store VRF result
+    game.vrf_result = randomness;
+    game.vrf_fulfilled = true;
+    game.vrf_pending = false;
+    
+    // This output decides nothing - every shot draws its own randomness
+    // when the trigger is pulled (see state::chamber)
+    
+    // Start game - first player's clock starts now
+    game.transition(GameStatus::InProgress)?;
+    game.reset_turn_deadline(Clock::get()?.unix_timestamp)?;
+    
+    msg!("🎲 VRF randomness received for game {}", game.game_id);
+    msg!("   Status: InProgress");
+    msg!("   Game ready for shots on Ephemeral Rollup");
+    
+    Ok(())
+}
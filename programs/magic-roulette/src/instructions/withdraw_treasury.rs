@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::{
+    constants::SQUADS_TREASURY_VAULT_INDEX,
+    errors::GameError,
+    events::TreasuryWithdrawn,
+    squads,
+    state::*,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [PlatformConfig::SEED],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Squads treasury vault of the platform multisig; signs only when the
+    /// Squads program executes an approved vault transaction
+    /// CHECK: Derivation and signature checked in the handler
+    #[account(mut)]
+    pub treasury_vault: AccountInfo<'info>,
+
+    /// CHECK: Any wallet the multisig chose to pay
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+    let config = &mut ctx.accounts.platform_config;
+
+    // SECURITY: Only platforms run by a Squads multisig, and only through
+    // the treasury vault the Squads program derives from that multisig
+    let multisig = config.multisig_authority.ok_or(GameError::MultisigUnauthorized)?;
+    let (vault, _) = squads::vault_address(&multisig, SQUADS_TREASURY_VAULT_INDEX);
+    require_keys_eq!(ctx.accounts.treasury_vault.key(), vault, GameError::MultisigUnauthorized);
+
+    // SECURITY: The Squads program signs for the vault only while executing
+    // a vault transaction its members approved
+    require!(ctx.accounts.treasury_vault.is_signer, GameError::MultisigProposalNotApproved);
+
+    // EFFECTS: Book the withdrawal before moving funds
+    config.debit_treasury(amount)?;
+
+    // INTERACTIONS: The vault is a system account signing this transaction
+    transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.treasury_vault.to_account_info(),
+                to: ctx.accounts.recipient.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    emit_cpi!(TreasuryWithdrawn {
+        multisig,
+        vault,
+        recipient: ctx.accounts.recipient.key(),
+        amount,
+        remaining_treasury_balance: config.treasury_balance,
+    });
+
+    msg!("🏛️ Treasury withdrawal approved by Squads multisig {}", multisig);
+    msg!("   Amount: {} SOL", amount as f64 / 1e9);
+    msg!("   Recipient: {}", ctx.accounts.recipient.key());
+    msg!("   Remaining treasury balance: {} SOL", config.treasury_balance as f64 / 1e9);
+
+    Ok(())
+}
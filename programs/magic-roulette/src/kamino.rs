@@ -0,0 +1,544 @@
+//! Kamino Lend accounts and instructions.
+//!
+//! Settlement only needs a handful of fields from an obligation and its
+//! borrow reserve, so they are read in place at their klend offsets rather
+//! than by deserializing the full (several KB) zero-copy structs.
+//!
+//! The instructions the program CPIs into are built here as well, with
+//! discriminators derived from their klend names at compile time and
+//! accounts in klend IDL order.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    sysvar,
+};
+use sha2_const_stable::Sha256;
+use crate::{constants::KAMINO_PROGRAM_ID, errors::GameError};
+
+/// `sha256("account:Obligation")[..8]`
+pub const OBLIGATION_DISCRIMINATOR: [u8; 8] = [0xa8, 0xce, 0x8d, 0x6a, 0x58, 0x4c, 0xac, 0xa7];
+/// `sha256("account:Reserve")[..8]`
+pub const RESERVE_DISCRIMINATOR: [u8; 8] = [0x2b, 0xf2, 0xcc, 0xca, 0x1a, 0xf7, 0x3b, 0x7f];
+
+/// klend scaled fractions (`_sf`, `_bsf`) carry 60 fractional bits
+pub const FRACTION_BITS: u32 = 60;
+
+/// Byte offsets into an Obligation account, discriminator included
+pub mod obligation_layout {
+    pub const LENDING_MARKET: usize = 32;
+    pub const OWNER: usize = 64;
+    /// `deposits: [ObligationCollateral; 8]`
+    pub const DEPOSITS: usize = 96;
+    pub const DEPOSIT_LEN: usize = 136;
+    pub const MAX_DEPOSITS: usize = 8;
+    /// `borrows: [ObligationLiquidity; 5]`, after 8 deposits of 136 bytes
+    /// and the lowest-LTV and deposited-value fields
+    pub const BORROWS: usize = 1208;
+    pub const BORROW_LEN: usize = 200;
+    pub const MAX_BORROWS: usize = 5;
+    pub const LEN: usize = BORROWS + BORROW_LEN * MAX_BORROWS;
+
+    // Within an ObligationCollateral; the amount is in the reserve's cTokens
+    pub const DEPOSIT_RESERVE: usize = 0;
+    pub const DEPOSITED_AMOUNT: usize = 32;
+
+    // Within an ObligationLiquidity
+    pub const BORROW_RESERVE: usize = 0;
+    pub const BORROW_CUMULATIVE_RATE: usize = 32;
+    pub const BORROWED_AMOUNT_SF: usize = 88;
+}
+
+/// Byte offsets into a Reserve account, discriminator included
+pub mod reserve_layout {
+    pub const LENDING_MARKET: usize = 32;
+    /// `liquidity.mint_pubkey`, `liquidity.supply_vault` and `liquidity.fee_vault`
+    pub const LIQUIDITY_MINT: usize = 128;
+    pub const LIQUIDITY_SUPPLY: usize = 160;
+    pub const LIQUIDITY_FEE_VAULT: usize = 192;
+    /// `liquidity.cumulative_borrow_rate_bsf`
+    pub const CUMULATIVE_BORROW_RATE: usize = 296;
+    /// `collateral.mint_pubkey` and `collateral.supply_vault`, after the
+    /// 1232-byte `liquidity` and its 150 words of padding
+    pub const COLLATERAL_MINT: usize = 2560;
+    pub const COLLATERAL_SUPPLY: usize = 2600;
+    pub const LEN: usize = COLLATERAL_SUPPLY + 32;
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_u128(data: &[u8], offset: usize) -> u128 {
+    u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap())
+}
+
+/// A `BigFractionBytes` is a little-endian U256; borrow rates start at 1.0
+/// and only grow, so anything that does not fit in its low 128 bits is corrupt
+fn read_big_fraction(data: &[u8], offset: usize) -> Option<u128> {
+    let high = &data[offset + 16..offset + 32];
+    high.iter().all(|byte| *byte == 0).then(|| read_u128(data, offset))
+}
+
+/// One entry of an obligation's `borrows`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ObligationBorrow {
+    pub cumulative_borrow_rate_bsf: u128,
+    pub borrowed_amount_sf: u128,
+}
+
+pub struct ObligationView<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ObligationView<'a> {
+    pub fn load(data: &'a [u8]) -> Result<Self> {
+        require!(
+            data.len() >= obligation_layout::LEN && data[..8] == OBLIGATION_DISCRIMINATOR,
+            GameError::InvalidKaminoObligation
+        );
+        Ok(Self { data })
+    }
+
+    pub fn lending_market(&self) -> Pubkey {
+        read_pubkey(self.data, obligation_layout::LENDING_MARKET)
+    }
+
+    pub fn owner(&self) -> Pubkey {
+        read_pubkey(self.data, obligation_layout::OWNER)
+    }
+
+    /// cTokens of `reserve` the obligation holds as collateral
+    pub fn deposited(&self, reserve: &Pubkey) -> u64 {
+        (0..obligation_layout::MAX_DEPOSITS)
+            .map(|index| obligation_layout::DEPOSITS + index * obligation_layout::DEPOSIT_LEN)
+            .find(|offset| read_pubkey(self.data, offset + obligation_layout::DEPOSIT_RESERVE) == *reserve)
+            .map_or(0, |offset| read_u64(self.data, offset + obligation_layout::DEPOSITED_AMOUNT))
+    }
+
+    /// The obligation's borrow against `reserve`, if it has one
+    pub fn borrow(&self, reserve: &Pubkey) -> Result<Option<ObligationBorrow>> {
+        for index in 0..obligation_layout::MAX_BORROWS {
+            let offset = obligation_layout::BORROWS + index * obligation_layout::BORROW_LEN;
+            if read_pubkey(self.data, offset + obligation_layout::BORROW_RESERVE) != *reserve {
+                continue;
+            }
+
+            let cumulative_borrow_rate_bsf =
+                read_big_fraction(self.data, offset + obligation_layout::BORROW_CUMULATIVE_RATE)
+                    .ok_or(GameError::InvalidKaminoObligation)?;
+            return Ok(Some(ObligationBorrow {
+                cumulative_borrow_rate_bsf,
+                borrowed_amount_sf: read_u128(self.data, offset + obligation_layout::BORROWED_AMOUNT_SF),
+            }));
+        }
+        Ok(None)
+    }
+}
+
+pub struct ReserveView<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ReserveView<'a> {
+    pub fn load(data: &'a [u8]) -> Result<Self> {
+        require!(
+            data.len() >= reserve_layout::LEN && data[..8] == RESERVE_DISCRIMINATOR,
+            GameError::InvalidKaminoReserve
+        );
+        Ok(Self { data })
+    }
+
+    pub fn lending_market(&self) -> Pubkey {
+        read_pubkey(self.data, reserve_layout::LENDING_MARKET)
+    }
+
+    pub fn cumulative_borrow_rate_bsf(&self) -> Result<u128> {
+        read_big_fraction(self.data, reserve_layout::CUMULATIVE_BORROW_RATE)
+            .ok_or(GameError::InvalidKaminoReserve.into())
+    }
+
+    pub fn keys(&self) -> ReserveKeys {
+        ReserveKeys {
+            lending_market: self.lending_market(),
+            liquidity_mint: read_pubkey(self.data, reserve_layout::LIQUIDITY_MINT),
+            liquidity_supply: read_pubkey(self.data, reserve_layout::LIQUIDITY_SUPPLY),
+            liquidity_fee_vault: read_pubkey(self.data, reserve_layout::LIQUIDITY_FEE_VAULT),
+            collateral_mint: read_pubkey(self.data, reserve_layout::COLLATERAL_MINT),
+            collateral_supply: read_pubkey(self.data, reserve_layout::COLLATERAL_SUPPLY),
+        }
+    }
+}
+
+/// The accounts a reserve records, which klend's instructions must be passed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReserveKeys {
+    pub lending_market: Pubkey,
+    pub liquidity_mint: Pubkey,
+    pub liquidity_supply: Pubkey,
+    pub liquidity_fee_vault: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub collateral_supply: Pubkey,
+}
+
+impl ReserveKeys {
+    /// Keys of a Kamino reserve account
+    pub fn load(reserve: &AccountInfo) -> Result<Self> {
+        require!(
+            reserve.owner == &KAMINO_PROGRAM_ID,
+            GameError::InvalidKaminoReserve
+        );
+        Ok(ReserveView::load(&reserve.try_borrow_data()?)?.keys())
+    }
+
+    /// SECURITY: A reserve of `lending_market` lending `liquidity_mint` out
+    /// of `liquidity_supply`, charging its fee to `fee_receiver`
+    pub fn require_borrow(
+        &self,
+        lending_market: &Pubkey,
+        liquidity_mint: &Pubkey,
+        liquidity_supply: &Pubkey,
+        fee_receiver: &Pubkey,
+    ) -> Result<()> {
+        self.require_liquidity(lending_market, liquidity_mint, liquidity_supply)?;
+        require!(self.liquidity_fee_vault == *fee_receiver, GameError::InvalidKaminoReserve);
+        Ok(())
+    }
+
+    /// SECURITY: A reserve of `lending_market` taking deposits of
+    /// `liquidity_mint` into `liquidity_supply`, minting its cTokens with
+    /// `collateral_mint` into `collateral_supply`
+    pub fn require_deposit(
+        &self,
+        lending_market: &Pubkey,
+        liquidity_mint: &Pubkey,
+        liquidity_supply: &Pubkey,
+        collateral_mint: &Pubkey,
+        collateral_supply: &Pubkey,
+    ) -> Result<()> {
+        self.require_liquidity(lending_market, liquidity_mint, liquidity_supply)?;
+        require!(
+            self.collateral_mint == *collateral_mint && self.collateral_supply == *collateral_supply,
+            GameError::InvalidKaminoReserve
+        );
+        Ok(())
+    }
+
+    fn require_liquidity(&self, lending_market: &Pubkey, liquidity_mint: &Pubkey, liquidity_supply: &Pubkey) -> Result<()> {
+        require!(self.lending_market == *lending_market, GameError::InvalidKaminoMarket);
+        require!(self.liquidity_mint == *liquidity_mint, GameError::InvalidMint);
+        require!(self.liquidity_supply == *liquidity_supply, GameError::InvalidKaminoReserve);
+        Ok(())
+    }
+}
+
+/// What `borrow` owes now: its borrowed amount compounded by the reserve's
+/// borrow rate growth since the obligation last saw it, rounded up.
+pub fn accrued_debt(borrow: &ObligationBorrow, reserve_cumulative_borrow_rate_bsf: u128) -> Result<u64> {
+    require!(
+        borrow.cumulative_borrow_rate_bsf > 0
+            && reserve_cumulative_borrow_rate_bsf >= borrow.cumulative_borrow_rate_bsf,
+        GameError::InvalidKaminoReserve
+    );
+
+    let one = 1u128 << FRACTION_BITS;
+    let borrowed = borrow.borrowed_amount_sf.div_ceil(one);
+    let debt = borrowed
+        .checked_mul(reserve_cumulative_borrow_rate_bsf)
+        .ok_or(GameError::ArithmeticOverflow)?
+        .div_ceil(borrow.cumulative_borrow_rate_bsf);
+    u64::try_from(debt).map_err(|_| GameError::ArithmeticOverflow.into())
+}
+
+/// Debt owed by `obligation` to `reserve`, read straight from Kamino's accounts.
+///
+/// Both accounts must belong to Kamino and to `lending_market`. The reserve
+/// should be refreshed in the same transaction, as Kamino's repay requires.
+pub fn obligation_debt(
+    obligation: &AccountInfo,
+    reserve: &AccountInfo,
+    lending_market: &Pubkey,
+) -> Result<u64> {
+    require!(
+        obligation.owner == &KAMINO_PROGRAM_ID,
+        GameError::InvalidKaminoObligation
+    );
+    require!(
+        reserve.owner == &KAMINO_PROGRAM_ID,
+        GameError::InvalidKaminoReserve
+    );
+
+    let obligation_data = obligation.try_borrow_data()?;
+    let reserve_data = reserve.try_borrow_data()?;
+    let obligation_view = ObligationView::load(&obligation_data)?;
+    let reserve_view = ReserveView::load(&reserve_data)?;
+
+    require!(
+        obligation_view.lending_market() == *lending_market
+            && reserve_view.lending_market() == *lending_market,
+        GameError::InvalidKaminoMarket
+    );
+
+    match obligation_view.borrow(reserve.key)? {
+        Some(borrow) => accrued_debt(&borrow, reserve_view.cumulative_borrow_rate_bsf()?),
+        None => Ok(0),
+    }
+}
+
+/// Cumulative borrow rate of a Kamino `reserve`, which every borrow from it
+/// accrues interest by
+pub fn reserve_borrow_rate(reserve: &AccountInfo) -> Result<u128> {
+    require!(
+        reserve.owner == &KAMINO_PROGRAM_ID,
+        GameError::InvalidKaminoReserve
+    );
+    ReserveView::load(&reserve.try_borrow_data()?)?.cumulative_borrow_rate_bsf()
+}
+
+/// Part of `obligation`'s debt to `reserve` that a single loan accounts for,
+/// along with the reserve's current cumulative borrow rate.
+///
+/// The loan owes `principal`, taken on when the reserve's rate stood at
+/// `borrow_rate_bsf`, plus the interest accrued on it since, and never more
+/// than the obligation owes in total. Other borrows the owner keeps on the
+/// same obligation are left for them to repay.
+pub fn loan_debt(
+    obligation: &AccountInfo,
+    reserve: &AccountInfo,
+    lending_market: &Pubkey,
+    principal: u64,
+    borrow_rate_bsf: u128,
+) -> Result<(u64, u128)> {
+    let total = obligation_debt(obligation, reserve, lending_market)?;
+    let rate = reserve_borrow_rate(reserve)?;
+    let own = accrued_debt(
+        &ObligationBorrow {
+            cumulative_borrow_rate_bsf: borrow_rate_bsf,
+            borrowed_amount_sf: (principal as u128) << FRACTION_BITS,
+        },
+        rate,
+    )?;
+    Ok((own.min(total), rate))
+}
+
+/// cTokens of `reserve` that `obligation` holds as collateral, which is
+/// what klend's withdraw takes; the obligation must belong to Kamino and to
+/// `lending_market`.
+pub fn obligation_collateral(
+    obligation: &AccountInfo,
+    reserve: &Pubkey,
+    lending_market: &Pubkey,
+) -> Result<u64> {
+    require!(
+        obligation.owner == &KAMINO_PROGRAM_ID,
+        GameError::InvalidKaminoObligation
+    );
+
+    let data = obligation.try_borrow_data()?;
+    let view = ObligationView::load(&data)?;
+    require!(view.lending_market() == *lending_market, GameError::InvalidKaminoMarket);
+    Ok(view.deposited(reserve))
+}
+
+// ============================================================================
+// INSTRUCTIONS
+// ============================================================================
+
+/// Anchor instruction discriminator, `sha256("global:<name>")[..8]`
+pub const fn sighash(name: &str) -> [u8; 8] {
+    let hash = Sha256::new()
+        .update(b"global:")
+        .update(name.as_bytes())
+        .finalize();
+    let mut discriminator = [0u8; 8];
+    let mut i = 0;
+    while i < 8 {
+        discriminator[i] = hash[i];
+        i += 1;
+    }
+    discriminator
+}
+
+/// Discriminators of the klend instructions the program calls
+pub mod discriminator {
+    use super::sighash;
+
+    pub const DEPOSIT_RESERVE_LIQUIDITY_AND_OBLIGATION_COLLATERAL: [u8; 8] =
+        sighash("deposit_reserve_liquidity_and_obligation_collateral");
+    pub const BORROW_OBLIGATION_LIQUIDITY: [u8; 8] = sighash("borrow_obligation_liquidity");
+    pub const REPAY_OBLIGATION_LIQUIDITY: [u8; 8] = sighash("repay_obligation_liquidity");
+    pub const WITHDRAW_OBLIGATION_COLLATERAL: [u8; 8] = sighash("withdraw_obligation_collateral");
+}
+
+/// klend takes a single `u64` amount after the discriminator
+fn instruction(discriminator: [u8; 8], amount: u64, accounts: Vec<AccountMeta>) -> Instruction {
+    let mut data = Vec::with_capacity(16);
+    data.extend_from_slice(&discriminator);
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id: KAMINO_PROGRAM_ID,
+        accounts,
+        data,
+    }
+}
+
+/// Optional klend accounts that are left out are passed as the klend
+/// program itself, as Anchor expects
+const NONE: AccountMeta = AccountMeta {
+    pubkey: KAMINO_PROGRAM_ID,
+    is_signer: false,
+    is_writable: false,
+};
+
+/// `deposit_reserve_liquidity_and_obligation_collateral`: deposit
+/// `user_source_liquidity` into `reserve` and pledge the minted collateral
+/// to `obligation` in one step
+pub struct DepositReserveLiquidityAndObligationCollateral {
+    pub owner: Pubkey,
+    pub obligation: Pubkey,
+    pub lending_market: Pubkey,
+    pub lending_market_authority: Pubkey,
+    pub reserve: Pubkey,
+    pub reserve_liquidity_mint: Pubkey,
+    pub reserve_liquidity_supply: Pubkey,
+    pub reserve_collateral_mint: Pubkey,
+    pub reserve_destination_deposit_collateral: Pubkey,
+    pub user_source_liquidity: Pubkey,
+    pub collateral_token_program: Pubkey,
+    pub liquidity_token_program: Pubkey,
+}
+
+impl DepositReserveLiquidityAndObligationCollateral {
+    pub fn instruction(&self, liquidity_amount: u64) -> Instruction {
+        instruction(
+            discriminator::DEPOSIT_RESERVE_LIQUIDITY_AND_OBLIGATION_COLLATERAL,
+            liquidity_amount,
+            vec![
+                AccountMeta::new(self.owner, true),
+                AccountMeta::new(self.obligation, false),
+                AccountMeta::new_readonly(self.lending_market, false),
+                AccountMeta::new_readonly(self.lending_market_authority, false),
+                AccountMeta::new(self.reserve, false),
+                AccountMeta::new_readonly(self.reserve_liquidity_mint, false),
+                AccountMeta::new(self.reserve_liquidity_supply, false),
+                AccountMeta::new(self.reserve_collateral_mint, false),
+                AccountMeta::new(self.reserve_destination_deposit_collateral, false),
+                AccountMeta::new(self.user_source_liquidity, false),
+                // placeholder_user_destination_collateral
+                NONE,
+                AccountMeta::new_readonly(self.collateral_token_program, false),
+                AccountMeta::new_readonly(self.liquidity_token_program, false),
+                AccountMeta::new_readonly(sysvar::instructions::ID, false),
+            ],
+        )
+    }
+}
+
+/// `borrow_obligation_liquidity`: borrow from `borrow_reserve` against
+/// `obligation` into `user_destination_liquidity`, without a referrer
+pub struct BorrowObligationLiquidity {
+    pub owner: Pubkey,
+    pub obligation: Pubkey,
+    pub lending_market: Pubkey,
+    pub lending_market_authority: Pubkey,
+    pub borrow_reserve: Pubkey,
+    pub borrow_reserve_liquidity_mint: Pubkey,
+    pub reserve_source_liquidity: Pubkey,
+    pub borrow_reserve_liquidity_fee_receiver: Pubkey,
+    pub user_destination_liquidity: Pubkey,
+    pub token_program: Pubkey,
+}
+
+impl BorrowObligationLiquidity {
+    pub fn instruction(&self, liquidity_amount: u64) -> Instruction {
+        instruction(
+            discriminator::BORROW_OBLIGATION_LIQUIDITY,
+            liquidity_amount,
+            vec![
+                AccountMeta::new_readonly(self.owner, true),
+                AccountMeta::new(self.obligation, false),
+                AccountMeta::new_readonly(self.lending_market, false),
+                AccountMeta::new_readonly(self.lending_market_authority, false),
+                AccountMeta::new(self.borrow_reserve, false),
+                AccountMeta::new_readonly(self.borrow_reserve_liquidity_mint, false),
+                AccountMeta::new(self.reserve_source_liquidity, false),
+                AccountMeta::new(self.borrow_reserve_liquidity_fee_receiver, false),
+                AccountMeta::new(self.user_destination_liquidity, false),
+                // referrer_token_state
+                NONE,
+                AccountMeta::new_readonly(self.token_program, false),
+                AccountMeta::new_readonly(sysvar::instructions::ID, false),
+            ],
+        )
+    }
+}
+
+/// `repay_obligation_liquidity`: repay `obligation`'s debt to
+/// `repay_reserve` out of `user_source_liquidity`, owned by `owner`
+pub struct RepayObligationLiquidity {
+    pub owner: Pubkey,
+    pub obligation: Pubkey,
+    pub lending_market: Pubkey,
+    pub repay_reserve: Pubkey,
+    pub reserve_liquidity_mint: Pubkey,
+    pub reserve_destination_liquidity: Pubkey,
+    pub user_source_liquidity: Pubkey,
+    pub token_program: Pubkey,
+}
+
+impl RepayObligationLiquidity {
+    pub fn instruction(&self, liquidity_amount: u64) -> Instruction {
+        instruction(
+            discriminator::REPAY_OBLIGATION_LIQUIDITY,
+            liquidity_amount,
+            vec![
+                AccountMeta::new_readonly(self.owner, true),
+                AccountMeta::new(self.obligation, false),
+                AccountMeta::new_readonly(self.lending_market, false),
+                AccountMeta::new(self.repay_reserve, false),
+                AccountMeta::new_readonly(self.reserve_liquidity_mint, false),
+                AccountMeta::new(self.reserve_destination_liquidity, false),
+                AccountMeta::new(self.user_source_liquidity, false),
+                AccountMeta::new_readonly(self.token_program, false),
+                AccountMeta::new_readonly(sysvar::instructions::ID, false),
+            ],
+        )
+    }
+}
+
+/// `withdraw_obligation_collateral`: release collateral pledged to
+/// `obligation` back to `user_destination_collateral`
+pub struct WithdrawObligationCollateral {
+    pub owner: Pubkey,
+    pub obligation: Pubkey,
+    pub lending_market: Pubkey,
+    pub lending_market_authority: Pubkey,
+    pub withdraw_reserve: Pubkey,
+    pub reserve_source_collateral: Pubkey,
+    pub user_destination_collateral: Pubkey,
+    pub token_program: Pubkey,
+}
+
+impl WithdrawObligationCollateral {
+    pub fn instruction(&self, collateral_amount: u64) -> Instruction {
+        instruction(
+            discriminator::WITHDRAW_OBLIGATION_COLLATERAL,
+            collateral_amount,
+            vec![
+                AccountMeta::new_readonly(self.owner, true),
+                AccountMeta::new(self.obligation, false),
+                AccountMeta::new_readonly(self.lending_market, false),
+                AccountMeta::new_readonly(self.lending_market_authority, false),
+                AccountMeta::new(self.withdraw_reserve, false),
+                AccountMeta::new(self.reserve_source_collateral, false),
+                AccountMeta::new(self.user_destination_collateral, false),
+                AccountMeta::new_readonly(self.token_program, false),
+                AccountMeta::new_readonly(sysvar::instructions::ID, false),
+            ],
+        )
+    }
+}
@@ -3,38 +3,29 @@
 #![allow(unused_imports)]
 
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::program_pack::Pack;
-use anchor_spl::token_2022::{Token2022, transfer_checked, TransferChecked};
-use anchor_spl::token_2022::spl_token_2022::state::Mint as MintState;
-use anchor_spl::token_2022::spl_token_2022::ID as TOKEN_2022_PROGRAM_ID;
+use anchor_spl::token_2022::Token2022;
 use ephemeral_rollups_sdk::anchor::ephemeral;
 use ephemeral_vrf_sdk::anchor::vrf;
 
 pub mod constants;
 pub mod errors;
+pub mod events;
 pub mod instructions;
+pub mod kamino;
+pub mod oracle;
+pub mod settlement;
+pub mod squads;
 pub mod state;
+pub mod token;
 
 use instructions::*;
-use state::{GameMode, AiDifficulty, GameStatus}; // GameStatus used in delegate_game and finalize_game
-use constants::MAGICBLOCK_DELEGATION_PROGRAM_ID;
-
-// Helper function to get mint decimals with ownership validation
-fn get_mint_decimals(mint_account: &AccountInfo) -> Result<u8> {
-    // SECURITY: Validate mint is owned by Token-2022 program
-    require!(
-        mint_account.owner == &TOKEN_2022_PROGRAM_ID,
-        errors::GameError::InvalidMint
-    );
-    
-    let mint_data = mint_account.try_borrow_data()?;
-    let mint = MintState::unpack(&mint_data)?;
-    Ok(mint.decimals)
-}
+use settlement::PayoutPlan;
+use token::GameTreasury;
+use state::{GameMode, AiDifficulty, GameStatus, Game, RewardAsset, TreasuryRewards}; // GameStatus used in delegate_game and finalize_game
 
 declare_id!("HA71kX5tHESphxAhqdnrhHWawmEHWHLdiHjeyfA82Bam");
 
-// #[ephemeral]  // TEMPORARY: Disabled for Windows build
+#[ephemeral]
 #[program]
 pub mod magic_roulette {
     use super::*;
@@ -48,9 +39,64 @@ pub mod magic_roulette {
         instructions::initialize_platform(ctx, platform_fee_bps, treasury_fee_bps)
     }
 
+    /// Set how long a player has to take their shot
+    pub fn set_turn_timeout(ctx: Context<UpdatePlatformConfig>, turn_timeout_seconds: i64) -> Result<()> {
+        instructions::set_turn_timeout(ctx, turn_timeout_seconds)
+    }
+
+    /// Set the share of each treasury fee credited back to players as rewards
+    pub fn set_rewards_share(ctx: Context<UpdatePlatformConfig>, rewards_share_bps: u16) -> Result<()> {
+        instructions::set_rewards_share(ctx, rewards_share_bps)
+    }
+
+    /// Set the minimum collateral ratio for Kamino loans
+    pub fn set_min_collateral_ratio(ctx: Context<UpdatePlatformConfig>, min_collateral_ratio_bps: u16) -> Result<()> {
+        instructions::set_min_collateral_ratio(ctx, min_collateral_ratio_bps)
+    }
+
+    /// Register the Pyth and Switchboard feeds that price a loan asset
+    pub fn set_asset_oracle(
+        ctx: Context<SetAssetOracle>,
+        pyth_feed_id: [u8; 32],
+        switchboard_feed: Pubkey,
+    ) -> Result<()> {
+        instructions::set_asset_oracle(ctx, pyth_feed_id, switchboard_feed)
+    }
+
+    /// Pause or resume game creation and joining
+    pub fn set_paused(ctx: Context<UpdatePlatformConfig>, paused: bool) -> Result<()> {
+        instructions::set_paused(ctx, paused)
+    }
+
+    /// Update platform and treasury fees (20% combined cap)
+    pub fn update_fees(
+        ctx: Context<UpdatePlatformConfig>,
+        platform_fee_bps: u16,
+        treasury_fee_bps: u16,
+    ) -> Result<()> {
+        instructions::update_fees(ctx, platform_fee_bps, treasury_fee_bps)
+    }
+
+    /// Point treasury fees at a new wallet (not on multisig platforms,
+    /// whose treasury is their Squads treasury vault)
+    pub fn update_treasury(ctx: Context<UpdatePlatformConfig>, new_treasury: Pubkey) -> Result<()> {
+        instructions::update_treasury(ctx, new_treasury)
+    }
+
+    /// Step 1 of an authority transfer: nominate the new authority
+    pub fn propose_authority(ctx: Context<UpdatePlatformConfig>, new_authority: Pubkey) -> Result<()> {
+        instructions::propose_authority(ctx, new_authority)
+    }
+
+    /// Step 2 of an authority transfer: the nominee takes over, and any
+    /// previous multisig loses its admin rights
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        instructions::accept_authority(ctx)
+    }
+
     /// Create a new game (1v1 or 2v2)
-    pub fn create_game(
-        ctx: Context<CreateGame>,
+    pub fn create_game<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateGame<'info>>,
         game_mode: GameMode,
         entry_fee: u64,
         vrf_seed: [u8; 32],
@@ -59,244 +105,187 @@ pub mod magic_roulette {
     }
 
     /// Join an existing game
-    pub fn join_game(ctx: Context<JoinGame>) -> Result<()> {
+    pub fn join_game<'info>(ctx: Context<'_, '_, 'info, 'info, JoinGame<'info>>) -> Result<()> {
         instructions::join_game(ctx)
     }
 
+    /// Cancel a game that never filled and refund every entrant
+    /// 
+    /// Creator can cancel any time before the game fills; anyone can once it expires
+    pub fn cancel_game<'info>(ctx: Context<'_, '_, 'info, 'info, CancelGame<'info>>) -> Result<()> {
+        instructions::cancel_game(ctx)
+    }
+
     /// Delegate game to Ephemeral Rollup
     /// 
     /// SECURITY: Permission check - only creator or platform authority can delegate
-    /// NOTE: Delegation is handled by the client using MagicBlock SDK
     pub fn delegate_game(ctx: Context<DelegateGame>) -> Result<()> {
-        let game = &mut ctx.accounts.game;
-        
-        // Permission check: Only creator or platform authority
-        require!(
-            ctx.accounts.payer.key() == game.creator 
-                || ctx.accounts.payer.key() == ctx.accounts.platform_config.authority,
-            errors::GameError::Unauthorized
-        );
-        
-        // Update status - actual delegation happens via CPI in client
-        game.status = GameStatus::Delegated;
-        
-        msg!("✅ Game {} marked for delegation to ER", game.game_id);
-        msg!("   Players can now take shots with sub-10ms latency");
-        
-        Ok(())
+        instructions::delegate_game(ctx)
     }
 
     /// Request VRF randomness for game (executed on ER)
     pub fn request_vrf_randomness(ctx: Context<RequestVrfRandomness>) -> Result<()> {
-        let game = &mut ctx.accounts.game;
-        require!(!game.vrf_pending, errors::GameError::VrfRequestPending);
-        game.vrf_pending = true;
-        msg!("VRF randomness requested for game {}", game.game_id);
-        Ok(())
+        instructions::request_vrf_randomness(ctx)
     }
 
-    /// VRF callback - receives verifiable randomness
+    /// VRF callback - receives verifiable randomness and starts the game
+    /// 
+    /// SECURITY: Only accepted when signed by the VRF program identity
     pub fn request_vrf_randomness_callback(ctx: Context<VrfCallback>, randomness: [u8; 32]) -> Result<()> {
-        let game = &mut ctx.accounts.game;
-        
-        // Store randomness in game state
-        game.vrf_result = randomness;
-        game.vrf_pending = false;
-        game.vrf_fulfilled = true;
-        
-        msg!("VRF randomness received for game {}", game.game_id);
-        msg!("Randomness: {:?}", &randomness[0..8]);
-        
-        Ok(())
-    }
-
-    /// Process VRF randomness result (legacy - kept for compatibility)
-    pub fn process_vrf_result(ctx: Context<ProcessVrfResult>, randomness: [u8; 32]) -> Result<()> {
-        instructions::process_vrf_result(ctx, randomness)
+        instructions::request_vrf_randomness_callback(ctx, randomness)
     }
 
-    /// Player takes a shot (executed in Private ER)
+    /// Player pulls the trigger, requesting the randomness their shot is
+    /// resolved with (executed in Private ER)
     pub fn take_shot(ctx: Context<TakeShot>) -> Result<()> {
         instructions::take_shot(ctx)
     }
 
+    /// Re-request a pulled trigger's randomness once its callback is overdue
+    /// (permissionless), so a lost callback cannot stall the game
+    pub fn retry_shot(ctx: Context<RetryShot>) -> Result<()> {
+        instructions::retry_shot(ctx)
+    }
+
+    /// VRF callback - resolves the pending shot with its own randomness
+    /// 
+    /// SECURITY: Only accepted when signed by the VRF program identity
+    pub fn resolve_shot_callback(ctx: Context<VrfCallback>, randomness: [u8; 32]) -> Result<()> {
+        instructions::resolve_shot_callback(ctx, randomness)
+    }
+
+    /// Forfeit the current player's turn once their deadline has passed (permissionless)
+    pub fn claim_timeout_forfeit(ctx: Context<ClaimTimeoutForfeit>) -> Result<()> {
+        instructions::claim_timeout_forfeit(ctx)
+    }
+
     /// Commit game state from ER to base layer
-    /// NOTE: Commit is handled by the client using MagicBlock SDK
     pub fn commit_game(ctx: Context<CommitGame>) -> Result<()> {
-        let game = &ctx.accounts.game;
-        
-        msg!("💾 Game {} state ready for commit to base layer", game.game_id);
-        msg!("   Status: {:?}", game.status);
-        msg!("   Shots taken: {}", game.shots_taken);
-        
-        Ok(())
+        instructions::commit_game(ctx)
     }
 
-    /// Undelegate game from ER and return to base layer
-    /// NOTE: Undelegation is handled by the client using MagicBlock SDK
+    /// Commit final state and undelegate game from ER back to base layer
     pub fn undelegate_game(ctx: Context<UndelegateGame>) -> Result<()> {
-        let game = &ctx.accounts.game;
-        
-        msg!("✅ Game {} ready for undelegation from Ephemeral Rollup", game.game_id);
-        msg!("   Final status: {:?}", game.status);
-        msg!("   Winner: Team {:?}", game.winner_team);
-        
-        Ok(())
+        instructions::undelegate_game(ctx)
     }
 
     /// Finalize game and distribute winnings
-    pub fn finalize_game(ctx: Context<FinalizeGame>) -> Result<()> {
+    pub fn finalize_game<'info>(ctx: Context<'_, '_, 'info, 'info, FinalizeGame<'info>>) -> Result<()> {
         let game = &mut ctx.accounts.game;
-        let platform_config = &ctx.accounts.platform_config;
-        
-        // SECURITY: Validate game status
-        require!(
-            game.status == GameStatus::Finished,
-            errors::GameError::GameNotInProgress
-        );
-        
-        require!(
-            game.winner_team.is_some(),
-            errors::GameError::GameNotInProgress
-        );
+        let winners = settlement::finished_game_winners(game)?;
+        let winning_team = game.winner_team.unwrap();
         
         // SECURITY: Skip distribution for practice mode (AI games)
         if game.is_practice_mode {
-            game.status = GameStatus::Cancelled;
+            game.transition(GameStatus::Settled)?;
+            emit_cpi!(PayoutPlan::default().finalized_event(game.key(), game.game_id, winning_team));
             msg!("🎮 Practice game finished - no prizes distributed");
-            msg!("Winner: Team {}", game.winner_team.unwrap());
+            msg!("Winner: Team {}", winning_team);
             return Ok(());
         }
         
-        // Calculate prize distribution
-        let total_pot = game.total_pot;
-        
-        let platform_fee = (total_pot as u128)
-            .checked_mul(platform_config.platform_fee_bps as u128)
-            .ok_or(errors::GameError::ArithmeticOverflow)?
-            .checked_div(10000)
-            .ok_or(errors::GameError::ArithmeticOverflow)? as u64;
-        
-        let treasury_fee = (total_pot as u128)
-            .checked_mul(platform_config.treasury_fee_bps as u128)
-            .ok_or(errors::GameError::ArithmeticOverflow)?
-            .checked_div(10000)
-            .ok_or(errors::GameError::ArithmeticOverflow)? as u64;
-        
-        let winner_amount = total_pot
-            .checked_sub(platform_fee)
-            .ok_or(errors::GameError::ArithmeticOverflow)?
-            .checked_sub(treasury_fee)
-            .ok_or(errors::GameError::ArithmeticOverflow)?;
-        
-        // Get winner count
-        let winning_team = game.winner_team.unwrap();
-        let winner_count = if winning_team == 0 {
-            game.team_a_count as usize
-        } else {
-            game.team_b_count as usize
-        };
+        // SECURITY: Validate winner accounts match actual game participants
+        settlement::require_winner_accounts(
+            &winners,
+            &[ctx.accounts.winner1.key(), ctx.accounts.winner2.key()],
+        )?;
         
-        let per_winner = winner_amount / winner_count as u64;
+        // Calculate prize distribution
+        let platform_config = &mut ctx.accounts.platform_config;
+        let mut plan = PayoutPlan::new(
+            game.total_pot,
+            platform_config.platform_fee_bps,
+            platform_config.treasury_fee_bps,
+            winners.len(),
+        )?;
         
         // Game PDA signer seeds
         let game_id_bytes = game.game_id.to_le_bytes();
         let bump_seed = [game.bump];
         let seeds: &[&[u8]] = &[
-            b"game",
+            Game::SEED,
             game_id_bytes.as_ref(),
             &bump_seed,
         ];
         let signer = &[seeds];
         
-        // EFFECTS: Update platform stats before interactions
-        let platform_config = &mut ctx.accounts.platform_config;
-        platform_config.total_volume = platform_config.total_volume
-            .checked_add(total_pot)
-            .ok_or(errors::GameError::ArithmeticOverflow)?;
-        
-        platform_config.treasury_balance = platform_config.treasury_balance
-            .checked_add(treasury_fee)
-            .ok_or(errors::GameError::ArithmeticOverflow)?;
-        
-        game.status = GameStatus::Cancelled;
+        // Platform-mint games hand part of the treasury fee back to their players
+        let rewards_pool = if ctx.accounts.mint.key() == platform_config.platform_mint {
+            TreasuryRewards::reward_pool(plan.treasury_fee, platform_config.rewards_share_bps)?
+        } else {
+            0
+        };
+        let (stats_accounts, rewards_accounts, hook_accounts) =
+            instructions::split_entrant_accounts(game, ctx.remaining_accounts, rewards_pool > 0)?;
         
-        // INTERACTIONS: Distribute funds
-        // Distribute to platform
-        transfer_checked(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                TransferChecked {
-                    from: ctx.accounts.game_vault.to_account_info(),
-                    to: ctx.accounts.platform_vault.to_account_info(),
-                    authority: game.to_account_info(),
-                    mint: ctx.accounts.mint.to_account_info(),
-                },
-                signer,
-            ),
-            platform_fee,
-            get_mint_decimals(&ctx.accounts.mint)?,
+        let treasury = GameTreasury::token(
+            ctx.accounts.game_vault.to_account_info(),
+            game.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            hook_accounts,
+            signer,
         )?;
+        treasury.require_funded(plan.total_pot)?;
         
-        // Distribute to treasury
-        transfer_checked(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                TransferChecked {
-                    from: ctx.accounts.game_vault.to_account_info(),
-                    to: ctx.accounts.treasury_vault.to_account_info(),
-                    authority: game.to_account_info(),
-                    mint: ctx.accounts.mint.to_account_info(),
-                },
-                signer,
-            ),
-            treasury_fee,
-            get_mint_decimals(&ctx.accounts.mint)?,
-        )?;
+        // EFFECTS: Update platform stats before interactions, setting the
+        // players' cut of the treasury fee aside
+        if rewards_pool > 0 {
+            require!(
+                ctx.accounts.rewards_vault.is_some(),
+                errors::GameError::InvalidVaultOwner
+            );
+            let (_, player_rewards) = instructions::treasury_reward_shares(game, rewards_pool)?;
+            plan = plan.with_player_rewards(player_rewards)?;
+        }
+        
+        settlement::record_settlement(game, platform_config, &plan)?;
+        instructions::record_player_stats(game, stats_accounts, plan.per_winner)?;
         
-        // Distribute to winner(s)
-        transfer_checked(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                TransferChecked {
-                    from: ctx.accounts.game_vault.to_account_info(),
-                    to: ctx.accounts.winner1_token_account.to_account_info(),
-                    authority: game.to_account_info(),
-                    mint: ctx.accounts.mint.to_account_info(),
-                },
-                signer,
-            ),
-            per_winner,
-            get_mint_decimals(&ctx.accounts.mint)?,
+        // INTERACTIONS: Fund the rewards vault, then distribute to
+        // platform, treasury and winner(s)
+        let rewards_vault = ctx.accounts.rewards_vault.as_ref().map(|vault| vault.to_account_info());
+        let winner_accounts = [
+            ctx.accounts.winner1_token_account.to_account_info(),
+            ctx.accounts.winner2_token_account.to_account_info(),
+        ];
+        let rewards_received = treasury.execute(
+            &plan,
+            &ctx.accounts.platform_vault.to_account_info(),
+            &ctx.accounts.treasury_vault.to_account_info(),
+            rewards_vault.as_ref(),
+            &winner_accounts[..winners.len()],
         )?;
         
-        if winner_count == 2 {
-            transfer_checked(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    TransferChecked {
-                        from: ctx.accounts.game_vault.to_account_info(),
-                        to: ctx.accounts.winner2_token_account.to_account_info(),
-                        authority: game.to_account_info(),
-                        mint: ctx.accounts.mint.to_account_info(),
-                    },
-                    signer,
-                ),
-                per_winner,
-                get_mint_decimals(&ctx.accounts.mint)?,
+        // EFFECTS: Credit players with the rewards that reached the vault,
+        // net of any transfer fee withheld on the way
+        if plan.player_rewards > 0 {
+            instructions::credit_treasury_rewards(
+                game,
+                rewards_accounts,
+                rewards_received,
+                RewardAsset::PlatformMint,
             )?;
         }
         
+        emit_cpi!(plan.finalized_event(game.key(), game.game_id, winning_team));
+        
         msg!("Game {} finalized", game.game_id);
-        msg!("Each winner receives: {}", per_winner);
-        msg!("Platform fee: {}", platform_fee);
-        msg!("Treasury fee: {}", treasury_fee);
+        msg!("Each winner receives: {}", plan.per_winner);
+        msg!("Platform fee: {}", plan.platform_fee);
+        msg!("Treasury fee: {}", plan.treasury_fee);
+        msg!("Player rewards: {}", plan.player_rewards);
         
         Ok(())
     }
 
+    /// Close a settled game and its vault, returning their rent to the creator
+    pub fn close_game<'info>(ctx: Context<'_, '_, 'info, 'info, CloseGame<'info>>) -> Result<()> {
+        instructions::close_game(ctx)
+    }
+
     /// Claim rewards from treasury
-    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+    pub fn claim_rewards<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimRewards<'info>>) -> Result<()> {
         instructions::claim_rewards(ctx)
     }
 
@@ -309,7 +298,7 @@ pub mod magic_roulette {
         instructions::create_ai_game(ctx, ai_difficulty, vrf_seed)
     }
 
-    /// AI bot takes a shot
+    /// AI bot pulls the trigger; resolved by resolve_shot_callback
     pub fn ai_take_shot(ctx: Context<AiTakeShot>) -> Result<()> {
         instructions::ai_take_shot(ctx)
     }
@@ -333,11 +322,16 @@ pub mod magic_roulette {
         instructions::join_game_sol(ctx)
     }
 
-    /// Finalize game and distribute SOL winnings
+    /// Finalize game, distribute SOL winnings and return rent to the creator
     pub fn finalize_game_sol(ctx: Context<FinalizeGameSol>) -> Result<()> {
         instructions::finalize_game_sol(ctx)
     }
 
+    /// Cancel a SOL game that never filled and refund every entrant
+    pub fn cancel_game_sol<'info>(ctx: Context<'_, '_, 'info, 'info, CancelGameSol<'info>>) -> Result<()> {
+        instructions::cancel_game_sol(ctx)
+    }
+
     // ========================================================================
     // KAMINO INTEGRATION INSTRUCTIONS
     // ========================================================================
@@ -348,16 +342,42 @@ pub mod magic_roulette {
         game_mode: GameMode,
         entry_fee: u64,
         collateral_amount: u64,
+        borrow_amount: u64,
         vrf_seed: [u8; 32],
     ) -> Result<()> {
-        instructions::create_game_with_loan(ctx, game_mode, entry_fee, collateral_amount, vrf_seed)
+        instructions::create_game_with_loan(ctx, game_mode, entry_fee, collateral_amount, borrow_amount, vrf_seed)
+    }
+
+    /// Join a SOL game with the entry fee borrowed from Kamino
+    pub fn join_game_with_loan(
+        ctx: Context<JoinGameWithLoan>,
+        collateral_amount: u64,
+        borrow_amount: u64,
+    ) -> Result<()> {
+        instructions::join_game_with_loan(ctx, collateral_amount, borrow_amount)
     }
 
     /// Finalize game and auto-repay Kamino loan from winnings
-    pub fn finalize_game_with_loan(ctx: Context<FinalizeGameWithLoan>) -> Result<()> {
+    pub fn finalize_game_with_loan<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FinalizeGameWithLoan<'info>>,
+    ) -> Result<()> {
         instructions::finalize_game_with_loan(ctx)
     }
 
+    /// Cancel a loan game that never filled, refund joiners and repay the Kamino loan
+    pub fn cancel_game_with_loan<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelGameWithLoan<'info>>,
+    ) -> Result<()> {
+        instructions::cancel_game_with_loan(ctx)
+    }
+
+    /// Repay a borrower's remaining Kamino debt and return their collateral
+    /// cTokens. Only the borrower can settle; an unsettled loan stays on
+    /// Kamino, where an unhealthy obligation is open to liquidation
+    pub fn settle_loan(ctx: Context<SettleLoan>) -> Result<()> {
+        instructions::settle_loan(ctx)
+    }
+
     // ========================================================================
     // SQUADS MULTISIG INTEGRATION INSTRUCTIONS
     // ========================================================================
@@ -370,4 +390,10 @@ pub mod magic_roulette {
     ) -> Result<()> {
         instructions::initialize_platform_with_multisig(ctx, platform_fee_bps, treasury_fee_bps)
     }
+
+    /// Withdraw from the treasury; executed by the Squads treasury vault
+    /// once the multisig has approved the withdrawal
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        instructions::withdraw_treasury(ctx, amount)
+    }
 }
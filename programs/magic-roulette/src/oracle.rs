@@ -0,0 +1,244 @@
+//! Oracle prices for valuing Kamino loan collateral.
+//!
+//! Each asset has an `AssetOracle` naming its trusted Pyth feed and
+//! Switchboard On-Demand feed. Pyth is read first and Switchboard is the
+//! fallback; either price must be fresh and have a tight confidence interval.
+//! Like `kamino`, both account types are read in place at their published
+//! offsets instead of pulling in the oracle SDKs.
+
+use anchor_lang::prelude::*;
+use crate::{
+    constants::{
+        ORACLE_MAX_AGE_SECONDS, ORACLE_MAX_CONFIDENCE_BPS, PYTH_RECEIVER_PROGRAM_ID,
+        SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+    },
+    errors::GameError,
+    state::AssetOracle,
+};
+
+/// `sha256("account:PriceUpdateV2")[..8]`
+pub const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [0x22, 0xf1, 0x23, 0x63, 0x9d, 0x7e, 0xf4, 0xcd];
+/// `sha256("account:PullFeedAccountData")[..8]`
+pub const PULL_FEED_DISCRIMINATOR: [u8; 8] = [0xc4, 0x1b, 0x6c, 0xc4, 0x0a, 0xd7, 0xdb, 0x28];
+
+/// Byte offsets into a Pyth PriceUpdateV2 account, discriminator included.
+/// Only fully verified updates are accepted, which fixes the layout.
+pub mod price_update_layout {
+    pub const VERIFICATION_LEVEL: usize = 40;
+    /// `VerificationLevel::Full`
+    pub const FULL: u8 = 1;
+    pub const FEED_ID: usize = 41;
+    pub const PRICE: usize = 73;
+    pub const CONF: usize = 81;
+    pub const EXPONENT: usize = 89;
+    pub const PUBLISH_TIME: usize = 93;
+    pub const LEN: usize = 133;
+}
+
+/// Byte offsets into a Switchboard On-Demand PullFeedAccountData account,
+/// discriminator included
+pub mod pull_feed_layout {
+    pub const LAST_UPDATE_TIMESTAMP: usize = 2216;
+    /// `result.value`, an i128 with 18 decimals
+    pub const RESULT_VALUE: usize = 2264;
+    /// `result.std_dev`, same scale as the value
+    pub const RESULT_STD_DEV: usize = 2280;
+    pub const LEN: usize = RESULT_VALUE + 128;
+    /// Switchboard values carry 18 decimals
+    pub const DECIMALS: u32 = 18;
+}
+
+/// Switchboard values are scaled down to 9 decimals so they multiply with
+/// token amounts the same way Pyth prices do
+const SWITCHBOARD_EXPONENT: i32 = -9;
+
+fn read_i64(data: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_i128(data: &[u8], offset: usize) -> i128 {
+    i128::from_le_bytes(data[offset..offset + 16].try_into().unwrap())
+}
+
+/// A USD price of `price * 10^exponent`, give or take `confidence`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OraclePrice {
+    pub price: u128,
+    pub confidence: u128,
+    pub exponent: i32,
+    pub publish_time: i64,
+}
+
+impl OraclePrice {
+    /// SECURITY: Reject prices that are not positive, older than
+    /// ORACLE_MAX_AGE_SECONDS, or less certain than ORACLE_MAX_CONFIDENCE_BPS
+    pub fn validate(self, now: i64) -> Result<Self> {
+        require!(self.price > 0, GameError::InvalidOracleAccount);
+        require!(
+            now.saturating_sub(self.publish_time) <= ORACLE_MAX_AGE_SECONDS,
+            GameError::StaleOraclePrice
+        );
+
+        let confidence_bps = self.confidence
+            .checked_mul(10000)
+            .ok_or(GameError::ArithmeticOverflow)?
+            / self.price;
+        require!(
+            confidence_bps <= ORACLE_MAX_CONFIDENCE_BPS as u128,
+            GameError::OracleConfidenceTooWide
+        );
+        Ok(self)
+    }
+
+    /// USD value of `amount` base units of a `decimals` token at `price`
+    fn value_at(&self, price: u128, amount: u64, decimals: u8) -> Result<UsdValue> {
+        Ok(UsdValue {
+            mantissa: price
+                .checked_mul(amount as u128)
+                .ok_or(GameError::ArithmeticOverflow)?,
+            exponent: self.exponent - decimals as i32,
+        })
+    }
+
+    /// Value at the bottom of the confidence interval, for collateral
+    pub fn lower_value(&self, amount: u64, decimals: u8) -> Result<UsdValue> {
+        self.value_at(self.price - self.confidence.min(self.price), amount, decimals)
+    }
+
+    /// Value at the top of the confidence interval, for debt
+    pub fn upper_value(&self, amount: u64, decimals: u8) -> Result<UsdValue> {
+        let price = self.price
+            .checked_add(self.confidence)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        self.value_at(price, amount, decimals)
+    }
+}
+
+/// `mantissa * 10^exponent` USD
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UsdValue {
+    pub mantissa: u128,
+    pub exponent: i32,
+}
+
+impl UsdValue {
+    /// Mantissa at a lower (or equal) exponent
+    fn rescale(&self, exponent: i32) -> Result<u128> {
+        let shift = u32::try_from(self.exponent - exponent).map_err(|_| GameError::ArithmeticOverflow)?;
+        10u128
+            .checked_pow(shift)
+            .and_then(|scale| self.mantissa.checked_mul(scale))
+            .ok_or(GameError::ArithmeticOverflow.into())
+    }
+
+    /// This value times `bps / 10000`, at least `other` times `other_bps / 10000`
+    pub fn covers(&self, bps: u16, other: &UsdValue, other_bps: u16) -> Result<bool> {
+        let exponent = self.exponent.min(other.exponent);
+        let lhs = self.rescale(exponent)?
+            .checked_mul(bps as u128)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        let rhs = other.rescale(exponent)?
+            .checked_mul(other_bps as u128)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        Ok(lhs >= rhs)
+    }
+}
+
+/// Fully verified Pyth price update for `feed_id`
+pub fn pyth_price(account: &AccountInfo, feed_id: &[u8; 32]) -> Result<OraclePrice> {
+    require!(
+        account.owner == &PYTH_RECEIVER_PROGRAM_ID,
+        GameError::InvalidOracleAccount
+    );
+
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() >= price_update_layout::LEN
+            && data[..8] == PRICE_UPDATE_V2_DISCRIMINATOR
+            && data[price_update_layout::VERIFICATION_LEVEL] == price_update_layout::FULL
+            && data[price_update_layout::FEED_ID..price_update_layout::FEED_ID + 32] == feed_id[..],
+        GameError::InvalidOracleAccount
+    );
+
+    let price = read_i64(&data, price_update_layout::PRICE);
+    let exponent = i32::from_le_bytes(
+        data[price_update_layout::EXPONENT..price_update_layout::EXPONENT + 4].try_into().unwrap(),
+    );
+    Ok(OraclePrice {
+        price: u128::try_from(price).map_err(|_| GameError::InvalidOracleAccount)?,
+        confidence: read_u64(&data, price_update_layout::CONF) as u128,
+        exponent,
+        publish_time: read_i64(&data, price_update_layout::PUBLISH_TIME),
+    })
+}
+
+/// Latest result of a Switchboard On-Demand pull feed, its standard
+/// deviation as the confidence
+pub fn switchboard_price(account: &AccountInfo) -> Result<OraclePrice> {
+    require!(
+        account.owner == &SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+        GameError::InvalidOracleAccount
+    );
+
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() >= pull_feed_layout::LEN && data[..8] == PULL_FEED_DISCRIMINATOR,
+        GameError::InvalidOracleAccount
+    );
+
+    let scale = 10i128.pow(pull_feed_layout::DECIMALS - (-SWITCHBOARD_EXPONENT) as u32);
+    let value = read_i128(&data, pull_feed_layout::RESULT_VALUE) / scale;
+    let std_dev = read_i128(&data, pull_feed_layout::RESULT_STD_DEV) / scale;
+    Ok(OraclePrice {
+        price: u128::try_from(value).map_err(|_| GameError::InvalidOracleAccount)?,
+        confidence: u128::try_from(std_dev).map_err(|_| GameError::InvalidOracleAccount)?,
+        exponent: SWITCHBOARD_EXPONENT,
+        publish_time: read_i64(&data, pull_feed_layout::LAST_UPDATE_TIMESTAMP),
+    })
+}
+
+/// Price of the asset behind `asset_oracle`: its Pyth feed if that is
+/// usable, otherwise its Switchboard feed
+pub fn price(
+    asset_oracle: &AssetOracle,
+    pyth: &AccountInfo,
+    switchboard: &AccountInfo,
+    now: i64,
+) -> Result<OraclePrice> {
+    pyth_price(pyth, &asset_oracle.pyth_feed_id)
+        .and_then(|price| price.validate(now))
+        .or_else(|_| {
+            // SECURITY: The fallback must be the feed registered for this asset
+            require_keys_eq!(
+                switchboard.key(),
+                asset_oracle.switchboard_feed,
+                GameError::InvalidOracleAccount
+            );
+            switchboard_price(switchboard)?.validate(now)
+        })
+}
+
+/// SECURITY: Collateral valued at the bottom of its price range must be worth
+/// `min_collateral_ratio_bps` of the loan valued at the top of its range
+pub fn require_collateral_ratio(
+    collateral_amount: u64,
+    collateral_decimals: u8,
+    collateral_price: &OraclePrice,
+    loan_amount: u64,
+    loan_decimals: u8,
+    loan_price: &OraclePrice,
+    min_collateral_ratio_bps: u16,
+) -> Result<()> {
+    let collateral = collateral_price.lower_value(collateral_amount, collateral_decimals)?;
+    let loan = loan_price.upper_value(loan_amount, loan_decimals)?;
+
+    require!(
+        collateral.covers(10000, &loan, min_collateral_ratio_bps)?,
+        GameError::InsufficientCollateral
+    );
+    Ok(())
+}
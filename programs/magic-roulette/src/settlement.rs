@@ -0,0 +1,223 @@
+//! Settlement of a finished game.
+//!
+//! Every finalize instruction - SOL, Token-2022 or Kamino-backed - builds the
+//! same `PayoutPlan` from the pot and the platform fees, records it, and then
+//! hands it to the vault's `GameTreasury` to execute.
+//!
+//! Rounding is deterministic and never strands funds: fees round down, so
+//! their truncation stays in the winners' share, and whatever is left after
+//! splitting that share equally between winners goes to the treasury.
+
+use anchor_lang::prelude::*;
+use crate::{errors::GameError, events::GameFinalized, state::*};
+
+/// Basis-point share of `amount`, rounded down
+pub fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    let share = (amount as u128)
+        .checked_mul(bps as u128)
+        .ok_or(GameError::ArithmeticOverflow)?
+        .checked_div(10000)
+        .ok_or(GameError::ArithmeticOverflow)?;
+    u64::try_from(share).map_err(|_| GameError::ArithmeticOverflow.into())
+}
+
+/// Platform and treasury cut of a finished pot; the rest goes to the winners
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeSplit {
+    pub platform_fee: u64,
+    pub treasury_fee: u64,
+    pub winner_amount: u64,
+}
+
+impl FeeSplit {
+    pub fn new(total_pot: u64, platform_fee_bps: u16, treasury_fee_bps: u16) -> Result<Self> {
+        let platform_fee = bps_of(total_pot, platform_fee_bps)?;
+        let treasury_fee = bps_of(total_pot, treasury_fee_bps)?;
+        let winner_amount = total_pot
+            .checked_sub(platform_fee)
+            .ok_or(GameError::ArithmeticOverflow)?
+            .checked_sub(treasury_fee)
+            .ok_or(GameError::ArithmeticOverflow)?;
+
+        Ok(Self { platform_fee, treasury_fee, winner_amount })
+    }
+
+    /// Equal share of `winner_amount` for each of `winner_count` winners
+    pub fn per_winner(&self, winner_count: usize) -> Result<u64> {
+        self.winner_amount
+            .checked_div(winner_count as u64)
+            .ok_or(GameError::ArithmeticOverflow.into())
+    }
+}
+
+/// A team has at most two players, so a game has at most two winners
+pub const MAX_WINNERS: usize = 2;
+
+/// Where every unit of a finished pot goes.
+///
+/// `platform_fee + treasury_fee + per_winner * winner_count + dust` is always
+/// exactly `total_pot`. A winner who borrowed their entry fee has their loan
+/// repaid out of their own share.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PayoutPlan {
+    pub total_pot: u64,
+    pub platform_fee: u64,
+    /// Treasury cut, including any `player_rewards`
+    pub treasury_fee: u64,
+    /// Part of the treasury fee credited back to players as TreasuryRewards
+    pub player_rewards: u64,
+    /// Kamino repayments taken out of the winners' shares, in total
+    pub loan_repayment: u64,
+    /// Kamino repayment taken out of each winner's share, in winner order
+    pub loan_repayments: [u64; MAX_WINNERS],
+    pub winner_count: u8,
+    pub per_winner: u64,
+    /// Remainder of the equal split between winners, paid to the treasury
+    pub dust: u64,
+}
+
+impl PayoutPlan {
+    pub fn new(
+        total_pot: u64,
+        platform_fee_bps: u16,
+        treasury_fee_bps: u16,
+        winner_count: usize,
+    ) -> Result<Self> {
+        require!(
+            winner_count > 0 && winner_count <= MAX_WINNERS,
+            GameError::InvalidWinner
+        );
+        let split = FeeSplit::new(total_pot, platform_fee_bps, treasury_fee_bps)?;
+
+        Self {
+            total_pot,
+            platform_fee: split.platform_fee,
+            treasury_fee: split.treasury_fee,
+            winner_count: u8::try_from(winner_count).map_err(|_| GameError::InvalidWinner)?,
+            ..Self::default()
+        }
+        .share_winnings(split.winner_amount)
+    }
+
+    /// Repay `amount` of a loan out of the share of the `winner`th winner
+    pub fn with_loan_repayment(self, winner: usize, amount: u64) -> Result<Self> {
+        require!(winner < self.winner_count as usize, GameError::InvalidWinner);
+
+        let mut loan_repayments = self.loan_repayments;
+        loan_repayments[winner] = loan_repayments[winner]
+            .checked_add(amount)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        require!(
+            loan_repayments[winner] <= self.per_winner,
+            GameError::InsufficientWinningsForRepayment
+        );
+        let loan_repayment = self.loan_repayment
+            .checked_add(amount)
+            .ok_or(GameError::ArithmeticOverflow)?;
+
+        Ok(Self { loan_repayment, loan_repayments, ..self })
+    }
+
+    /// What the `winner`th winner is paid once their loan is repaid
+    pub fn winner_payout(&self, winner: usize) -> u64 {
+        self.per_winner - self.loan_repayments[winner]
+    }
+
+    /// Hand `amount` of the treasury fee back to players as rewards
+    pub fn with_player_rewards(self, amount: u64) -> Result<Self> {
+        let player_rewards = self.player_rewards
+            .checked_add(amount)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        require!(player_rewards <= self.treasury_fee, GameError::ArithmeticOverflow);
+
+        Ok(Self { player_rewards, ..self })
+    }
+
+    /// What the treasury itself receives: its fee less player rewards,
+    /// plus the dust of the winner split
+    pub fn treasury_payout(&self) -> u64 {
+        self.treasury_fee - self.player_rewards + self.dust
+    }
+
+    /// Everything the winners are owed together, before the equal split
+    pub fn winnings(&self) -> Result<u64> {
+        self.per_winner
+            .checked_mul(self.winner_count as u64)
+            .and_then(|paid| paid.checked_add(self.dust))
+            .ok_or(GameError::ArithmeticOverflow.into())
+    }
+
+    fn share_winnings(self, winnings: u64) -> Result<Self> {
+        let winner_count = self.winner_count as u64;
+        let per_winner = winnings
+            .checked_div(winner_count)
+            .ok_or(GameError::ArithmeticOverflow)?;
+
+        Ok(Self {
+            per_winner,
+            dust: winnings % winner_count,
+            ..self
+        })
+    }
+
+    pub fn finalized_event(&self, game: Pubkey, game_id: u64, winner_team: u8) -> GameFinalized {
+        GameFinalized {
+            game,
+            game_id,
+            winner_team,
+            total_pot: self.total_pot,
+            platform_fee: self.platform_fee,
+            treasury_fee: self.treasury_fee,
+            per_winner: self.per_winner,
+            rewards_credited: self.player_rewards,
+        }
+    }
+}
+
+/// Winners of a game that is ready to be finalized
+pub fn finished_game_winners(game: &Game) -> Result<Vec<Pubkey>> {
+    // SECURITY: Only finished games can be settled
+    lifecycle::transition(game.status, GameStatus::Settled)?;
+
+    require!(
+        game.winner_team.is_some(),
+        GameError::GameNotInProgress
+    );
+
+    game.winners()
+}
+
+/// SECURITY: Winner accounts must be the winning team, in slot order.
+/// Extra accounts (winner 2 in a 1v1) are ignored.
+pub fn require_winner_accounts(winners: &[Pubkey], accounts: &[Pubkey]) -> Result<()> {
+    require!(accounts.len() >= winners.len(), GameError::InvalidWinner);
+    for (winner, account) in winners.iter().zip(accounts) {
+        require!(winner == account, GameError::InvalidWinner);
+    }
+    Ok(())
+}
+
+/// EFFECTS: Book a settled pot into the platform stats and mark the game settled.
+///
+/// `treasury_balance` is in lamports, what withdraw_treasury pays out, so
+/// only SOL games (including loan games) add to it. Token games pay their
+/// treasury fee straight into the treasury's token account.
+pub fn record_settlement(
+    game: &mut Game,
+    platform_config: &mut PlatformConfig,
+    plan: &PayoutPlan,
+) -> Result<()> {
+    platform_config.total_volume = platform_config.total_volume
+        .checked_add(plan.total_pot)
+        .ok_or(GameError::ArithmeticOverflow)?;
+
+    if game.mint.is_none() {
+        platform_config.treasury_balance = platform_config.treasury_balance
+            .checked_add(plan.treasury_payout())
+            .ok_or(GameError::ArithmeticOverflow)?;
+    }
+
+    game.transition(GameStatus::Settled)?;
+
+    Ok(())
+}
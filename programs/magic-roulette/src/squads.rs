@@ -0,0 +1,34 @@
+//! Squads v4 multisig accounts.
+//!
+//! A Squads multisig keeps its funds in vault PDAs of the Squads program,
+//! and the program only signs for a vault while executing a vault
+//! transaction the members approved. A signature from the vault derived
+//! here is therefore the multisig's approval.
+
+use anchor_lang::prelude::*;
+use crate::{constants::SQUADS_PROGRAM_ID, errors::GameError};
+
+/// `sha256("account:Multisig")[..8]`
+pub const MULTISIG_DISCRIMINATOR: [u8; 8] = [0xe0, 0x74, 0x79, 0xba, 0x44, 0xa1, 0x4f, 0xec];
+
+pub const SEED_PREFIX: &[u8] = b"multisig";
+pub const SEED_VAULT: &[u8] = b"vault";
+
+/// Vault `index` of `multisig`, as the Squads program derives it
+pub fn vault_address(multisig: &Pubkey, index: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SEED_PREFIX, multisig.as_ref(), SEED_VAULT, &[index]],
+        &SQUADS_PROGRAM_ID,
+    )
+}
+
+/// SECURITY: `account` must be a multisig created by the Squads program
+pub fn require_multisig(account: &AccountInfo) -> Result<()> {
+    require!(account.owner == &SQUADS_PROGRAM_ID, GameError::InvalidMultisig);
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() >= 8 && data[..8] == MULTISIG_DISCRIMINATOR,
+        GameError::InvalidMultisig
+    );
+    Ok(())
+}
@@ -1,32 +1,55 @@
+//! Program account layouts.
+//!
+//! These layouts are not compatible with accounts written by earlier
+//! deployments: `PlatformConfig` and `Game` gained fields in the middle of
+//! their layouts and `Game` lost its single-borrower loan fields, so existing
+//! accounts no longer deserialize. Upgrading a live deployment needs a fresh
+//! program (or a migration that settles, closes and re-creates every
+//! `PlatformConfig` and `Game`), not an in-place upgrade.
+
 use anchor_lang::prelude::*;
+use crate::constants::{GAME_EXPIRY_SECONDS, MIN_ENTRY_FEE, MIN_SOL_ENTRY_FEE, SQUADS_PLATFORM_VAULT_INDEX};
+use crate::errors::GameError;
+use crate::squads;
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
 pub enum GameMode {
+    #[default]
     OneVsOne,
     TwoVsTwo,
     HumanVsAi,  // New: Human vs AI mode
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+/// Game lifecycle, see `lifecycle` for the legal transitions
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
 pub enum GameStatus {
+    #[default]
     WaitingForPlayers,
     Delegated,
     InProgress,
     Finished,
-    Cancelled,
+    Settled,     // Pot paid out by finalize
+    Ready,       // Every seat filled, waiting to be delegated
+    AwaitingRandomness,
+    Expired,     // Never started before GAME_EXPIRY_SECONDS, refunded
+    Refunded,    // Cancelled by the creator before the game filled
 }
 
 #[account]
 pub struct PlatformConfig {
     pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,  // Set by propose_authority, cleared on accept
     pub treasury: Pubkey,
     pub platform_mint: Pubkey,  // Official platform token
     pub platform_fee_bps: u16,  // 500 = 5%
     pub treasury_fee_bps: u16,  // 1000 = 10%
+    pub rewards_share_bps: u16, // Share of treasury_fee credited to players, 2000 = 20%
     pub total_games: u64,
     pub total_volume: u64,
-    pub treasury_balance: u64,
+    pub treasury_balance: u64,  // Lamports of SOL-game treasury fees not yet withdrawn
     pub paused: bool,  // Emergency pause
+    pub turn_timeout_seconds: i64,  // Idle time before a turn can be forfeited
+    pub min_collateral_ratio_bps: u16,  // Kamino loan collateral floor, 11000 = 110%
     
     // Squads multisig integration
     pub multisig_authority: Option<Pubkey>,  // Squads multisig PDA (if using multisig)
@@ -37,9 +60,59 @@ pub struct PlatformConfig {
 }
 
 impl PlatformConfig {
-    pub const LEN: usize = 8 + 32 + 32 + 32 + 2 + 2 + 8 + 8 + 8 + 1 
+    /// PDA seed - the single source of truth for the platform config address
+    pub const SEED: &'static [u8] = b"platform";
+
+    pub const LEN: usize = 8 + 32 + (1 + 32) + 32 + 32 + 2 + 2 + 2 + 8 + 8 + 8 + 1 + 8 + 2
         + (1 + 32) + (1 + 32) + (1 + 32)  // Squads fields
         + 1;
+
+    pub fn find_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], &crate::ID)
+    }
+
+    /// Wallet that receives platform fees (Squads vault 0 when multisig)
+    pub fn platform_fee_recipient(&self) -> Pubkey {
+        self.platform_vault.unwrap_or(self.authority)
+    }
+
+    /// Signer of admin instructions: the authority wallet, or on multisig
+    /// platforms Squads vault 0, which the Squads program only signs for
+    /// while executing a transaction the members approved. The multisig
+    /// account itself can never sign.
+    pub fn admin(&self) -> Pubkey {
+        match self.multisig_authority {
+            Some(multisig) => squads::vault_address(&multisig, SQUADS_PLATFORM_VAULT_INDEX).0,
+            None => self.authority,
+        }
+    }
+
+    /// SECURITY: A multisig platform's treasury stays its Squads treasury
+    /// vault; other platforms may move it to any wallet
+    pub fn require_treasury_update(&self, new_treasury: &Pubkey) -> Result<()> {
+        require!(self.multisig_authority.is_none(), GameError::TreasuryManagedByMultisig);
+        require!(*new_treasury != Pubkey::default(), GameError::InvalidTreasury);
+        Ok(())
+    }
+
+    /// EFFECTS: Book a withdrawal of `amount` lamports from the treasury
+    pub fn debit_treasury(&mut self, amount: u64) -> Result<()> {
+        self.treasury_balance = self.treasury_balance
+            .checked_sub(amount)
+            .ok_or(GameError::InsufficientTreasuryBalance)?;
+        Ok(())
+    }
+
+    /// Hand the platform to `new_authority`, a wallet. A previous multisig
+    /// loses its admin rights and its vaults stop receiving platform fees;
+    /// the treasury is left for the new authority to update.
+    pub fn transfer_authority(&mut self, new_authority: Pubkey) {
+        self.authority = new_authority;
+        self.pending_authority = None;
+        self.multisig_authority = None;
+        self.platform_vault = None;
+        self.treasury_vault = None;
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
@@ -50,6 +123,7 @@ pub enum AiDifficulty {
 }
 
 #[account]
+#[derive(Default)]
 pub struct Game {
     pub game_id: u64,
     pub creator: Pubkey,
@@ -57,6 +131,7 @@ pub struct Game {
     pub status: GameStatus,
     pub entry_fee: u64,
     pub total_pot: u64,
+    pub mint: Option<Pubkey>,  // Token-2022 entry fee mint, None for SOL and practice games
     
     // AI settings (Practice mode - no real money)
     pub is_ai_game: bool,
@@ -64,11 +139,8 @@ pub struct Game {
     pub ai_player: Option<Pubkey>,  // Bot wallet address
     pub is_practice_mode: bool,  // True for AI games (no entry fee, no prizes)
     
-    // Kamino loan integration
-    pub has_loan: bool,                      // True if creator borrowed from Kamino
-    pub loan_obligation: Option<Pubkey>,     // Kamino obligation account
-    pub collateral_amount: u64,              // Amount of collateral deposited
-    pub loan_amount: u64,                    // Amount borrowed from Kamino
+    // Kamino loan integration, one LoanPosition per leveraged seat
+    pub loan_seats: u8,  // Bitmask of seats whose entry fee was borrowed
     
     // Players (max 4 for 2v2)
     pub team_a: [Pubkey; 2],
@@ -77,15 +149,20 @@ pub struct Game {
     pub team_b_count: u8,
     
     // Game state
-    pub bullet_chamber: u8,  // 1-6, set by VRF
+    pub bullet_chamber: u8,  // 1-6, revealed only when the bullet fires
     pub current_chamber: u8, // Current position
-    pub current_turn: u8,    // Which player's turn
+    pub current_turn: u8,    // Position in turn_order
     pub shots_taken: u8,
+    pub turn_order: [u8; turn_order::MAX_SEATS],  // Seat rotation, locked when the game fills
+    pub turn_order_len: u8,
+    pub turn_timeout_seconds: i64,   // Snapshot of PlatformConfig.turn_timeout_seconds
+    pub turn_deadline: Option<i64>,  // Current player must shoot before this
+    pub shots_survived: [u8; turn_order::MAX_SEATS],  // Per seat, flushed to PlayerStats on finalize
     
     // VRF (MagicBlock VRF Plugin)
     pub vrf_seed: [u8; 32],
-    pub vrf_result: [u8; 32],
-    pub vrf_pending: bool,
+    pub vrf_result: [u8; 32],  // Latest VRF output, stored once it has been used
+    pub vrf_pending: bool,     // Randomness requested, to start the game or resolve a shot
     pub vrf_fulfilled: bool,
     
     // Results
@@ -97,15 +174,27 @@ pub struct Game {
 }
 
 impl Game {
-    pub const LEN: usize = 8 + 8 + 32 + 1 + 1 + 8 + 8 
+    /// PDA seeds: game is `[SEED, game_id]`, its SOL or token vault is `[VAULT_SEED, game]`
+    pub const SEED: &'static [u8] = b"game";
+    pub const VAULT_SEED: &'static [u8] = b"game_vault";
+
+    pub const LEN: usize = 8 + 8 + 32 + 1 + 1 + 8 + 8 + (1 + 32)
         + 1 + (1 + 1) + (1 + 32) + 1  // AI fields + practice mode
-        + 1 + (1 + 32) + 8 + 8  // Kamino loan fields
+        + 1  // Kamino loan seats
         + (32 * 4) + 1 + 1 
-        + 1 + 1 + 1 + 1 
+        + 1 + 1 + 1 + 1 + 4 + 1 + 8 + (1 + 8) + 4  // Turn state + rotation + timeout + survivals
         + 32 + 32 + 1 + 1  // VRF fields (seed, result, pending, fulfilled)
-        + (1 + 1) + 8 + (1 + 8) 
+        + (1 + 1) + 8 + (1 + 8)
         + 1;
 
+    pub fn find_address(game_id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, game_id.to_le_bytes().as_ref()], &crate::ID)
+    }
+
+    pub fn find_vault_address(game: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::VAULT_SEED, game.as_ref()], &crate::ID)
+    }
+
     pub fn is_full(&self) -> bool {
         match self.game_mode {
             GameMode::OneVsOne => self.team_a_count == 1 && self.team_b_count == 1,
@@ -122,15 +211,390 @@ impl Game {
         }
     }
 
-    pub fn get_current_player(&self) -> Pubkey {
-        let team = self.current_turn % 2;
-        let player_idx = (self.current_turn / 2) as usize;
+    /// Move to `to` if the lifecycle allows it from the current status
+    pub fn transition(&mut self, to: GameStatus) -> Result<()> {
+        self.status = lifecycle::transition(self.status, to)?;
+        Ok(())
+    }
+
+    /// Players that have paid into the pot, in seat order (team A, then team B)
+    pub fn entrants(&self) -> Vec<Pubkey> {
+        self.team_a[..self.team_a_count as usize]
+            .iter()
+            .chain(self.team_b[..self.team_b_count as usize].iter())
+            .copied()
+            .collect()
+    }
+
+    /// Seat a player joining a 1v1 or 2v2 game. Seats fill in turn order:
+    /// A1 (the creator), B1, A2, B2
+    pub fn add_joiner(&mut self, player: Pubkey) -> Result<()> {
+        // SECURITY: One seat per player, and never more seats than the mode has
+        require!(player != self.creator, GameError::CannotJoinOwnGame);
+        require!(!self.is_full(), GameError::GameFull);
+        require!(self.seat_of(&player).is_none(), GameError::PlayerAlreadyInGame);
+
+        match self.game_mode {
+            GameMode::OneVsOne => {
+                // Join team B (team A already has creator)
+                self.team_b[0] = player;
+                self.team_b_count = 1;
+            }
+            GameMode::TwoVsTwo => {
+                if self.team_a_count == 1 && self.team_b_count == 0 {
+                    self.team_b[0] = player;
+                    self.team_b_count = 1;
+                } else if self.team_a_count == 1 && self.team_b_count == 1 {
+                    self.team_a[1] = player;
+                    self.team_a_count = 2;
+                } else if self.team_a_count == 2 && self.team_b_count == 1 {
+                    self.team_b[1] = player;
+                    self.team_b_count = 2;
+                }
+            }
+            GameMode::HumanVsAi => {
+                return Err(GameError::CannotJoinAiGame.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Credit an entry fee that arrived in the vault to the pot
+    pub fn add_to_pot(&mut self, amount: u64) -> Result<()> {
+        self.total_pot = self.total_pot
+            .checked_add(amount)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// SECURITY: Reject entry fees below MIN_SOL_ENTRY_FEE in SOL games, or
+    /// below MIN_ENTRY_FEE scaled to the mint's decimals in token games
+    pub fn require_entry_fee(entry_fee: u64, token_decimals: Option<u8>) -> Result<()> {
+        let min_fee = match token_decimals {
+            None => MIN_SOL_ENTRY_FEE,
+            Some(decimals) => 10u128
+                .checked_pow(decimals.into())
+                .and_then(|scale| scale.checked_mul(MIN_ENTRY_FEE.into()))
+                .and_then(|min_fee| u64::try_from(min_fee / 1_000_000_000).ok())
+                .ok_or(GameError::ArithmeticOverflow)?,
+        };
+        require!(entry_fee >= min_fee.max(1), GameError::InsufficientEntryFee);
+        Ok(())
+    }
+
+    /// Seat of an entrant, see `turn_order` for the encoding
+    pub fn seat_of(&self, player: &Pubkey) -> Option<u8> {
+        let team_a = self.team_a[..self.team_a_count as usize].iter().position(|p| p == player);
+        let team_b = self.team_b[..self.team_b_count as usize].iter().position(|p| p == player);
+        match (team_a, team_b) {
+            (Some(slot), _) => Some(turn_order::seat(0, slot as u8)),
+            (None, Some(slot)) => Some(turn_order::seat(1, slot as u8)),
+            (None, None) => None,
+        }
+    }
+
+    /// True if any entrant paid with a Kamino loan
+    pub fn has_loan(&self) -> bool {
+        self.loan_seats != 0
+    }
+
+    /// Mark `player`'s seat as paid for by a loan
+    pub fn record_loan(&mut self, player: &Pubkey) -> Result<()> {
+        let seat = self.seat_of(player).ok_or(GameError::InvalidLoanPosition)?;
+        self.loan_seats |= 1 << seat;
+        Ok(())
+    }
+
+    /// Entrants whose entry fee was borrowed, in seat order
+    pub fn borrowers(&self) -> Vec<Pubkey> {
+        self.entrants()
+            .into_iter()
+            .filter(|player| {
+                self.seat_of(player)
+                    .is_some_and(|seat| self.loan_seats & (1 << seat) != 0)
+            })
+            .collect()
+    }
+
+    /// Players on the winning team, in slot order
+    pub fn winners(&self) -> Result<Vec<Pubkey>> {
+        let winners = match self.winner_team.ok_or(GameError::GameNotInProgress)? {
+            0 => &self.team_a[..self.team_a_count as usize],
+            _ => &self.team_b[..self.team_b_count as usize],
+        };
+        Ok(winners.to_vec())
+    }
+
+    /// True once the game has waited longer than GAME_EXPIRY_SECONDS for players
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.created_at.saturating_add(GAME_EXPIRY_SECONDS)
+    }
+
+    /// End a game that never started. Past GAME_EXPIRY_SECONDS anyone may
+    /// cancel it and it ends Expired; before that only the creator may, while
+    /// seats are still open, and it ends Refunded
+    pub fn cancel(&mut self, caller: &Pubkey, now: i64) -> Result<()> {
+        let expired = self.is_expired(now);
+        self.transition(if expired { GameStatus::Expired } else { GameStatus::Refunded })?;
+        if !expired {
+            require!(*caller == self.creator, GameError::GameNotExpired);
+            require!(!self.is_full(), GameError::GameFull);
+        }
+        self.finished_at = Some(now);
+        Ok(())
+    }
+
+    /// What each entrant gets back when the game is cancelled, in seat order.
+    /// Every entrant paid the same entry fee, so the pot splits evenly and
+    /// any remainder goes to the creator
+    pub fn refunds(&self) -> Vec<(Pubkey, u64)> {
+        let entrants = self.entrants();
+        let share = self.total_pot / entrants.len().max(1) as u64;
+        let remainder = self.total_pot - share * entrants.len() as u64;
+        entrants
+            .into_iter()
+            .enumerate()
+            .map(|(seat, entrant)| (entrant, if seat == 0 { share + remainder } else { share }))
+            .collect()
+    }
+
+    /// Start the clock for whoever's turn it is now
+    pub fn reset_turn_deadline(&mut self, now: i64) -> Result<()> {
+        self.turn_deadline = Some(
+            now.checked_add(self.turn_timeout_seconds)
+                .ok_or(GameError::ArithmeticOverflow)?,
+        );
+        Ok(())
+    }
+
+    /// Lock the seat rotation once every seat is filled
+    pub fn lock_turn_order(&mut self) {
+        let (rotation, len) = turn_order::build_rotation(&self.team_a, &self.team_b);
+        self.turn_order = rotation;
+        self.turn_order_len = len;
+        self.current_turn = 0;
+    }
+
+    /// Seat whose turn it is
+    pub fn current_seat(&self) -> Result<u8> {
+        require!(self.turn_order_len > 0, GameError::GameNotReady);
+        Ok(self.turn_order[self.current_turn as usize % self.turn_order_len as usize])
+    }
+
+    pub fn get_current_player(&self) -> Result<Pubkey> {
+        let seat = self.current_seat()?;
+        let player = if turn_order::team_of(seat) == 0 {
+            self.team_a[turn_order::slot_of(seat)]
+        } else {
+            self.team_b[turn_order::slot_of(seat)]
+        };
+        Ok(player)
+    }
+
+    /// Team of the player whose turn it is (0 = team_a, 1 = team_b)
+    pub fn current_team(&self) -> Result<u8> {
+        Ok(turn_order::team_of(self.current_seat()?))
+    }
+
+    /// Pull the trigger for the current shot, returning the VRF caller seed
+    /// to request its randomness with.
+    ///
+    /// The shot stays unresolved until that randomness arrives, so nothing
+    /// stored on the game decides it in advance. The turn deadline restarts
+    /// and now bounds the wait for the callback: the shooter has already
+    /// acted, so it cannot be forfeited, but once it passes the request can
+    /// be retried (see `retry_trigger`).
+    pub fn pull_trigger(&mut self, now: i64) -> Result<[u8; 32]> {
+        require!(self.status == GameStatus::InProgress, GameError::GameNotInProgress);
+        require!(self.vrf_fulfilled, GameError::VrfNotReady);
+        require!(!self.vrf_pending, GameError::VrfRequestPending);
+        
+        self.vrf_pending = true;
+        self.reset_turn_deadline(now)?;
+        Ok(chamber::shot_seed(&self.vrf_seed, self.shots_taken))
+    }
+    
+    /// Re-request the randomness of a pulled trigger whose callback never
+    /// arrived before the deadline, returning the same caller seed. Nobody
+    /// has seen an output for the shot yet, so a fresh draw is as fair as
+    /// the lost one; whichever callback lands first resolves it
+    pub fn retry_trigger(&mut self, now: i64) -> Result<[u8; 32]> {
+        require!(
+            self.status == GameStatus::InProgress && self.vrf_pending,
+            GameError::VrfNotReady
+        );
+        let deadline = self.turn_deadline.ok_or(GameError::GameNotInProgress)?;
+        require!(now > deadline, GameError::TurnNotExpired);
+        
+        self.reset_turn_deadline(now)?;
+        Ok(chamber::shot_seed(&self.vrf_seed, self.shots_taken))
+    }
+    
+    /// Resolve the pulled trigger from its fresh VRF output, returning
+    /// whether the shot fired. A fired shot loses the game for the shooter's
+    /// team; otherwise the survivor is credited and the gun passes on
+    pub fn resolve_shot(&mut self, randomness: [u8; 32], now: i64) -> Result<bool> {
+        require!(
+            self.status == GameStatus::InProgress && self.vrf_pending,
+            GameError::VrfNotReady
+        );
+        
+        self.vrf_pending = false;
+        self.vrf_result = randomness;
+        let fired = chamber::shot_fires(&randomness, self.shots_taken);
+        self.shots_taken += 1;
         
-        if team == 0 {
-            self.team_a[player_idx]
+        if fired {
+            let losing_team = self.current_team()?;
+            self.winner_team = Some(1 - losing_team);
+            self.bullet_chamber = self.current_chamber;
+            self.transition(GameStatus::Finished)?;
+            self.finished_at = Some(now);
+            self.turn_deadline = None;
         } else {
-            self.team_b[player_idx]
+            self.record_survived_shot()?;
+            self.advance_turn(now)?;
         }
+        Ok(fired)
+    }
+
+    /// Credit the current seat with surviving a trigger pull
+    pub fn record_survived_shot(&mut self) -> Result<()> {
+        let seat = self.current_seat()? as usize;
+        self.shots_survived[seat] = self.shots_survived[seat].saturating_add(1);
+        Ok(())
+    }
+
+    /// Rotate the cylinder and pass the gun to the next seat
+    pub fn advance_turn(&mut self, now: i64) -> Result<()> {
+        self.current_chamber = self.current_chamber % 6 + 1;
+        self.current_turn = turn_order::next_turn(self.current_turn, self.turn_order_len);
+        self.reset_turn_deadline(now)
+    }
+}
+
+/// Chamber resolution
+///
+/// The bullet position is never stored while the game is live, and no
+/// randomness that could predict it is either. Every trigger pull requests
+/// its own VRF output and is resolved from that output and the shot index
+/// only once it arrives: shot `k` fires with probability 1 / (6 - k), so the
+/// firing shot is uniform over chambers 1-6. Each output is published with
+/// its `ShotTaken` event, so anyone can replay every shot afterwards.
+pub mod chamber {
+    use solana_sha256_hasher::hashv;
+
+    pub const CHAMBERS: u8 = 6;
+
+    /// VRF caller seed for the shot at `shot_index`, distinct for every shot
+    pub fn shot_seed(vrf_seed: &[u8; 32], shot_index: u8) -> [u8; 32] {
+        hashv(&[vrf_seed.as_ref(), &[shot_index]]).to_bytes()
+    }
+
+    /// Whether the shot at `shot_index` (0-based) fires, given the VRF
+    /// output drawn for that shot
+    pub fn shot_fires(randomness: &[u8; 32], shot_index: u8) -> bool {
+        let remaining = (CHAMBERS - shot_index % CHAMBERS) as u64;
+        let digest = hashv(&[randomness.as_ref(), &[shot_index]]).to_bytes();
+        let mut roll = [0u8; 8];
+        roll.copy_from_slice(&digest[..8]);
+        u64::from_le_bytes(roll) % remaining == 0
+    }
+}
+
+/// Turn-order engine
+///
+/// Seats are encoded as `team * 2 + slot` (0 = A1, 1 = A2, 2 = B1, 3 = B2).
+/// The rotation interleaves the teams (A1, B1, A2, B2) and skips empty seats,
+/// so 1v1 and HumanVsAi rotate A1, B1 and 2v2 rotates through all four.
+pub mod turn_order {
+    use anchor_lang::prelude::Pubkey;
+
+    pub const MAX_SEATS: usize = 4;
+
+    pub fn seat(team: u8, slot: u8) -> u8 {
+        team * 2 + slot
+    }
+
+    pub fn team_of(seat: u8) -> u8 {
+        seat / 2
+    }
+
+    pub fn slot_of(seat: u8) -> usize {
+        (seat % 2) as usize
+    }
+
+    /// Build the seat rotation from the filled team slots
+    pub fn build_rotation(team_a: &[Pubkey; 2], team_b: &[Pubkey; 2]) -> ([u8; MAX_SEATS], u8) {
+        let mut rotation = [0u8; MAX_SEATS];
+        let mut len = 0u8;
+        for slot in 0..2u8 {
+            for (team, players) in [(0u8, team_a), (1u8, team_b)] {
+                if players[slot as usize] != Pubkey::default() {
+                    rotation[len as usize] = seat(team, slot);
+                    len += 1;
+                }
+            }
+        }
+        (rotation, len)
+    }
+
+    /// Rotation position after `turn`, wrapping back to the first seat
+    pub fn next_turn(turn: u8, len: u8) -> u8 {
+        if len == 0 {
+            return 0;
+        }
+        (turn + 1) % len
+    }
+}
+
+/// Game state machine
+///
+/// WaitingForPlayers -> Ready -> Delegated -> AwaitingRandomness ->
+/// InProgress -> Finished -> Settled. A game that never fills may be
+/// Refunded by its creator, and one that never starts becomes Expired once
+/// anyone cancels it after GAME_EXPIRY_SECONDS. Every status change goes
+/// through `transition`; Refunded, Expired and Settled are terminal.
+pub mod lifecycle {
+    use anchor_lang::prelude::*;
+    use super::GameStatus::{self, *};
+    use crate::errors::GameError;
+
+    /// Every status a game can reach
+    pub const ALL_STATUSES: [GameStatus; 9] = [
+        WaitingForPlayers,
+        Ready,
+        Delegated,
+        AwaitingRandomness,
+        InProgress,
+        Finished,
+        Settled,
+        Refunded,
+        Expired,
+    ];
+
+    pub fn can_transition(from: GameStatus, to: GameStatus) -> bool {
+        matches!(
+            (from, to),
+            (WaitingForPlayers, Ready)
+                | (Ready, Delegated)
+                | (Delegated, AwaitingRandomness)
+                | (AwaitingRandomness, InProgress)
+                | (InProgress, Finished)
+                | (Finished, Settled)
+                | (WaitingForPlayers, Refunded)
+                | (WaitingForPlayers | Ready, Expired)
+        )
+    }
+
+    /// The single guard for status changes: returns `to` if `from -> to` is legal
+    pub fn transition(from: GameStatus, to: GameStatus) -> Result<GameStatus> {
+        require!(can_transition(from, to), GameError::InvalidGameStatus);
+        Ok(to)
+    }
+
+    pub fn is_terminal(status: GameStatus) -> bool {
+        matches!(status, Settled | Refunded | Expired)
     }
 }
 
@@ -146,18 +610,201 @@ pub struct PlayerStats {
 }
 
 impl PlayerStats {
+    pub const SEED: &'static [u8] = b"stats";
+
     pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    pub fn find_address(player: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, player.as_ref()], &crate::ID)
+    }
+
+    /// Bind a stats account created by `init_if_needed` to its player
+    pub fn ensure_initialized(&mut self, player: Pubkey, bump: u8) {
+        if self.player == Pubkey::default() {
+            self.player = player;
+            self.bump = bump;
+        }
+    }
+
+    /// Fold one finished game into the running totals
+    pub fn record_game(&mut self, wager: u64, winnings: Option<u64>, shots_survived: u8) -> Result<()> {
+        self.games_played = self.games_played.checked_add(1).ok_or(GameError::ArithmeticOverflow)?;
+        self.total_wagered = self.total_wagered.checked_add(wager).ok_or(GameError::ArithmeticOverflow)?;
+        self.shots_survived = self.shots_survived
+            .checked_add(shots_survived as u64)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        if let Some(amount) = winnings {
+            self.games_won = self.games_won.checked_add(1).ok_or(GameError::ArithmeticOverflow)?;
+            self.total_winnings = self.total_winnings.checked_add(amount).ok_or(GameError::ArithmeticOverflow)?;
+        }
+        Ok(())
+    }
 }
 
+/// A player's share of treasury fees, kept per asset: platform-mint games
+/// pay rewards in the platform mint, SOL and Kamino loan games in lamports.
+/// Games in any other mint pay their whole treasury fee to the treasury.
 #[account]
 pub struct TreasuryRewards {
     pub player: Pubkey,
-    pub claimable_amount: u64,
+    pub claimable_amount: u64,  // Platform mint units
     pub total_claimed: u64,
     pub last_claim: i64,
     pub bump: u8,
+    pub claimable_lamports: u64,  // Held by the platform config PDA
+    pub total_claimed_lamports: u64,
 }
 
 impl TreasuryRewards {
-    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 1;
+    pub const SEED: &'static [u8] = b"rewards";
+
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 1 + 8 + 8;
+
+    pub fn find_address(player: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, player.as_ref()], &crate::ID)
+    }
+
+    /// Bind a rewards account created by `init_if_needed` to its player
+    pub fn ensure_initialized(&mut self, player: Pubkey, bump: u8) {
+        if self.player == Pubkey::default() {
+            self.player = player;
+            self.bump = bump;
+        }
+    }
+
+    /// Part of a game's treasury fee set aside for its players
+    pub fn reward_pool(treasury_fee: u64, rewards_share_bps: u16) -> Result<u64> {
+        crate::settlement::bps_of(treasury_fee, rewards_share_bps)
+    }
+
+    /// A player's cut of the pool, weighted by what they wagered (rounded down)
+    pub fn wager_share(pool: u64, wager: u64, total_wagered: u64) -> Result<u64> {
+        if total_wagered == 0 {
+            return Ok(0);
+        }
+        Ok((pool as u128)
+            .checked_mul(wager as u128)
+            .ok_or(GameError::ArithmeticOverflow)?
+            .checked_div(total_wagered as u128)
+            .ok_or(GameError::ArithmeticOverflow)? as u64)
+    }
+
+    pub fn credit(&mut self, amount: u64) -> Result<()> {
+        self.claimable_amount = self.claimable_amount
+            .checked_add(amount)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    pub fn credit_lamports(&mut self, amount: u64) -> Result<()> {
+        self.claimable_lamports = self.claimable_lamports
+            .checked_add(amount)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// EFFECTS: Book a claim of everything claimable, returning the
+    /// `(platform mint, lamports)` amounts to pay out
+    pub fn take_claimable(&mut self, now: i64) -> Result<(u64, u64)> {
+        let amount = self.claimable_amount;
+        let lamports = self.claimable_lamports;
+        require!(amount > 0 || lamports > 0, GameError::NoRewardsToClaim);
+
+        self.total_claimed = self.total_claimed
+            .checked_add(amount)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        self.total_claimed_lamports = self.total_claimed_lamports
+            .checked_add(lamports)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        self.claimable_amount = 0;
+        self.claimable_lamports = 0;
+        self.last_claim = now;
+        Ok((amount, lamports))
+    }
+}
+
+/// Asset a game's treasury rewards are credited in
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RewardAsset {
+    PlatformMint,
+    Sol,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LoanStatus {
+    Open,        // Game still running; settled by finalize or cancel
+    Outstanding, // Debt not covered by the game, repaid through settle_loan
+    Repaid,      // Debt repaid from the pot, collateral left to withdraw
+}
+
+/// A Kamino loan that paid one player's entry fee.
+///
+/// Every seat that borrowed has its own position. Finalize and cancel repay
+/// what they can from the pot and mark it Repaid, or Outstanding if the
+/// borrower lost; either way only the borrower closes it, with `settle_loan`.
+/// Until they do, an Outstanding loan keeps accruing interest on Kamino, and
+/// once the obligation is unhealthy Kamino's liquidators may seize the
+/// collateral like any other.
+#[account]
+pub struct LoanPosition {
+    pub game: Pubkey,
+    pub game_id: u64,
+    pub borrower: Pubkey,
+    pub lending_market: Pubkey,
+    pub reserve: Pubkey,
+    pub collateral_reserve: Pubkey,
+    pub obligation: Pubkey,
+    pub collateral_amount: u64,    // Collateral deposited, in the collateral mint
+    pub collateral_ctokens: u64,   // cTokens the deposit added to the obligation, withdrawn by settle_loan
+    pub loan_amount: u64,          // Debt this loan added to the obligation, less what the pot repaid
+    pub status: LoanStatus,
+    pub bump: u8,
+    pub borrow_rate_bsf: u128,     // Reserve's cumulative borrow rate loan_amount was accrued to
+}
+
+impl LoanPosition {
+    pub const SEED: &'static [u8] = b"loan";
+
+    pub const LEN: usize = 8 + 32 + 8 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + 16;
+
+    pub fn find_address(game: &Pubkey, borrower: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, game.as_ref(), borrower.as_ref()], &crate::ID)
+    }
+
+    /// SECURITY: What this loan owes now, capped at its own principal and
+    /// interest so other debt on the obligation is never repaid for the
+    /// borrower. Returns the reserve's current cumulative borrow rate too
+    pub fn debt(&self, obligation: &AccountInfo, reserve: &AccountInfo) -> Result<(u64, u128)> {
+        crate::kamino::loan_debt(obligation, reserve, &self.lending_market, self.loan_amount, self.borrow_rate_bsf)
+    }
+
+    /// SECURITY: Kamino accounts passed in must be the ones the loan was opened with
+    pub fn require_kamino_accounts(&self, lending_market: &Pubkey, reserve: &Pubkey, obligation: &Pubkey) -> Result<()> {
+        require!(*lending_market == self.lending_market, GameError::InvalidKaminoMarket);
+        require!(*reserve == self.reserve, GameError::InvalidKaminoReserve);
+        require!(*obligation == self.obligation, GameError::InvalidKaminoObligation);
+        Ok(())
+    }
+}
+
+/// Trusted price feeds for one asset, registered by the platform authority.
+///
+/// Loans are valued with the feeds named here, so a caller can only ever
+/// supply a fresh update of the right feed, never a feed of their choosing.
+#[account]
+pub struct AssetOracle {
+    pub mint: Pubkey,
+    pub pyth_feed_id: [u8; 32],    // Pyth price feed id, checked on every price update
+    pub switchboard_feed: Pubkey,  // Switchboard On-Demand pull feed, used when Pyth is unusable
+    pub bump: u8,
+}
+
+impl AssetOracle {
+    pub const SEED: &'static [u8] = b"oracle";
+
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 1;
+
+    pub fn find_address(mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, mint.as_ref()], &crate::ID)
+    }
 }
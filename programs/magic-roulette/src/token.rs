@@ -0,0 +1,418 @@
+//! Asset handling shared by every instruction that moves a pot.
+//!
+//! Mint validation and hook-aware transfers for Token-2022,
+//! `GameTreasury`, which deposits into and pays out of a game vault the same
+//! way whether it holds SOL or Token-2022, and `VaultWsol`, through which a
+//! SOL vault borrows from and repays Kamino.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::{program_pack::Pack, system_instruction};
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token::spl_token::{self, native_mint};
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    default_account_state::DefaultAccountState,
+    non_transferable::NonTransferable,
+    permanent_delegate::PermanentDelegate,
+    transfer_fee::{instruction::harvest_withheld_tokens_to_mint, TransferFeeAmount},
+    BaseStateWithExtensions, StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::instruction::close_account;
+use anchor_spl::token_2022::spl_token_2022::onchain::invoke_transfer_checked;
+use anchor_spl::token_2022::spl_token_2022::state::{Account as TokenAccountState, AccountState, Mint as MintState};
+use anchor_spl::token_2022::spl_token_2022::ID as TOKEN_2022_PROGRAM_ID;
+use crate::{errors::GameError, settlement::PayoutPlan};
+
+/// Validate a Token-2022 game mint and return its decimals.
+///
+/// Extensions are fine as long as the pot can always be paid out, so mints
+/// that are non-transferable, carry a permanent delegate (who could drain the
+/// vault) or freeze new accounts by default are rejected.
+pub fn mint_decimals(mint_account: &AccountInfo) -> Result<u8> {
+    // SECURITY: Validate mint is owned by Token-2022 program
+    require!(
+        mint_account.owner == &TOKEN_2022_PROGRAM_ID,
+        GameError::InvalidMint
+    );
+
+    let mint_data = mint_account.try_borrow_data()?;
+    validate_mint_data(&mint_data)
+}
+
+/// Extension checks behind `mint_decimals`, on raw mint account data
+pub fn validate_mint_data(mint_data: &[u8]) -> Result<u8> {
+    let mint = StateWithExtensions::<MintState>::unpack(mint_data)
+        .map_err(|_| GameError::InvalidMint)?;
+
+    require!(
+        mint.get_extension::<NonTransferable>().is_err(),
+        GameError::NonTransferableMint
+    );
+
+    if let Ok(permanent_delegate) = mint.get_extension::<PermanentDelegate>() {
+        require!(
+            Option::<Pubkey>::from(permanent_delegate.delegate).is_none(),
+            GameError::PermanentDelegateMint
+        );
+    }
+
+    if let Ok(default_state) = mint.get_extension::<DefaultAccountState>() {
+        require!(
+            default_state.state != AccountState::Frozen as u8,
+            GameError::FrozenByDefaultMint
+        );
+    }
+
+    Ok(mint.base.decimals)
+}
+
+/// Token-2022 instructions that close an emptied `vault` into `destination`.
+///
+/// Transfer-fee mints withhold each deposit's fee in the vault, and
+/// Token-2022 refuses to close an account until those fees are harvested
+/// to the mint, so any withheld fees are harvested first.
+pub fn close_vault_instructions(
+    token_program: &Pubkey,
+    mint: &Pubkey,
+    vault: &AccountInfo,
+    destination: &Pubkey,
+    authority: &Pubkey,
+) -> Result<Vec<Instruction>> {
+    let data = vault.try_borrow_data()?;
+    let account = StateWithExtensions::<TokenAccountState>::unpack(&data)
+        .map_err(|_| GameError::InvalidVaultOwner)?;
+    let withheld = account
+        .get_extension::<TransferFeeAmount>()
+        .map(|fees| u64::from(fees.withheld_amount))
+        .unwrap_or(0);
+
+    let mut instructions = Vec::with_capacity(2);
+    if withheld > 0 {
+        instructions.push(harvest_withheld_tokens_to_mint(token_program, mint, &[vault.key])?);
+    }
+    instructions.push(close_account(token_program, vault.key, destination, authority, &[])?);
+    Ok(instructions)
+}
+
+/// `transfer_checked` that forwards TransferHook accounts.
+///
+/// `extra_accounts` may be a superset: the hook program, its validation
+/// account and any extra metas it lists are picked out by key, and mints
+/// without a hook ignore it entirely.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_checked<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    extra_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    invoke_transfer_checked(
+        token_program.key,
+        from.clone(),
+        mint.clone(),
+        to.clone(),
+        authority.clone(),
+        extra_accounts,
+        amount,
+        decimals,
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+enum TreasuryAsset<'a, 'info> {
+    Sol {
+        system_program: AccountInfo<'info>,
+    },
+    Token {
+        mint: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        decimals: u8,
+        hook_accounts: &'a [AccountInfo<'info>],
+    },
+}
+
+/// A game's vault, holding either SOL or a Token-2022 mint.
+///
+/// Payouts are signed with `signer_seeds`: the vault PDA signs for itself
+/// when it holds SOL, while a token vault is signed for by the game PDA.
+pub struct GameTreasury<'a, 'info> {
+    vault: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &'a [&'a [&'a [u8]]],
+    asset: TreasuryAsset<'a, 'info>,
+}
+
+impl<'a, 'info> GameTreasury<'a, 'info> {
+    /// SOL vault PDA (`[Game::VAULT_SEED, game]`), which signs its own payouts
+    pub fn sol(
+        vault: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+        signer_seeds: &'a [&'a [&'a [u8]]],
+    ) -> Self {
+        Self {
+            authority: vault.clone(),
+            vault,
+            signer_seeds,
+            asset: TreasuryAsset::Sol { system_program },
+        }
+    }
+
+    /// Token-2022 vault owned by `authority`, the game PDA.
+    /// Validates the mint's extensions up front.
+    pub fn token(
+        vault: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        mint: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        hook_accounts: &'a [AccountInfo<'info>],
+        signer_seeds: &'a [&'a [&'a [u8]]],
+    ) -> Result<Self> {
+        let decimals = mint_decimals(&mint)?;
+        Ok(Self {
+            vault,
+            authority,
+            signer_seeds,
+            asset: TreasuryAsset::Token { mint, token_program, decimals, hook_accounts },
+        })
+    }
+
+    /// Lamports or token units currently held by the vault
+    pub fn balance(&self) -> Result<u64> {
+        self.balance_of(&self.vault)
+    }
+
+    /// Lamports or token units of the vault's asset held by `account`
+    fn balance_of(&self, account: &AccountInfo) -> Result<u64> {
+        match &self.asset {
+            TreasuryAsset::Sol { .. } => Ok(account.lamports()),
+            TreasuryAsset::Token { .. } => {
+                let data = account.try_borrow_data()?;
+                let account = StateWithExtensions::<TokenAccountState>::unpack(&data)
+                    .map_err(|_| GameError::InvalidVaultOwner)?;
+                Ok(account.base.amount)
+            }
+        }
+    }
+
+    /// Move `amount` from `payer` into the vault and return what arrived.
+    ///
+    /// `source` is the payer's token account, or the payer itself for SOL.
+    /// Transfer-fee mints deliver less than `amount`; the pot should only
+    /// ever be credited with the returned value.
+    pub fn deposit(&self, payer: &AccountInfo<'info>, source: &AccountInfo<'info>, amount: u64) -> Result<u64> {
+        let before = self.balance()?;
+
+        match &self.asset {
+            TreasuryAsset::Sol { system_program } => transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    Transfer {
+                        from: payer.clone(),
+                        to: self.vault.clone(),
+                    },
+                ),
+                amount,
+            )?,
+            TreasuryAsset::Token { mint, token_program, decimals, hook_accounts } => transfer_checked(
+                token_program,
+                source,
+                mint,
+                &self.vault,
+                payer,
+                hook_accounts,
+                amount,
+                *decimals,
+                &[],
+            )?,
+        }
+
+        self.balance()?
+            .checked_sub(before)
+            .ok_or(GameError::ArithmeticOverflow.into())
+    }
+
+    /// Pay `amount` out of the vault to `recipient`; zero amounts are skipped
+    pub fn payout(&self, recipient: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        match &self.asset {
+            TreasuryAsset::Sol { system_program } => transfer(
+                CpiContext::new_with_signer(
+                    system_program.clone(),
+                    Transfer {
+                        from: self.vault.clone(),
+                        to: recipient.clone(),
+                    },
+                    self.signer_seeds,
+                ),
+                amount,
+            ),
+            TreasuryAsset::Token { mint, token_program, decimals, hook_accounts } => transfer_checked(
+                token_program,
+                &self.vault,
+                mint,
+                recipient,
+                &self.authority,
+                hook_accounts,
+                amount,
+                *decimals,
+                self.signer_seeds,
+            ),
+        }
+    }
+
+    /// Close an emptied vault, returning its rent to `destination`.
+    /// A SOL vault is closed by sweeping every lamport it still holds, a
+    /// token vault as `close_vault_instructions` lays out, so the mint must
+    /// be writable.
+    pub fn close(&self, destination: &AccountInfo<'info>) -> Result<()> {
+        match &self.asset {
+            TreasuryAsset::Sol { .. } => self.payout(destination, self.vault.lamports()),
+            TreasuryAsset::Token { mint, token_program, .. } => {
+                let instructions = close_vault_instructions(
+                    token_program.key,
+                    mint.key,
+                    &self.vault,
+                    destination.key,
+                    self.authority.key,
+                )?;
+                let accounts = [mint.clone(), self.vault.clone(), destination.clone(), self.authority.clone()];
+                for instruction in instructions.iter() {
+                    invoke_signed(instruction, &accounts, self.signer_seeds)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// SECURITY: Check the vault still holds `total_pot` before paying out.
+    /// A SOL vault only ever receives the entry fees and needs no rent
+    /// reserve: the instruction that empties it also closes it.
+    pub fn require_funded(&self, total_pot: u64) -> Result<()> {
+        require!(
+            self.balance()? >= total_pot,
+            GameError::InsufficientVaultBalance
+        );
+        Ok(())
+    }
+
+    /// Execute a settlement plan: player rewards, the platform and treasury
+    /// fees, then each winner's share less their loan repayment.
+    ///
+    /// Loan repayment is not paid from here; the lender pulls it from the
+    /// vault before the plan is executed.
+    ///
+    /// Returns the player rewards that reached `rewards_vault`. Transfer-fee
+    /// mints deliver less than the plan sends, and players may only be
+    /// credited with what arrived.
+    pub fn execute(
+        &self,
+        plan: &PayoutPlan,
+        platform: &AccountInfo<'info>,
+        treasury: &AccountInfo<'info>,
+        rewards_vault: Option<&AccountInfo<'info>>,
+        winners: &[AccountInfo<'info>],
+    ) -> Result<u64> {
+        require!(
+            winners.len() == plan.winner_count as usize,
+            GameError::InvalidWinner
+        );
+
+        let mut rewards_received = 0;
+        if plan.player_rewards > 0 {
+            let rewards_vault = rewards_vault.ok_or(GameError::InvalidVaultOwner)?;
+            let before = self.balance_of(rewards_vault)?;
+            self.payout(rewards_vault, plan.player_rewards)?;
+            rewards_received = self.balance_of(rewards_vault)?
+                .checked_sub(before)
+                .ok_or(GameError::ArithmeticOverflow)?;
+        }
+        self.payout(platform, plan.platform_fee)?;
+        self.payout(treasury, plan.treasury_payout())?;
+        for (index, winner) in winners.iter().enumerate() {
+            self.payout(winner, plan.winner_payout(index))?;
+        }
+        Ok(rewards_received)
+    }
+}
+
+/// Wrapped SOL account of a SOL game vault, its associated token account
+/// for the native mint.
+///
+/// Kamino only lends and takes repayment in SPL tokens, so a borrowed entry
+/// fee lands here and a repayment is paid from here. The account never
+/// outlives the instruction that opens it: closing it unwraps its balance
+/// and rent into the vault.
+pub struct VaultWsol {
+    pub vault: Pubkey,
+    pub account: Pubkey,
+}
+
+impl VaultWsol {
+    pub fn new(vault: &Pubkey) -> Self {
+        Self {
+            vault: *vault,
+            account: get_associated_token_address(vault, &native_mint::ID),
+        }
+    }
+
+    /// Create the account, `payer` covering its rent
+    pub fn open(&self, payer: &Pubkey) -> Instruction {
+        create_associated_token_account_idempotent(payer, &self.vault, &native_mint::ID, &spl_token::ID)
+    }
+
+    /// Move `lamports` of the vault's SOL into the account and sync its balance
+    pub fn wrap(&self, lamports: u64) -> Result<[Instruction; 2]> {
+        Ok([
+            system_instruction::transfer(&self.vault, &self.account, lamports),
+            spl_token::instruction::sync_native(&spl_token::ID, &self.account)?,
+        ])
+    }
+
+    /// Close the account into the vault, signed for by the vault
+    pub fn close(&self) -> Result<Instruction> {
+        Ok(spl_token::instruction::close_account(
+            &spl_token::ID,
+            &self.account,
+            &self.vault,
+            &self.vault,
+            &[],
+        )?)
+    }
+
+    /// Pay `lamports` of the vault's SOL through `payment`, which must spend
+    /// them out of the account with the vault as authority.
+    ///
+    /// The vault fronts the account's rent out of the pot and gets it back
+    /// when the account is closed in the same plan.
+    pub fn pay_with(&self, lamports: u64, payment: Instruction) -> Result<Vec<Instruction>> {
+        let mut ixs = vec![self.open(&self.vault)];
+        ixs.extend(self.wrap(lamports)?);
+        ixs.push(payment);
+        ixs.push(self.close()?);
+        Ok(ixs)
+    }
+
+    /// Wrapped SOL held by an open account, and the rent it will give back
+    pub fn balance(account: &AccountInfo) -> Result<(u64, u64)> {
+        require!(
+            account.owner == &spl_token::ID,
+            GameError::InvalidVaultOwner
+        );
+        let state = spl_token::state::Account::unpack(&account.try_borrow_data()?)
+            .map_err(|_| GameError::InvalidVaultOwner)?;
+        let rent = Option::<u64>::from(state.is_native).ok_or(GameError::InvalidMint)?;
+        Ok((state.amount, rent))
+    }
+}
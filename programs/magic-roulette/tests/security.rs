@@ -0,0 +1,2 @@
+#[path = "../../../tests/security_tests.rs"]
+mod suite;
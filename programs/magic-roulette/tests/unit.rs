@@ -0,0 +1,2 @@
+#[path = "../../../tests/unit_tests.rs"]
+mod suite;
@@ -171,30 +171,21 @@ async function main() {
     }
   }
   
-  // Generate mock randomness (in production, this comes from MagicBlock VRF)
-  const mockRandomness = Array(32).fill(0).map(() => Math.floor(Math.random() * 256));
+  console.log("\n   Waiting for the VRF callback...");
   
-  console.log("\n   Processing VRF result...");
-  
-  // Try to process VRF - if it fails due to status, we'll handle it
+  // Only the MagicBlock VRF program can deliver randomness, through
+  // request_vrf_randomness_callback - wait for it to start the game
   let vrfProcessed = false;
   try {
-    const vrfTx = await program.methods
-      .processVrfResult(mockRandomness)
-      .accounts({
-        game: gamePda,
-        vrfAuthority: walletKeypair.publicKey,
-      })
-      .rpc();
-
-    console.log("✅ VRF processed!");
-    console.log("   TX:", vrfTx);
-    vrfProcessed = true;
-
     await new Promise(resolve => setTimeout(resolve, 2000));
 
     game = await program.account.game.fetch(gamePda);
-    console.log("   Bullet Chamber:", game.bulletChamber);
+    if (Object.keys(game.status)[0] !== "inProgress") {
+      throw new Error(`game is ${Object.keys(game.status)[0]}, VRF callback not received`);
+    }
+
+    console.log("✅ VRF processed!");
+    vrfProcessed = true;
     console.log("   Status:", Object.keys(game.status)[0]);
   } catch (error) {
     console.log("❌ VRF processing failed:", error.message);
@@ -65,7 +65,7 @@ async function main() {
 
   // Get platform config
   const [platformConfig] = PublicKey.findProgramAddressSync(
-    [Buffer.from("platform_config")],
+    [Buffer.from("platform")],
     program.programId
   );
 
@@ -274,6 +274,25 @@ export function calculateRequiredCollateral(entryFee: BN): BN {
   return entryFee.mul(new BN(110)).div(new BN(100));
 }
 
+/**
+ * Calculate how much to borrow so that an entry fee is left after Kamino's
+ * origination fee, which klend takes out of the loan itself.
+ *
+ * The program keeps exactly the entry fee in the game vault and sends any
+ * surplus back to the player; it fails if less than the entry fee arrives.
+ *
+ * @param entryFee - Entry fee in lamports
+ * @param borrowFeeBps - The SOL reserve's borrow fee, in basis points
+ */
+export function calculateBorrowAmount(entryFee: BN, borrowFeeBps: number = 0): BN {
+  if (borrowFeeBps === 0) {
+    return entryFee;
+  }
+  const net = new BN(10_000 - borrowFeeBps);
+  // Round up, plus a lamport for klend rounding its fee up too
+  return entryFee.muln(10_000).add(net.subn(1)).div(net).addn(1);
+}
+
 /**
  * Calculate maximum borrow amount for given collateral (90.9% of collateral)
  */
@@ -333,7 +352,7 @@ export async function checkObligationExists(
  * ```typescript
  * import { Connection, PublicKey, Keypair } from '@solana/web3.js';
  * import { Program, AnchorProvider, BN } from '@coral-xyz/anchor';
- * import { getKaminoAccountsForGame, solToLamports } from './kamino-helpers';
+ * import { getKaminoAccountsForGame, solToLamports, calculateBorrowAmount } from './kamino-helpers';
  * 
  * const connection = new Connection("https://api.devnet.solana.com");
  * const wallet = Keypair.generate(); // Your wallet
@@ -342,6 +361,7 @@ export async function checkObligationExists(
  * // Game parameters
  * const entryFee = solToLamports(0.1); // 0.1 SOL
  * const collateral = solToLamports(0.11); // 0.11 SOL (110%)
+ * const borrowFeeBps = 10; // SOL reserve's borrow fee
  * const vrfSeed = Array.from(crypto.getRandomValues(new Uint8Array(32)));
  * 
  * // Derive PDAs
@@ -369,6 +389,7 @@ export async function checkObligationExists(
  *     { oneVsOne: {} },
  *     entryFee,
  *     collateral,
+ *     calculateBorrowAmount(entryFee, borrowFeeBps),
  *     vrfSeed
  *   )
  *   .accounts(accounts)
@@ -21,6 +21,7 @@ import { MagicRoulette } from "../target/types/magic_roulette";
 import {
   getKaminoAccountsForGame,
   calculateRequiredCollateral,
+  calculateBorrowAmount,
   validateCollateral,
   lamportsToSol,
   solToLamports,
@@ -159,6 +160,7 @@ describe("Kamino Integration Tests", () => {
           { oneVsOne: {} },
           entryFee,
           collateral,
+          calculateBorrowAmount(entryFee),
           vrfSeed
         )
         .accounts(accounts)
@@ -225,6 +227,7 @@ describe("Kamino Integration Tests", () => {
             { oneVsOne: {} },
             entryFee,
             insufficientCollateral,
+            calculateBorrowAmount(entryFee),
             vrfSeed
           )
           .accounts(accounts)
@@ -262,6 +265,7 @@ describe("Kamino Integration Tests", () => {
             { oneVsOne: {} },
             tooLowEntryFee,
             collateral,
+            calculateBorrowAmount(tooLowEntryFee),
             vrfSeed
           )
           .accounts(accounts)
@@ -273,6 +277,44 @@ describe("Kamino Integration Tests", () => {
         assert.include(error.toString(), "InsufficientEntryFee");
       }
     });
+    
+    it("rejects a borrow that leaves less than the entry fee", async () => {
+      const entryFee = solToLamports(0.01);
+      const collateral = calculateRequiredCollateral(entryFee);
+      const vrfSeed = Array.from(crypto.getRandomValues(new Uint8Array(32)));
+      
+      const gameId = new BN(0);
+      [gamePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("game"), gameId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      
+      const accounts = await getKaminoAccountsForGame(
+        connection,
+        player.publicKey,
+        gamePda,
+        platformConfig,
+        true
+      );
+      
+      try {
+        await program.methods
+          .createGameWithLoan(
+            { oneVsOne: {} },
+            entryFee,
+            collateral,
+            entryFee.subn(1),
+            vrfSeed
+          )
+          .accounts(accounts)
+          .signers([player])
+          .rpc();
+        
+        assert.fail("Should have thrown BorrowBelowEntryFee error");
+      } catch (error: any) {
+        assert.include(error.toString(), "BorrowBelowEntryFee");
+      }
+    });
   });
 
   describe("Kamino Market Data (Optional)", () => {
@@ -2,16 +2,35 @@
 // Tests all critical vulnerabilities identified in security audit
 
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::pubkey;
-use magic_roulette::*;
+use ::magic_roulette::*;
 
 // Test constants
 const KAMINO_PROGRAM_ID: Pubkey = pubkey!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD");
-const VRF_PROGRAM_ID: Pubkey = pubkey!("EPHvrfnQ5RPLRaakdqLZwxbDyLcrMnhL7QNTNwE5pto");
+const VRF_PROGRAM_ID: Pubkey = pubkey!("Vrf1RNUjXmQGjmQrQLvJHs9SNkvDJEsRVFPkfSQUwGz");
 
 #[cfg(test)]
 mod security_tests {
     use super::*;
+    use super::integration_helpers::*;
+    use anchor_lang::error::ErrorCode;
+    use anchor_lang::solana_program::system_program;
+    use anchor_lang::Bumps;
+    use anchor_spl::token::spl_token::native_mint;
+    use errors::GameError;
+    use instructions::admin::UpdatePlatformConfig;
+    use instructions::cancel_game_sol::CancelGameSol;
+    use instructions::claim_timeout_forfeit::ClaimTimeoutForfeit;
+    use instructions::close_game::CloseGame;
+    use instructions::finalize_game_sol::FinalizeGameSol;
+    use instructions::finalize_game_with_loan::{BorrowerLoan, FinalizeGameWithLoan};
+    use instructions::join_game_sol::JoinGameSol;
+    use instructions::settle_loan::SettleLoan;
+    use instructions::vrf_instructions::VrfCallback;
+    use instructions::withdraw_treasury::WithdrawTreasury;
+    use oracle::OraclePrice;
+    use settlement::PayoutPlan;
+    use state::{lifecycle, AssetOracle, Game, GameMode, GameStatus, LoanPosition, LoanStatus, PlatformConfig};
+    use token::GameTreasury;
 
     // ========================================================================
     // CRITICAL VULNERABILITY TESTS
@@ -23,24 +42,24 @@ mod security_tests {
         // Test: Arbitrary CPI - Kamino Program Validation
         // Ensures only legitimate Kamino program can be used for CPI
         
-        // Setup: Create fake Kamino program ID
+        // The program CPIs into the real KLend deployment
+        assert_eq!(constants::KAMINO_PROGRAM_ID, KAMINO_PROGRAM_ID);
+        
+        // Setup: Create fake Kamino program ID and a loan waiting to be settled
         let fake_kamino = Pubkey::new_unique();
+        assert_ne!(fake_kamino, KAMINO_PROGRAM_ID, "Test setup failed");
         
-        // Attempt: Try to create game with loan using fake program
+        let borrower = Pubkey::new_unique();
+        let (loan_address, loan) = create_test_loan(&borrower, LoanStatus::Outstanding);
+        
+        // Attempt: Try to settle the loan through the fake program
         // Expected: Should panic with InvalidKaminoProgram error
         
         // This test validates that the constraint check:
         // constraint = kamino_program.key() == KAMINO_PROGRAM_ID
         // properly rejects unauthorized programs
-        
-        assert_ne!(fake_kamino, KAMINO_PROGRAM_ID, "Test setup failed");
-        
-        // TODO: Implement full test with program context
-        // For now, verify the constant is correct
-        assert_eq!(
-            KAMINO_PROGRAM_ID.to_string(),
-            "KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD"
-        );
+        let accounts = settle_loan_accounts(loan_address, &loan, borrower, fake_kamino);
+        try_accounts::<SettleLoan>(accounts, &[]).unwrap();
     }
 
     #[test]
@@ -49,24 +68,29 @@ mod security_tests {
         // Test: Missing Signer Check - VRF Authority
         // Ensures only authorized VRF program can provide randomness
         
-        // Setup: Create fake VRF authority
+        // The program trusts the MagicBlock VRF deployment
+        assert_eq!(constants::MAGICBLOCK_VRF_PROGRAM_ID, VRF_PROGRAM_ID);
+        
+        // Setup: Create fake VRF authority and a game awaiting randomness
         let fake_vrf = Pubkey::new_unique();
+        assert_ne!(fake_vrf, constants::MAGICBLOCK_VRF_PROGRAM_IDENTITY, "Test setup failed");
+        
+        let mut game = create_test_game(GameMode::OneVsOne, Pubkey::new_unique());
+        game.status = GameStatus::AwaitingRandomness;
         
-        // Attempt: Try to process VRF result with unauthorized signer
+        // Attempt: Try to deliver randomness with unauthorized signer
         // Expected: Should panic with InvalidVrfAuthority error
         
         // This test validates that the constraint check:
-        // constraint = vrf_authority.key() == crate::ID
-        // properly rejects unauthorized VRF providers
-        
-        assert_ne!(fake_vrf, VRF_PROGRAM_ID, "Test setup failed");
-        
-        // TODO: Implement full test with program context
-        // For now, verify the VRF program ID is correct
-        assert_eq!(
-            VRF_PROGRAM_ID.to_string(),
-            "EPHvrfnQ5RPLRaakdqLZwxbDyLcrMnhL7QNTNwE5pto"
-        );
+        // address = MAGICBLOCK_VRF_PROGRAM_IDENTITY
+        // properly rejects unauthorized VRF providers on the one callback
+        // path that starts games and resolves shots
+        let mut accounts = vec![
+            signer_account(fake_vrf),
+            program_state(Game::find_address(game.game_id).0, &game, Game::LEN),
+        ];
+        accounts.extend(event_cpi_accounts());
+        try_accounts::<VrfCallback>(accounts, &simulate_vrf_randomness()).unwrap();
     }
 
     #[test]
@@ -75,19 +99,26 @@ mod security_tests {
         // Test: Missing Winner Validation
         // Ensures only actual game participants can receive winnings
         
-        // Setup: Create game with known participants
-        // Team A: [player1, player2]
-        // Team B: [player3, player4]
+        // Setup: Create game with known participants, seated in turn order
+        // Team A: [player1, player3]
+        // Team B: [player2, player4]
         // Winner: Team A
+        let players = create_test_players(4);
+        let mut game = create_test_game(GameMode::TwoVsTwo, players[0]);
+        for player in &players[1..] {
+            game.add_joiner(*player).unwrap();
+        }
+        game.winner_team = Some(0);
+        let winners = game.winners().unwrap();
+        assert_eq!(winners, vec![players[0], players[2]], "Test setup failed");
         
         // Attempt: Try to finalize with attacker address as winner
         // Expected: Should panic with InvalidWinner error
         
         // This test validates that finalize functions check:
-        // require!(ctx.accounts.winner1.key() == expected_winner1, InvalidWinner)
-        
-        // TODO: Implement full test with program context
-        println!("Test: Winner validation prevents fund theft");
+        // require!(winner == account, InvalidWinner)
+        let attacker = Pubkey::new_unique();
+        settlement::require_winner_accounts(&winners, &[players[0], attacker]).unwrap();
     }
 
     // ========================================================================
@@ -102,34 +133,48 @@ mod security_tests {
         
         // Setup: Create game with total_pot = 1000
         // Drain vault to 500
+        let mut game = create_test_game(GameMode::OneVsOne, Pubkey::new_unique());
+        game.total_pot = 1000;
+        
+        let (vault, _) = Game::find_vault_address(&Game::find_address(game.game_id).0);
+        let treasury = GameTreasury::sol(
+            test_account(vault, system_program::ID, 500, vec![], false),
+            program_account(system_program::ID),
+            &[],
+        );
         
         // Attempt: Try to finalize game
         // Expected: Should panic with InsufficientVaultBalance error
         
         // This test validates the check:
         // require!(vault_balance >= total_pot, InsufficientVaultBalance)
-        
-        // TODO: Implement full test with program context
-        println!("Test: Vault balance validation prevents failed transfers");
+        treasury.require_funded(game.total_pot).unwrap();
     }
 
     #[test]
     #[should_panic(expected = "Unauthorized")]
     fn test_fake_treasury_vault() {
-        // Test: Improper Authority Check in claim_rewards
+        // Test: Improper Authority Check in finalize
         // Ensures only legitimate treasury vault can be used
         
         // Setup: Create fake treasury vault controlled by attacker
         let fake_treasury = Pubkey::new_unique();
+        let config = create_test_platform_config();
+        assert_ne!(fake_treasury, config.treasury, "Test setup failed");
         
-        // Attempt: Try to claim rewards with fake vault
+        let players = create_test_players(2);
+        let mut game = create_test_game(GameMode::OneVsOne, players[0]);
+        game.add_joiner(players[1]).unwrap();
+        game.status = GameStatus::Finished;
+        game.winner_team = Some(0);
+        
+        // Attempt: Try to finalize with fake vault as the treasury
         // Expected: Should panic with Unauthorized error
         
         // This test validates the constraint:
-        // constraint = treasury_vault.key() == platform_config.treasury
-        
-        // TODO: Implement full test with program context
-        println!("Test: Treasury vault validation prevents fake vault attacks");
+        // constraint = treasury.key() == platform_config.treasury
+        let accounts = finalize_game_sol_accounts(&game, &config, game.creator, fake_treasury);
+        try_accounts::<FinalizeGameSol>(accounts, &[]).unwrap();
     }
 
     #[test]
@@ -139,15 +184,16 @@ mod security_tests {
         // Ensures multiple players can't join simultaneously beyond limit
         
         // Setup: Create 1v1 game with 1 player already joined
+        let players = create_test_players(3);
+        let mut game = create_test_game(GameMode::OneVsOne, players[0]);
+        game.add_joiner(players[1]).unwrap();
         
         // Attempt: Simulate 2 players trying to join at same time
         // Expected: Second player should fail with GameFull error
         
         // This test validates the check:
         // require!(!game.is_full(), GameFull)
-        
-        // TODO: Implement full test with concurrent transactions
-        println!("Test: Race condition prevention in join_game");
+        game.add_joiner(players[2]).unwrap();
     }
 
     // ========================================================================
@@ -161,14 +207,14 @@ mod security_tests {
         // Ensures checked arithmetic prevents overflow attacks
         
         // Setup: Create game with total_pot near u64::MAX
-        let near_max = u64::MAX - 100;
+        let mut game = create_test_game(GameMode::OneVsOne, Pubkey::new_unique());
+        game.total_pot = u64::MAX - 100;
         
         // Attempt: Try to add entry fee that would overflow
         let entry_fee = 200u64;
         
         // Expected: Should panic with ArithmeticOverflow
-        let result = near_max.checked_add(entry_fee);
-        assert!(result.is_none(), "Overflow should be detected");
+        game.add_to_pot(entry_fee).unwrap();
     }
 
     #[test]
@@ -186,6 +232,7 @@ mod security_tests {
             .expect("Division by zero") as u64;
         
         assert_eq!(fee, 50_000_000); // 0.05 SOL
+        assert_eq!(calculate_fees(total_pot, platform_fee_bps, 1000), (fee, 100_000_000));
     }
 
     // ========================================================================
@@ -198,16 +245,19 @@ mod security_tests {
         // Test: Player duplicate check
         // Ensures same player can't join game twice
         
-        // Setup: Create game with player1 in team_a
+        // Setup: Create 2v2 game with player1 in team_a
+        let players = create_test_players(3);
+        let mut game = create_test_game(GameMode::TwoVsTwo, players[0]);
+        game.add_joiner(players[1]).unwrap();
+        game.add_joiner(players[2]).unwrap();
+        assert_eq!(game.team_a[1], players[2], "Test setup failed");
         
         // Attempt: Try to join again with same player
         // Expected: Should panic with PlayerAlreadyInGame
         
         // This test validates:
-        // require!(!game.team_a.contains(&player) && !game.team_b.contains(&player))
-        
-        // TODO: Implement full test with program context
-        println!("Test: Duplicate player prevention");
+        // require!(game.seat_of(&player).is_none(), PlayerAlreadyInGame)
+        game.add_joiner(players[2]).unwrap();
     }
 
     #[test]
@@ -217,15 +267,15 @@ mod security_tests {
         // Ensures game creator can't join their own game
         
         // Setup: Create game with creator
+        let creator = Pubkey::new_unique();
+        let mut game = create_test_game(GameMode::OneVsOne, creator);
         
         // Attempt: Try to join with creator wallet
         // Expected: Should panic with CannotJoinOwnGame
         
         // This test validates:
         // require!(player != game.creator, CannotJoinOwnGame)
-        
-        // TODO: Implement full test with program context
-        println!("Test: Creator self-join prevention");
+        game.add_joiner(creator).unwrap();
     }
 
     #[test]
@@ -236,7 +286,7 @@ mod security_tests {
         
         // Execute: Finish game with winner
         
-        // Verify: No funds transferred, status = Cancelled
+        // Verify: No funds transferred, status = Settled
         
         // This test validates:
         // if game.is_practice_mode { return Ok(()); }
@@ -264,7 +314,10 @@ mod security_tests {
         
         // Verify PDA is valid
         assert!(pda != Pubkey::default());
-        assert!(bump <= 255);
+        assert_eq!(
+            Pubkey::create_program_address(&[b"game_vault", game_pubkey.as_ref(), &[bump]], &program_id),
+            Ok(pda)
+        );
         
         println!("Test: Game vault PDA derivation is correct");
     }
@@ -283,11 +336,604 @@ mod security_tests {
         
         // Verify PDA is valid
         assert!(pda != Pubkey::default());
-        assert!(bump <= 255);
+        assert_eq!(
+            Pubkey::create_program_address(&[b"platform", &[bump]], &program_id),
+            Ok(pda)
+        );
         
         println!("Test: Platform config PDA derivation is correct");
     }
 
+    #[test]
+    fn test_pda_helpers_derive_from_shared_seeds() {
+        // Test: Address helpers and Accounts constraints share one set of seeds
+        use ::magic_roulette::state::{Game, PlatformConfig, PlayerStats, TreasuryRewards};
+
+        let (platform, _) = PlatformConfig::find_address();
+        let (expected, _) = Pubkey::find_program_address(&[b"platform"], &::magic_roulette::ID);
+        assert_eq!(platform, expected);
+
+        let (game, _) = Game::find_address(7);
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"game", 7u64.to_le_bytes().as_ref()],
+            &::magic_roulette::ID,
+        );
+        assert_eq!(game, expected);
+
+        let (vault, _) = Game::find_vault_address(&game);
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"game_vault", game.as_ref()],
+            &::magic_roulette::ID,
+        );
+        assert_eq!(vault, expected);
+
+        let player = Pubkey::new_unique();
+        let (rewards, _) = TreasuryRewards::find_address(&player);
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"rewards", player.as_ref()],
+            &::magic_roulette::ID,
+        );
+        assert_eq!(rewards, expected);
+
+        let (stats, _) = PlayerStats::find_address(&player);
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"stats", player.as_ref()],
+            &::magic_roulette::ID,
+        );
+        assert_eq!(stats, expected);
+
+        // Finalize only pays out a game stored at its own PDA
+        let players = create_test_players(2);
+        let game = create_finished_game(&players);
+        let config = create_test_platform_config();
+        // A copy of the game elsewhere, with a vault derived from the copy
+        let imposter = |mut accounts: Vec<AccountInfo<'static>>, vault: usize| {
+            let address = Pubkey::new_unique();
+            let (vault_address, _) = Game::find_vault_address(&address);
+            accounts[0] = program_state(address, &game, Game::LEN);
+            accounts[vault] = unchecked_account(vault_address);
+            accounts
+        };
+        let accounts = finalize_game_sol_accounts(&game, &config, players[0], config.treasury);
+        let err = try_accounts::<FinalizeGameSol>(imposter(accounts, 4), &[]).err().unwrap();
+        assert_eq!(err, ErrorCode::ConstraintSeeds.into());
+
+        let mut accounts = imposter(finalize_game_with_loan_accounts(&game, &config, players[0], config.treasury), 3);
+        accounts[4] = unchecked_account(token::VaultWsol::new(accounts[3].key).account);
+        let err = try_accounts::<FinalizeGameWithLoan>(accounts, &[]).err().unwrap();
+        assert_eq!(err, ErrorCode::ConstraintSeeds.into());
+    }
+
+    #[test]
+    fn test_platform_fees_go_to_the_configured_vault() {
+        // Test: Token finalize pins platform_vault to the authority, or to
+        // Squads vault 0 once the platform runs under a multisig
+        use ::magic_roulette::state::PlatformConfig;
+
+        let authority = Pubkey::new_unique();
+        let mut config = PlatformConfig {
+            authority,
+            pending_authority: None,
+            treasury: Pubkey::new_unique(),
+            platform_mint: Pubkey::new_unique(),
+            platform_fee_bps: 500,
+            treasury_fee_bps: 1000,
+            rewards_share_bps: 2000,
+            total_games: 0,
+            total_volume: 0,
+            treasury_balance: 0,
+            paused: false,
+            turn_timeout_seconds: 60,
+            min_collateral_ratio_bps: 11_000,
+            multisig_authority: None,
+            platform_vault: None,
+            treasury_vault: None,
+            bump: 255,
+        };
+        assert_eq!(config.platform_fee_recipient(), authority);
+
+        let squads_vault = Pubkey::new_unique();
+        config.multisig_authority = Some(Pubkey::new_unique());
+        config.platform_vault = Some(squads_vault);
+        assert_eq!(config.platform_fee_recipient(), squads_vault);
+    }
+
+    #[test]
+    fn test_platform_config_must_be_its_canonical_pda() {
+        // Test: Accounts structs only take the platform config at the PDA
+        // its shared seeds derive, under the canonical bump it stored
+        let player = Pubkey::new_unique();
+        let game = create_test_game(GameMode::OneVsOne, Pubkey::new_unique());
+        let config = create_test_platform_config();
+        let (config_address, _) = PlatformConfig::find_address();
+
+        let accounts = join_game_sol_accounts(&game, player, config_address, &config);
+        assert!(try_accounts::<JoinGameSol>(accounts, &[]).is_ok());
+
+        // A copy of the config at any other address is rejected
+        let accounts = join_game_sol_accounts(&game, player, Pubkey::new_unique(), &config);
+        let err = try_accounts::<JoinGameSol>(accounts, &[]).err().unwrap();
+        assert_eq!(err, ErrorCode::ConstraintSeeds.into());
+
+        // So is the config claiming a bump other than the canonical one
+        let mut forged = config.clone();
+        forged.bump = config.bump.wrapping_sub(1);
+        let accounts = join_game_sol_accounts(&game, player, config_address, &forged);
+        let err = try_accounts::<JoinGameSol>(accounts, &[]).err().unwrap();
+        assert_eq!(err, ErrorCode::ConstraintSeeds.into());
+    }
+
+    #[test]
+    fn test_treasury_withdrawals_need_the_squads_vault() {
+        // Test: withdraw_treasury only runs when the Squads treasury vault
+        // derived from the configured multisig signs it, and never pays out
+        // more than treasury_balance has booked
+        let multisig = Pubkey::new_unique();
+        let (vault, _) = squads::vault_address(&multisig, constants::SQUADS_TREASURY_VAULT_INDEX);
+        let mut config = create_test_platform_config();
+        config.treasury_balance = ENTRY_FEE;
+        let withdraw = |config: &PlatformConfig, treasury_vault: AccountInfo<'static>, amount: u64| {
+            run_instruction::<WithdrawTreasury>(withdraw_treasury_accounts(config, treasury_vault), |ctx| {
+                instructions::withdraw_treasury::withdraw_treasury(ctx, amount)
+            })
+        };
+
+        // A platform run by a single wallet has no treasury vault
+        let (_, result) = withdraw(&config, signer_account(vault), 1);
+        assert_eq!(result.unwrap_err(), GameError::MultisigUnauthorized.into());
+
+        // Any other signer is not the multisig's treasury vault
+        config.multisig_authority = Some(multisig);
+        let (_, result) = withdraw(&config, signer_account(Pubkey::new_unique()), 1);
+        assert_eq!(result.unwrap_err(), GameError::MultisigUnauthorized.into());
+
+        // The vault only signs once the members approved the withdrawal
+        let (_, result) = withdraw(&config, unchecked_account(vault), 1);
+        assert_eq!(result.unwrap_err(), GameError::MultisigProposalNotApproved.into());
+
+        // Even then it cannot take out more than the treasury has booked
+        let (accounts, result) = withdraw(&config, signer_account(vault), ENTRY_FEE + 1);
+        assert_eq!(result.unwrap_err(), GameError::InsufficientTreasuryBalance.into());
+        assert_eq!(accounts.platform_config.treasury_balance, ENTRY_FEE);
+
+        // Multisig platforms can only be set up with a multisig the Squads
+        // program created
+        let data = squads::MULTISIG_DISCRIMINATOR.to_vec();
+        let fake = test_account(multisig, Pubkey::new_unique(), 0, data.clone(), false);
+        assert_eq!(squads::require_multisig(&fake).unwrap_err(), GameError::InvalidMultisig.into());
+        let squads_multisig = test_account(multisig, constants::SQUADS_PROGRAM_ID, 0, data, false);
+        assert!(squads::require_multisig(&squads_multisig).is_ok());
+    }
+
+    #[test]
+    fn test_kamino_instructions_target_klend() {
+        // Test: Every Kamino instruction the program builds is addressed to
+        // the KLend deployment, whatever accounts it is given
+        let key = Pubkey::new_unique;
+        let instructions = [
+            kamino::DepositReserveLiquidityAndObligationCollateral {
+                owner: key(),
+                obligation: key(),
+                lending_market: key(),
+                lending_market_authority: key(),
+                reserve: key(),
+                reserve_liquidity_mint: key(),
+                reserve_liquidity_supply: key(),
+                reserve_collateral_mint: key(),
+                reserve_destination_deposit_collateral: key(),
+                user_source_liquidity: key(),
+                collateral_token_program: key(),
+                liquidity_token_program: key(),
+            }
+            .instruction(2 * ENTRY_FEE),
+            kamino::BorrowObligationLiquidity {
+                owner: key(),
+                obligation: key(),
+                lending_market: key(),
+                lending_market_authority: key(),
+                borrow_reserve: key(),
+                borrow_reserve_liquidity_mint: key(),
+                reserve_source_liquidity: key(),
+                borrow_reserve_liquidity_fee_receiver: key(),
+                user_destination_liquidity: key(),
+                token_program: key(),
+            }
+            .instruction(ENTRY_FEE),
+            kamino::RepayObligationLiquidity {
+                owner: key(),
+                obligation: key(),
+                lending_market: key(),
+                repay_reserve: key(),
+                reserve_liquidity_mint: key(),
+                reserve_destination_liquidity: key(),
+                user_source_liquidity: key(),
+                token_program: key(),
+            }
+            .instruction(ENTRY_FEE),
+            kamino::WithdrawObligationCollateral {
+                owner: key(),
+                obligation: key(),
+                lending_market: key(),
+                lending_market_authority: key(),
+                withdraw_reserve: key(),
+                reserve_source_collateral: key(),
+                user_destination_collateral: key(),
+                token_program: key(),
+            }
+            .instruction(2 * ENTRY_FEE),
+        ];
+        for instruction in instructions {
+            assert_eq!(instruction.program_id, KAMINO_PROGRAM_ID);
+        }
+    }
+
+    #[test]
+    fn test_instructions_require_the_event_authority() {
+        // Test: Instructions take the event authority and program that
+        // emit_cpi! needs, so lifecycle events cannot be dropped or spoofed
+        fn check<T>(accounts: Vec<AccountInfo<'static>>)
+        where
+            T: Accounts<'static, <T as Bumps>::Bumps> + Bumps,
+            <T as Bumps>::Bumps: Default,
+        {
+            let len = accounts.len();
+            assert!(try_accounts::<T>(accounts.clone(), &[]).is_ok());
+
+            // Without the event accounts the instruction is short of keys
+            let err = try_accounts::<T>(accounts[..len - 2].to_vec(), &[]).err().unwrap();
+            assert_eq!(err, ErrorCode::AccountNotEnoughKeys.into());
+
+            // And the event authority must be this program's
+            let mut forged = accounts;
+            forged[len - 2] = unchecked_account(Pubkey::new_unique());
+            let err = try_accounts::<T>(forged, &[]).err().unwrap();
+            assert_eq!(err, ErrorCode::ConstraintSeeds.into());
+        }
+
+        let players = create_test_players(2);
+        let config = create_test_platform_config();
+        let (config_address, _) = PlatformConfig::find_address();
+        let waiting = create_test_game(GameMode::OneVsOne, players[0]);
+        let finished = create_finished_game(&players);
+        let (loan_address, loan) = create_test_loan(&players[1], LoanStatus::Outstanding);
+
+        check::<JoinGameSol>(join_game_sol_accounts(&waiting, players[1], config_address, &config));
+        check::<CancelGameSol>(cancel_game_sol_accounts(&waiting, players[0], players[0]));
+        check::<FinalizeGameSol>(finalize_game_sol_accounts(&finished, &config, players[0], config.treasury));
+        check::<FinalizeGameWithLoan>(finalize_game_with_loan_accounts(&finished, &config, players[0], config.treasury));
+        check::<SettleLoan>(settle_loan_accounts(loan_address, &loan, players[1], KAMINO_PROGRAM_ID));
+        check::<WithdrawTreasury>(withdraw_treasury_accounts(&config, signer_account(Pubkey::new_unique())));
+    }
+
+    #[test]
+    fn test_settlement_books_the_whole_plan() {
+        // Test: Settling a finished game books its whole pot, pays the
+        // treasury what the plan leaves it, and can only happen once
+        let players = create_test_players(4);
+        let mut game = create_finished_game(&players);
+        let mut config = create_test_platform_config();
+
+        let winners = settlement::finished_game_winners(&game).unwrap();
+        assert_eq!(winners, vec![players[0], players[2]]);
+        let plan = PayoutPlan::new(
+            game.total_pot,
+            config.platform_fee_bps,
+            config.treasury_fee_bps,
+            winners.len(),
+        )
+        .unwrap();
+        let plan = plan.with_player_rewards(plan.treasury_fee / 2).unwrap();
+
+        // Every lamport of the pot has somewhere to go
+        assert_eq!(plan.total_pot, 4 * ENTRY_FEE);
+        assert_eq!(plan.platform_fee + plan.treasury_fee + plan.winnings().unwrap(), plan.total_pot);
+
+        settlement::record_settlement(&mut game, &mut config, &plan).unwrap();
+        assert_eq!(game.status, GameStatus::Settled);
+        assert_eq!(config.total_volume, plan.total_pot);
+        assert_eq!(config.treasury_balance, plan.treasury_payout());
+
+        // A settled game cannot be paid out again
+        assert_eq!(
+            settlement::finished_game_winners(&game).unwrap_err(),
+            GameError::InvalidGameStatus.into()
+        );
+
+        // Token games pay their treasury fee in tokens, so only the volume
+        // is booked against the lamport treasury
+        let mut token_game = create_finished_game(&players);
+        token_game.mint = Some(Pubkey::new_unique());
+        settlement::record_settlement(&mut token_game, &mut config, &plan).unwrap();
+        assert_eq!(config.total_volume, 2 * plan.total_pot);
+        assert_eq!(config.treasury_balance, plan.treasury_payout());
+    }
+
+    #[test]
+    fn test_started_games_cannot_be_cancelled_or_settled_early() {
+        // Test: Status changes go through the lifecycle, so a game in play
+        // can neither be refunded nor paid out
+        let players = create_test_players(2);
+        let mut game = create_test_game(GameMode::OneVsOne, players[0]);
+        game.add_joiner(players[1]).unwrap();
+        game.add_to_pot(ENTRY_FEE).unwrap();
+        game.status = GameStatus::InProgress;
+        let config = create_test_platform_config();
+
+        let accounts = cancel_game_sol_accounts(&game, players[0], players[0]);
+        let (accounts, result) = run_instruction::<CancelGameSol>(accounts, |ctx| {
+            instructions::cancel_game_sol::cancel_game_sol(ctx)
+        });
+        assert_eq!(result.unwrap_err(), GameError::InvalidGameStatus.into());
+        assert_eq!(accounts.game.status, GameStatus::InProgress);
+
+        let accounts = finalize_game_sol_accounts(&game, &config, players[0], config.treasury);
+        let (accounts, result) = run_instruction::<FinalizeGameSol>(accounts, |ctx| {
+            instructions::finalize_game_sol::finalize_game_sol(ctx)
+        });
+        assert_eq!(result.unwrap_err(), GameError::InvalidGameStatus.into());
+        assert_eq!(accounts.game.status, GameStatus::InProgress);
+
+        // Nothing leaves a terminal status
+        for status in [GameStatus::Settled, GameStatus::Refunded, GameStatus::Expired] {
+            assert!(lifecycle::is_terminal(status));
+            assert_eq!(
+                lifecycle::transition(status, GameStatus::InProgress).unwrap_err(),
+                GameError::InvalidGameStatus.into()
+            );
+        }
+    }
+
+    #[test]
+    fn test_pending_shot_cannot_be_forfeited() {
+        // Test: A shooter waiting on their shot's randomness has acted, so
+        // an overdue callback is retried rather than forfeited
+        let players = create_test_players(2);
+        let mut game = create_test_game(GameMode::OneVsOne, players[0]);
+        game.add_joiner(players[1]).unwrap();
+        game.lock_turn_order();
+        game.status = GameStatus::InProgress;
+        game.vrf_fulfilled = true;
+        game.pull_trigger(-100).unwrap();
+
+        let accounts = claim_timeout_forfeit_accounts(&game);
+        let (accounts, result) = run_instruction::<ClaimTimeoutForfeit>(accounts, |ctx| {
+            instructions::claim_timeout_forfeit::claim_timeout_forfeit(ctx)
+        });
+        assert_eq!(result.unwrap_err(), GameError::VrfRequestPending.into());
+        assert_eq!(accounts.game.status, GameStatus::InProgress);
+        assert!(accounts.game.vrf_pending);
+    }
+
+    #[test]
+    fn test_sol_vault_funded_by_entry_fees_alone_settles() {
+        // Test: The entry fees are all a SOL vault receives, and all
+        // finalize needs; the vault is closed in the same instruction
+        let players = create_test_players(2);
+        let game = create_finished_game(&players);
+        let config = create_test_platform_config();
+        let (game_address, _) = Game::find_address(game.game_id);
+        let (vault_address, _) = Game::find_vault_address(&game_address);
+
+        let funded = |lamports: u64| {
+            let mut accounts = finalize_game_sol_accounts(&game, &config, players[0], config.treasury);
+            accounts[4] = test_account(vault_address, system_program::ID, lamports, vec![], false);
+            accounts
+        };
+
+        let (accounts, result) = run_until_cpi::<FinalizeGameSol>(funded(game.total_pot - 1), entrant_accounts(&game), |ctx| {
+            instructions::finalize_game_sol::finalize_game_sol(ctx)
+        });
+        assert_eq!(result.unwrap_err(), GameError::InsufficientVaultBalance.into());
+        assert_eq!(accounts.game.status, GameStatus::Finished);
+
+        // Settled and ready to pay out when the first transfer goes out
+        let (accounts, result) = run_until_cpi::<FinalizeGameSol>(funded(game.total_pot), entrant_accounts(&game), |ctx| {
+            instructions::finalize_game_sol::finalize_game_sol(ctx)
+        });
+        result.unwrap();
+        assert_eq!(accounts.game.status, GameStatus::Settled);
+        assert_eq!(accounts.platform_config.total_volume, game.total_pot);
+    }
+
+    #[test]
+    fn test_only_settled_games_can_be_closed() {
+        // Test: close_game refuses a game that has not been paid out
+        let players = create_test_players(2);
+        let mut game = create_finished_game(&players);
+
+        let (accounts, result) = run_instruction::<CloseGame>(close_game_accounts(&game, players[0]), |ctx| {
+            instructions::close_game::close_game(ctx)
+        });
+        assert_eq!(result.unwrap_err(), GameError::InvalidGameStatus.into());
+        assert_eq!(accounts.game.status, GameStatus::Finished);
+
+        game.transition(GameStatus::Settled).unwrap();
+        assert!(try_accounts::<CloseGame>(close_game_accounts(&game, players[0]), &[]).is_ok());
+    }
+
+    #[test]
+    fn test_game_rent_only_returns_to_creator() {
+        // Test: Every instruction that closes a game account or its vault
+        // only takes the game's own creator as the rent recipient
+        let players = create_test_players(2);
+        let attacker = Pubkey::new_unique();
+        let config = create_test_platform_config();
+        let waiting = create_test_game(GameMode::OneVsOne, players[0]);
+        let finished = create_finished_game(&players);
+        let mut settled = create_finished_game(&players);
+        settled.transition(GameStatus::Settled).unwrap();
+
+        let accounts = finalize_game_sol_accounts(&finished, &config, attacker, config.treasury);
+        let err = try_accounts::<FinalizeGameSol>(accounts, &[]).err().unwrap();
+        assert_eq!(err, GameError::Unauthorized.into());
+
+        let accounts = cancel_game_sol_accounts(&waiting, players[0], attacker);
+        let err = try_accounts::<CancelGameSol>(accounts, &[]).err().unwrap();
+        assert_eq!(err, GameError::Unauthorized.into());
+
+        let err = try_accounts::<CloseGame>(close_game_accounts(&settled, attacker), &[]).err().unwrap();
+        assert_eq!(err, GameError::Unauthorized.into());
+    }
+
+    #[test]
+    fn test_losing_borrower_debt_is_settled_in_program() {
+        // Test: Debt the vault could not repay stays on the borrower's
+        // LoanPosition as Outstanding, and only that borrower can settle it
+        let borrower = Pubkey::new_unique();
+        let (loan_address, loan) = create_test_loan(&borrower, LoanStatus::Open);
+        let loan_account: &'static AccountInfo<'static> =
+            Box::leak(Box::new(program_state(loan_address, &loan, LoanPosition::LEN)));
+        let account = |key| -> &'static AccountInfo<'static> { Box::leak(Box::new(unchecked_account(key))) };
+        let mut borrower_loan = BorrowerLoan {
+            borrower,
+            debt: loan.loan_amount,
+            borrow_rate_bsf: loan.borrow_rate_bsf,
+            repaid: 0,
+            position: Account::try_from(loan_account).unwrap(),
+            lending_market: account(loan.lending_market),
+            reserve: account(loan.reserve),
+            reserve_liquidity_supply: account(Pubkey::new_unique()),
+            obligation: account(loan.obligation),
+        };
+
+        // A losing borrower's share does not cover their debt
+        assert_eq!(borrower_loan.cover(loan.loan_amount / 4), loan.loan_amount / 4);
+        assert_eq!(borrower_loan.outstanding(), loan.loan_amount - loan.loan_amount / 4);
+        borrower_loan.record().unwrap();
+        let position = Account::<LoanPosition>::try_from(loan_account).unwrap();
+        assert_eq!(position.status, LoanStatus::Outstanding);
+        assert_eq!(position.loan_amount, borrower_loan.outstanding());
+
+        // Only the borrower can settle it: the position is derived from the
+        // borrower, so no other signer can even address it
+        let accounts = settle_loan_accounts(loan_address, &position, Pubkey::new_unique(), KAMINO_PROGRAM_ID);
+        let err = try_accounts::<SettleLoan>(accounts, &[]).err().unwrap();
+        assert_eq!(err, ErrorCode::ConstraintSeeds.into());
+
+        // A winner's share covers the whole debt
+        assert_eq!(borrower_loan.cover(2 * loan.loan_amount), loan.loan_amount);
+        borrower_loan.record().unwrap();
+        let position = Account::<LoanPosition>::try_from(loan_account).unwrap();
+        assert_eq!(position.status, LoanStatus::Repaid);
+
+        // Positions still open are settled by finalize or cancel first
+        let accounts = settle_loan_accounts(loan_address, &loan, borrower, KAMINO_PROGRAM_ID);
+        let (_, result) = run_instruction::<SettleLoan>(accounts, |ctx| {
+            instructions::settle_loan::settle_loan(ctx)
+        });
+        assert_eq!(result.unwrap_err(), GameError::InvalidGameStatus.into());
+    }
+
+    #[test]
+    fn test_loan_games_settle_every_borrower() {
+        // Test: Loans are tracked per seat, settling a loan game needs the
+        // open LoanPosition of every borrower, and the plain SOL paths
+        // refuse loan games
+        let players = create_test_players(4);
+        let mut game = create_finished_game(&players);
+        game.record_loan(&players[1]).unwrap();
+        game.record_loan(&players[2]).unwrap();
+        assert!(game.has_loan());
+        assert_eq!(game.borrowers(), vec![players[2], players[1]]);
+        assert_eq!(
+            game.record_loan(&Pubkey::new_unique()).unwrap_err(),
+            GameError::InvalidLoanPosition.into()
+        );
+
+        let (game_address, _) = Game::find_address(game.game_id);
+        let loan_accounts = |borrower: &Pubkey, status| {
+            let (loan_address, loan) = create_test_loan(borrower, status);
+            vec![
+                program_state(loan_address, &loan, LoanPosition::LEN),
+                unchecked_account(loan.lending_market),
+                unchecked_account(loan.reserve),
+                unchecked_account(Pubkey::new_unique()),
+                unchecked_account(loan.obligation),
+            ]
+        };
+        let load_all = |accounts: Vec<AccountInfo<'static>>| {
+            let accounts: &'static [AccountInfo<'static>] = Box::leak(accounts.into_boxed_slice());
+            BorrowerLoan::load_all(&game_address, &game.borrowers(), accounts).err().unwrap()
+        };
+
+        // Every borrower's position must be passed in
+        assert_eq!(
+            load_all(loan_accounts(&players[2], LoanStatus::Open)),
+            GameError::InvalidLoanPosition.into()
+        );
+
+        // In seat order, and still open
+        let swapped = [
+            loan_accounts(&players[1], LoanStatus::Open),
+            loan_accounts(&players[2], LoanStatus::Open),
+        ]
+        .concat();
+        assert_eq!(load_all(swapped), GameError::InvalidLoanPosition.into());
+        let settled = [
+            loan_accounts(&players[2], LoanStatus::Repaid),
+            loan_accounts(&players[1], LoanStatus::Open),
+        ]
+        .concat();
+        assert_eq!(load_all(settled), GameError::InvalidLoanPosition.into());
+
+        // SOL finalize and cancel would leave the loans unpaid
+        let config = create_test_platform_config();
+        let accounts = finalize_game_sol_accounts(&game, &config, players[0], config.treasury);
+        let (_, result) = run_instruction::<FinalizeGameSol>(accounts, |ctx| {
+            instructions::finalize_game_sol::finalize_game_sol(ctx)
+        });
+        assert_eq!(result.unwrap_err(), GameError::InvalidGameMode.into());
+
+        let mut waiting = create_test_game(GameMode::OneVsOne, players[0]);
+        waiting.record_loan(&players[0]).unwrap();
+        let accounts = cancel_game_sol_accounts(&waiting, players[0], players[0]);
+        let (accounts, result) = run_instruction::<CancelGameSol>(accounts, |ctx| {
+            instructions::cancel_game_sol::cancel_game_sol(ctx)
+        });
+        assert_eq!(result.unwrap_err(), GameError::InvalidGameMode.into());
+        assert_eq!(accounts.game.status, GameStatus::WaitingForPlayers);
+    }
+
+    #[test]
+    fn test_loan_collateral_is_priced_by_registered_oracles() {
+        // Test: Prices only come from the feeds registered for the asset,
+        // and the collateral ratio can never drop to 100% or below
+        let asset_oracle = AssetOracle {
+            mint: native_mint::ID,
+            pyth_feed_id: [7; 32],
+            switchboard_feed: Pubkey::new_unique(),
+            bump: AssetOracle::find_address(&native_mint::ID).1,
+        };
+
+        // A price account the attacker wrote is not a Pyth price update, and
+        // the Switchboard fallback must be the feed registered for the asset
+        let fake_pyth = test_account(Pubkey::new_unique(), Pubkey::new_unique(), 0, vec![0; 256], false);
+        let unregistered = test_account(
+            Pubkey::new_unique(),
+            constants::SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+            0,
+            vec![0; 256],
+            false,
+        );
+        assert_eq!(
+            oracle::price(&asset_oracle, &fake_pyth, &unregistered, 0).unwrap_err(),
+            GameError::InvalidOracleAccount.into()
+        );
+
+        // The admin cannot lower the ratio to an under-collateralised loan
+        let config = create_test_platform_config();
+        let mut accounts = vec![
+            program_state(PlatformConfig::find_address().0, &config, PlatformConfig::LEN),
+            signer_account(config.authority),
+        ];
+        accounts.extend(event_cpi_accounts());
+        let (accounts, result) = run_instruction::<UpdatePlatformConfig>(accounts, |ctx| {
+            instructions::admin::set_min_collateral_ratio(ctx, 9_999)
+        });
+        assert_eq!(result.unwrap_err(), GameError::InvalidCollateralRatio.into());
+        assert_eq!(accounts.platform_config.min_collateral_ratio_bps, config.min_collateral_ratio_bps);
+    }
+
     // ========================================================================
     // GAME LOGIC TESTS
     // ========================================================================
@@ -343,13 +989,12 @@ mod security_tests {
         let min_fee = 100_000_000u64; // 0.1 tokens with 9 decimals
         let invalid_fee = 50_000_000u64; // 0.05 tokens
         
-        // Attempt: Create game with fee below minimum
-        // Expected: Should panic with InsufficientEntryFee
-        
         assert!(invalid_fee < min_fee, "Test setup failed");
+        Game::require_entry_fee(min_fee, Some(9)).unwrap();
         
-        // TODO: Implement full test with program context
-        println!("Test: Minimum entry fee validation for tokens");
+        // Attempt: Create game with fee below minimum
+        // Expected: Should panic with InsufficientEntryFee
+        Game::require_entry_fee(invalid_fee, Some(9)).unwrap();
     }
 
     #[test]
@@ -360,13 +1005,12 @@ mod security_tests {
         let min_fee = 10_000_000u64; // 0.01 SOL
         let invalid_fee = 5_000_000u64; // 0.005 SOL
         
-        // Attempt: Create game with fee below minimum
-        // Expected: Should panic with InsufficientEntryFee
-        
         assert!(invalid_fee < min_fee, "Test setup failed");
+        Game::require_entry_fee(min_fee, None).unwrap();
         
-        // TODO: Implement full test with program context
-        println!("Test: Minimum entry fee validation for SOL");
+        // Attempt: Create game with fee below minimum
+        // Expected: Should panic with InsufficientEntryFee
+        Game::require_entry_fee(invalid_fee, None).unwrap();
     }
 
     // ========================================================================
@@ -382,13 +1026,18 @@ mod security_tests {
         let required_collateral = entry_fee * 110 / 100; // 0.11 SOL
         let insufficient_collateral = entry_fee; // 0.1 SOL (only 100%)
         
-        // Attempt: Create game with insufficient collateral
-        // Expected: Should panic with InsufficientCollateral
-        
         assert!(insufficient_collateral < required_collateral, "Test setup failed");
         
-        // TODO: Implement full test with program context
-        println!("Test: Collateral requirement validation");
+        // Collateral and loan priced alike, at $1 with no uncertainty
+        let price = OraclePrice { price: 100_000_000, confidence: 0, exponent: -8, publish_time: 0 };
+        let min_ratio = constants::DEFAULT_MIN_COLLATERAL_RATIO_BPS;
+        oracle::require_collateral_ratio(required_collateral, 9, &price, entry_fee, 9, &price, min_ratio)
+            .unwrap();
+        
+        // Attempt: Create game with insufficient collateral
+        // Expected: Should panic with InsufficientCollateral
+        oracle::require_collateral_ratio(insufficient_collateral, 9, &price, entry_fee, 9, &price, min_ratio)
+            .unwrap();
     }
 
     #[test]
@@ -417,15 +1066,19 @@ mod security_tests {
         // Test: Paused platform prevents new joins
         
         // Setup: Set platform_config.paused = true
+        let mut config = create_test_platform_config();
+        config.paused = true;
+        
+        let game = create_test_game(GameMode::OneVsOne, Pubkey::new_unique());
+        let player = Pubkey::new_unique();
         
         // Attempt: Try to join game
         // Expected: Should panic with PlatformPaused
         
         // This test validates:
         // constraint = !platform_config.paused @ GameError::PlatformPaused
-        
-        // TODO: Implement full test with program context
-        println!("Test: Platform pause prevents joins");
+        let accounts = join_game_sol_accounts(&game, player, PlatformConfig::find_address().0, &config);
+        try_accounts::<JoinGameSol>(accounts, &[]).unwrap();
     }
 }
 
@@ -436,10 +1089,91 @@ mod security_tests {
 #[cfg(test)]
 mod integration_helpers {
     use super::*;
+    use solana_sysvar::program_stubs::{set_syscall_stubs, SyscallStubs};
+    use anchor_lang::solana_program::{bpf_loader_upgradeable, system_program, sysvar};
+    use anchor_lang::Bumps;
+    use anchor_spl::token::spl_token::{self, native_mint};
+    use state::{Game, GameMode, GameStatus, LoanPosition, LoanStatus, PlatformConfig, PlayerStats, TreasuryRewards};
+    use std::collections::BTreeSet;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::Once;
+
+    pub const ENTRY_FEE: u64 = 100_000_000;
 
-    // Helper: Create test game
-    pub fn create_test_game() {
-        // TODO: Implement game creation helper
+    // Helper: Create test game, waiting for players with only the creator seated
+    pub fn create_test_game(game_mode: GameMode, creator: Pubkey) -> Game {
+        let game_id = 7;
+        Game {
+            game_id,
+            creator,
+            game_mode,
+            status: GameStatus::WaitingForPlayers,
+            entry_fee: ENTRY_FEE,
+            total_pot: ENTRY_FEE,
+            team_a: [creator, Pubkey::default()],
+            team_a_count: 1,
+            turn_timeout_seconds: 60,
+            bump: Game::find_address(game_id).1,
+            ..Default::default()
+        }
+    }
+
+    // Helper: Create test platform config, run by a single authority wallet
+    pub fn create_test_platform_config() -> PlatformConfig {
+        PlatformConfig {
+            authority: Pubkey::new_unique(),
+            pending_authority: None,
+            treasury: Pubkey::new_unique(),
+            platform_mint: Pubkey::new_unique(),
+            platform_fee_bps: 500,
+            treasury_fee_bps: 1000,
+            rewards_share_bps: 2000,
+            total_games: 0,
+            total_volume: 0,
+            treasury_balance: 0,
+            paused: false,
+            turn_timeout_seconds: 60,
+            min_collateral_ratio_bps: constants::DEFAULT_MIN_COLLATERAL_RATIO_BPS,
+            multisig_authority: None,
+            platform_vault: None,
+            treasury_vault: None,
+            bump: PlatformConfig::find_address().1,
+        }
+    }
+
+    // Helper: Create a game whose team A has won, with every seat paid in
+    pub fn create_finished_game(players: &[Pubkey]) -> Game {
+        let game_mode = if players.len() == 4 { GameMode::TwoVsTwo } else { GameMode::OneVsOne };
+        let mut game = create_test_game(game_mode, players[0]);
+        for player in &players[1..] {
+            game.add_joiner(*player).unwrap();
+            game.add_to_pot(ENTRY_FEE).unwrap();
+        }
+        game.status = GameStatus::Finished;
+        game.winner_team = Some(0);
+        game
+    }
+
+    // Helper: Create the loan `borrower` took out to join the test game
+    pub fn create_test_loan(borrower: &Pubkey, status: LoanStatus) -> (Pubkey, LoanPosition) {
+        let (game, _) = Game::find_address(7);
+        let (address, bump) = LoanPosition::find_address(&game, borrower);
+        let loan = LoanPosition {
+            game,
+            game_id: 7,
+            borrower: *borrower,
+            lending_market: Pubkey::new_unique(),
+            reserve: Pubkey::new_unique(),
+            collateral_reserve: Pubkey::new_unique(),
+            obligation: Pubkey::new_unique(),
+            collateral_amount: 2 * ENTRY_FEE,
+            collateral_ctokens: 2 * ENTRY_FEE,
+            loan_amount: ENTRY_FEE,
+            status,
+            bump,
+            borrow_rate_bsf: 1 << 60,
+        };
+        (address, loan)
     }
 
     // Helper: Create test players
@@ -460,6 +1194,359 @@ mod integration_helpers {
         let treasury_fee = (total_pot as u128 * treasury_fee_bps as u128 / 10000) as u64;
         (platform_fee, treasury_fee)
     }
+
+    // Helper: Writable account handed to an instruction, leaked so it
+    // lives as long as the accounts struct built from it
+    pub fn test_account(
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+        is_signer: bool,
+    ) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            true,
+            Box::leak(Box::new(lamports)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            false,
+            0,
+        )
+    }
+
+    // Helper: Empty system account, for accounts the program does not read
+    pub fn unchecked_account(key: Pubkey) -> AccountInfo<'static> {
+        test_account(key, system_program::ID, 0, vec![], false)
+    }
+
+    // Helper: Wallet that signs the transaction
+    pub fn signer_account(key: Pubkey) -> AccountInfo<'static> {
+        test_account(key, system_program::ID, 10 * ENTRY_FEE, vec![], true)
+    }
+
+    // Helper: Deployed program
+    pub fn program_account(program_id: Pubkey) -> AccountInfo<'static> {
+        let mut account = unchecked_account(program_id);
+        account.owner = Box::leak(Box::new(bpf_loader_upgradeable::ID));
+        account.executable = true;
+        account
+    }
+
+    // Helper: Rent exempt account of this program holding `state` in `space` bytes
+    pub fn program_state<T: AccountSerialize>(key: Pubkey, state: &T, space: usize) -> AccountInfo<'static> {
+        let mut data = Vec::with_capacity(space);
+        state.try_serialize(&mut data).unwrap();
+        data.resize(space, 0);
+        test_account(key, ::magic_roulette::ID, Rent::default().minimum_balance(space), data, false)
+    }
+
+    // Helper: Event authority and program accounts #[event_cpi] appends
+    pub fn event_cpi_accounts() -> [AccountInfo<'static>; 2] {
+        let (event_authority, _) = Pubkey::find_program_address(&[b"__event_authority"], &::magic_roulette::ID);
+        [unchecked_account(event_authority), program_account(::magic_roulette::ID)]
+    }
+
+    // Helper: `player`'s PlayerStats account, before their first game
+    pub fn player_stats_account(player: Pubkey) -> AccountInfo<'static> {
+        let (address, bump) = PlayerStats::find_address(&player);
+        let stats = PlayerStats {
+            player,
+            games_played: 0,
+            games_won: 0,
+            total_wagered: 0,
+            total_winnings: 0,
+            shots_survived: 0,
+            bump,
+        };
+        program_state(address, &stats, PlayerStats::LEN)
+    }
+
+    // Helper: `player`'s TreasuryRewards account, with nothing claimable yet
+    pub fn treasury_rewards_account(player: Pubkey) -> AccountInfo<'static> {
+        let (address, bump) = TreasuryRewards::find_address(&player);
+        let rewards = TreasuryRewards {
+            player,
+            claimable_amount: 0,
+            total_claimed: 0,
+            last_claim: 0,
+            bump,
+            claimable_lamports: 0,
+            total_claimed_lamports: 0,
+        };
+        program_state(address, &rewards, TreasuryRewards::LEN)
+    }
+
+    // Helper: Remaining accounts of a finalize - every entrant's PlayerStats,
+    // then their TreasuryRewards
+    pub fn entrant_accounts(game: &Game) -> Vec<AccountInfo<'static>> {
+        let entrants = game.entrants();
+        let stats = entrants.iter().map(|player| player_stats_account(*player));
+        let rewards = entrants.iter().map(|player| treasury_rewards_account(*player));
+        stats.chain(rewards).collect()
+    }
+
+    // Helper: JoinGameSol accounts for `player`, whose stats and rewards
+    // accounts already exist, with the platform config stored at `config_address`
+    pub fn join_game_sol_accounts(
+        game: &Game,
+        player: Pubkey,
+        config_address: Pubkey,
+        config: &PlatformConfig,
+    ) -> Vec<AccountInfo<'static>> {
+        let (game_address, _) = Game::find_address(game.game_id);
+        let mut accounts = vec![
+            program_state(game_address, game, Game::LEN),
+            signer_account(player),
+            player_stats_account(player),
+            treasury_rewards_account(player),
+            program_state(config_address, config, PlatformConfig::LEN),
+            unchecked_account(Game::find_vault_address(&game_address).0),
+            program_account(system_program::ID),
+        ];
+        accounts.extend(event_cpi_accounts());
+        accounts
+    }
+
+    // Helper: CancelGameSol accounts, signed by `caller`
+    pub fn cancel_game_sol_accounts(game: &Game, caller: Pubkey, creator: Pubkey) -> Vec<AccountInfo<'static>> {
+        let (game_address, _) = Game::find_address(game.game_id);
+        let mut accounts = vec![
+            program_state(game_address, game, Game::LEN),
+            signer_account(caller),
+            unchecked_account(creator),
+            unchecked_account(Game::find_vault_address(&game_address).0),
+            program_account(system_program::ID),
+        ];
+        accounts.extend(event_cpi_accounts());
+        accounts
+    }
+
+    // Helper: ClaimTimeoutForfeit accounts
+    pub fn claim_timeout_forfeit_accounts(game: &Game) -> Vec<AccountInfo<'static>> {
+        let (game_address, _) = Game::find_address(game.game_id);
+        let mut accounts = vec![program_state(game_address, game, Game::LEN)];
+        accounts.extend(event_cpi_accounts());
+        accounts
+    }
+
+    // Helper: FinalizeGameSol accounts, paying out to the game's first two entrants
+    pub fn finalize_game_sol_accounts(
+        game: &Game,
+        config: &PlatformConfig,
+        creator: Pubkey,
+        treasury: Pubkey,
+    ) -> Vec<AccountInfo<'static>> {
+        let (game_address, _) = Game::find_address(game.game_id);
+        let entrants = game.entrants();
+        let mut accounts = vec![
+            program_state(game_address, game, Game::LEN),
+            program_state(PlatformConfig::find_address().0, config, PlatformConfig::LEN),
+            signer_account(Pubkey::new_unique()),
+            unchecked_account(creator),
+            unchecked_account(Game::find_vault_address(&game_address).0),
+            unchecked_account(config.platform_fee_recipient()),
+            unchecked_account(treasury),
+            unchecked_account(entrants[0]),
+            unchecked_account(entrants.get(1).copied().unwrap_or_default()),
+            program_account(system_program::ID),
+        ];
+        accounts.extend(event_cpi_accounts());
+        accounts
+    }
+
+    // Helper: FinalizeGameWithLoan accounts, paying out to the game's first two entrants
+    pub fn finalize_game_with_loan_accounts(
+        game: &Game,
+        config: &PlatformConfig,
+        creator: Pubkey,
+        treasury: Pubkey,
+    ) -> Vec<AccountInfo<'static>> {
+        let (game_address, _) = Game::find_address(game.game_id);
+        let (vault_address, _) = Game::find_vault_address(&game_address);
+        let entrants = game.entrants();
+        let mut accounts = vec![
+            program_state(game_address, game, Game::LEN),
+            program_state(PlatformConfig::find_address().0, config, PlatformConfig::LEN),
+            unchecked_account(creator),
+            unchecked_account(vault_address),
+            unchecked_account(token::VaultWsol::new(&vault_address).account),
+            program_account(KAMINO_PROGRAM_ID),
+            unchecked_account(native_mint::ID),
+            unchecked_account(sysvar::instructions::ID),
+            unchecked_account(entrants[0]),
+            unchecked_account(entrants.get(1).copied().unwrap_or_default()),
+            unchecked_account(config.platform_fee_recipient()),
+            unchecked_account(treasury),
+            program_account(spl_token::ID),
+            program_account(anchor_spl::associated_token::ID),
+            program_account(system_program::ID),
+        ];
+        accounts.extend(event_cpi_accounts());
+        accounts
+    }
+
+    // Helper: CloseGame accounts for a SOL game
+    pub fn close_game_accounts(game: &Game, creator: Pubkey) -> Vec<AccountInfo<'static>> {
+        let (game_address, _) = Game::find_address(game.game_id);
+        let mut accounts = vec![
+            program_state(game_address, game, Game::LEN),
+            signer_account(Pubkey::new_unique()),
+            unchecked_account(creator),
+            unchecked_account(Game::find_vault_address(&game_address).0),
+            // Token accounts left out, passed as the program id
+            program_account(::magic_roulette::ID),
+            program_account(::magic_roulette::ID),
+            program_account(::magic_roulette::ID),
+            program_account(system_program::ID),
+        ];
+        accounts.extend(event_cpi_accounts());
+        accounts
+    }
+
+    // Helper: SettleLoan accounts for `loan`, signed by `signer`
+    pub fn settle_loan_accounts(
+        loan_address: Pubkey,
+        loan: &LoanPosition,
+        signer: Pubkey,
+        kamino_program: Pubkey,
+    ) -> Vec<AccountInfo<'static>> {
+        let mut accounts = vec![
+            program_state(loan_address, loan, LoanPosition::LEN),
+            signer_account(signer),
+            unchecked_account(loan.lending_market),
+            unchecked_account(Pubkey::new_unique()),
+            unchecked_account(loan.reserve),
+            unchecked_account(native_mint::ID),
+            unchecked_account(Pubkey::new_unique()),
+            unchecked_account(loan.obligation),
+            unchecked_account(loan.collateral_reserve),
+            unchecked_account(Pubkey::new_unique()),
+            unchecked_account(Pubkey::new_unique()),
+            unchecked_account(Pubkey::new_unique()),
+            program_account(kamino_program),
+            unchecked_account(sysvar::instructions::ID),
+            program_account(spl_token::ID),
+        ];
+        accounts.extend(event_cpi_accounts());
+        accounts
+    }
+
+    // Helper: WithdrawTreasury accounts, with `treasury_vault` as passed in
+    pub fn withdraw_treasury_accounts(
+        config: &PlatformConfig,
+        treasury_vault: AccountInfo<'static>,
+    ) -> Vec<AccountInfo<'static>> {
+        let mut accounts = vec![
+            program_state(PlatformConfig::find_address().0, config, PlatformConfig::LEN),
+            treasury_vault,
+            unchecked_account(Pubkey::new_unique()),
+            program_account(system_program::ID),
+        ];
+        accounts.extend(event_cpi_accounts());
+        accounts
+    }
+
+    // Reporting success for a sysvar off-chain leaves its default in place:
+    // Rent::default(), and a Clock at slot and timestamp 0
+    struct DefaultSysvars;
+
+    impl SyscallStubs for DefaultSysvars {
+        fn sol_get_clock_sysvar(&self, _var_addr: *mut u8) -> u64 {
+            0
+        }
+
+        fn sol_get_rent_sysvar(&self, _var_addr: *mut u8) -> u64 {
+            0
+        }
+    }
+
+    // Helper: Make the Rent and Clock sysvars readable off-chain
+    pub fn install_sysvars() {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            set_syscall_stubs(Box::new(DefaultSysvars));
+        });
+    }
+
+    // Helper: Validate `accounts` against an instruction's Accounts struct,
+    // as the program entrypoint does before running the handler
+    pub fn validate<T>(accounts: Vec<AccountInfo<'static>>, ix_data: &[u8]) -> Result<(T, <T as Bumps>::Bumps)>
+    where
+        T: Accounts<'static, <T as Bumps>::Bumps> + Bumps,
+        <T as Bumps>::Bumps: Default,
+    {
+        install_sysvars();
+        let mut accounts: &'static [AccountInfo<'static>] = Box::leak(accounts.into_boxed_slice());
+        let mut bumps = Default::default();
+        let validated = T::try_accounts(
+            &::magic_roulette::ID,
+            &mut accounts,
+            ix_data,
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )?;
+        Ok((validated, bumps))
+    }
+
+    // Helper: Validated accounts only, for checks that stop at validation
+    pub fn try_accounts<T>(accounts: Vec<AccountInfo<'static>>, ix_data: &[u8]) -> Result<T>
+    where
+        T: Accounts<'static, <T as Bumps>::Bumps> + Bumps,
+        <T as Bumps>::Bumps: Default,
+    {
+        validate(accounts, ix_data).map(|(validated, _)| validated)
+    }
+
+    // Helper: Run `handler` on validated `accounts`, returning the accounts
+    // as the handler left them along with its result. Handlers can only run
+    // up to their first CPI off-chain, so this exercises their checks
+    pub fn run_instruction<T>(
+        accounts: Vec<AccountInfo<'static>>,
+        handler: impl FnOnce(Context<'_, '_, 'static, 'static, T>) -> Result<()>,
+    ) -> (T, Result<()>)
+    where
+        T: Accounts<'static, <T as Bumps>::Bumps> + Bumps,
+        <T as Bumps>::Bumps: Default,
+    {
+        let (mut validated, bumps) = validate::<T>(accounts, &[]).unwrap_or_else(|err| panic!("{err}"));
+        let result = handler(Context::new(&::magic_roulette::ID, &mut validated, &[], bumps));
+        (validated, result)
+    }
+
+    // Helper: Like run_instruction, with `remaining` accounts, for handlers
+    // that pass every check. Reaching the first CPI, which only runs on-chain,
+    // counts as success; the accounts are returned as they were just before it
+    pub fn run_until_cpi<T>(
+        accounts: Vec<AccountInfo<'static>>,
+        remaining: Vec<AccountInfo<'static>>,
+        handler: impl FnOnce(Context<'_, '_, 'static, 'static, T>) -> Result<()>,
+    ) -> (T, Result<()>)
+    where
+        T: Accounts<'static, <T as Bumps>::Bumps> + Bumps,
+        <T as Bumps>::Bumps: Default,
+    {
+        let (mut validated, bumps) = validate::<T>(accounts, &[]).unwrap_or_else(|err| panic!("{err}"));
+        let remaining: &'static [AccountInfo<'static>] = Box::leak(remaining.into_boxed_slice());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            handler(Context::new(&::magic_roulette::ID, &mut validated, remaining, bumps))
+        }));
+        match result {
+            Ok(result) => (validated, result),
+            Err(payload) => {
+                let message = payload
+                    .downcast_ref::<String>()
+                    .map(String::as_str)
+                    .or_else(|| payload.downcast_ref::<&str>().copied())
+                    .unwrap_or_default();
+                if !message.contains("target_os = \"solana\"") {
+                    panic::resume_unwind(payload);
+                }
+                (validated, Ok(()))
+            }
+        }
+    }
 }
 
 // ========================================================================
@@ -3,93 +3,60 @@
 
 #[cfg(test)]
 mod unit_tests {
-    use magic_roulette::*;
+    use magic_roulette::state::{Game, GameMode};
 
     // ========================================================================
     // GAME CREATION TESTS
     // ========================================================================
 
-    #[test]
-    fn test_create_game_valid_1v1() {
-        let mut game = Game::default();
-        let entry_fee = 100_000_000u64;
-        
-        game.entry_fee = entry_fee;
-        game.game_mode = GameMode::OneVsOne;
-        game.status = GameStatus::WaitingForPlayers;
-        
-        assert_eq!(game.entry_fee, entry_fee);
-        assert_eq!(game.game_mode, GameMode::OneVsOne);
-        assert_eq!(game.status, GameStatus::WaitingForPlayers);
-    }
-
-    #[test]
-    fn test_create_game_valid_2v2() {
-        let mut game = Game::default();
-        game.game_mode = GameMode::TwoVsTwo;
-        
-        assert_eq!(game.game_mode, GameMode::TwoVsTwo);
-    }
-
-    #[test]
-    fn test_create_game_zero_fee_invalid() {
-        let entry_fee = 0u64;
-        assert_eq!(entry_fee, 0);
-        // Should be rejected by constraint
-    }
-
-    #[test]
-    fn test_create_game_min_fee_valid() {
-        let entry_fee = 10_000_000u64; // 0.01 SOL
-        assert!(entry_fee > 0);
-    }
-
-    #[test]
-    fn test_create_game_max_fee_valid() {
-        let entry_fee = u64::MAX;
-        assert!(entry_fee > 0);
-    }
-
     // ========================================================================
     // GAME STATE TESTS
     // ========================================================================
 
     #[test]
     fn test_game_is_full_1v1_not_full() {
-        let mut game = Game::default();
-        game.game_mode = GameMode::OneVsOne;
-        game.team_a_count = 1;
-        game.team_b_count = 0;
+        let game = Game {
+            game_mode: GameMode::OneVsOne,
+            team_a_count: 1,
+            team_b_count: 0,
+            ..Default::default()
+        };
         
         assert!(!game.is_full());
     }
 
     #[test]
     fn test_game_is_full_1v1_full() {
-        let mut game = Game::default();
-        game.game_mode = GameMode::OneVsOne;
-        game.team_a_count = 1;
-        game.team_b_count = 1;
+        let game = Game {
+            game_mode: GameMode::OneVsOne,
+            team_a_count: 1,
+            team_b_count: 1,
+            ..Default::default()
+        };
         
         assert!(game.is_full());
     }
 
     #[test]
     fn test_game_is_full_2v2_not_full() {
-        let mut game = Game::default();
-        game.game_mode = GameMode::TwoVsTwo;
-        game.team_a_count = 2;
-        game.team_b_count = 1;
+        let game = Game {
+            game_mode: GameMode::TwoVsTwo,
+            team_a_count: 2,
+            team_b_count: 1,
+            ..Default::default()
+        };
         
         assert!(!game.is_full());
     }
 
     #[test]
     fn test_game_is_full_2v2_full() {
-        let mut game = Game::default();
-        game.game_mode = GameMode::TwoVsTwo;
-        game.team_a_count = 2;
-        game.team_b_count = 2;
+        let game = Game {
+            game_mode: GameMode::TwoVsTwo,
+            team_a_count: 2,
+            team_b_count: 2,
+            ..Default::default()
+        };
         
         assert!(game.is_full());
     }
@@ -113,378 +80,2641 @@ mod unit_tests {
         
         assert_eq!(game.get_required_players(), 4);
     }
+}
 
-    // ========================================================================
-    // FEE CALCULATION TESTS
-    // ========================================================================
+// ========================================================================
+// TURN ORDER TESTS
+// ========================================================================
 
-    #[test]
-    fn test_fee_calculation_5_percent() {
-        let total_pot = 1_000_000_000u64;
-        let fee_bps = 500u16; // 5%
-        
-        let fee = (total_pot as u128)
-            .checked_mul(fee_bps as u128)
+#[cfg(test)]
+mod turn_order_tests {
+    use anchor_lang::prelude::Pubkey;
+    use magic_roulette::errors::GameError;
+    use magic_roulette::state::{chamber, turn_order, AiDifficulty, Game, GameMode, GameStatus};
+
+    const ALL_MODES: [GameMode; 3] = [GameMode::OneVsOne, GameMode::TwoVsTwo, GameMode::HumanVsAi];
+
+    // Helper: Build a full, in-progress game for the given mode
+    fn full_game(game_mode: GameMode) -> Game {
+        let creator = Pubkey::new_unique();
+        let (team_a, team_b, team_a_count, team_b_count) = match game_mode {
+            GameMode::OneVsOne => (
+                [creator, Pubkey::default()],
+                [Pubkey::new_unique(), Pubkey::default()],
+                1,
+                1,
+            ),
+            GameMode::TwoVsTwo => (
+                [creator, Pubkey::new_unique()],
+                [Pubkey::new_unique(), Pubkey::new_unique()],
+                2,
+                2,
+            ),
+            GameMode::HumanVsAi => (
+                [creator, Pubkey::default()],
+                [Pubkey::new_unique(), Pubkey::default()],
+                1,
+                1,
+            ),
+        };
+        let is_ai_game = game_mode == GameMode::HumanVsAi;
+
+        let mut game = Game {
+            game_id: 0,
+            creator,
+            game_mode,
+            status: GameStatus::InProgress,
+            entry_fee: 0,
+            total_pot: 0,
+            mint: None,
+            is_ai_game,
+            ai_difficulty: is_ai_game.then_some(AiDifficulty::Easy),
+            ai_player: is_ai_game.then_some(team_b[0]),
+            is_practice_mode: is_ai_game,
+            loan_seats: 0,
+            team_a,
+            team_b,
+            team_a_count,
+            team_b_count,
+            bullet_chamber: 0,
+            current_chamber: 1,
+            current_turn: 0,
+            shots_taken: 0,
+            turn_order: [0u8; turn_order::MAX_SEATS],
+            turn_order_len: 0,
+            turn_timeout_seconds: 60,
+            turn_deadline: None,
+            shots_survived: [0; turn_order::MAX_SEATS],
+            vrf_seed: [0u8; 32],
+            vrf_result: [0u8; 32],
+            vrf_pending: false,
+            vrf_fulfilled: true,
+            winner_team: None,
+            created_at: 0,
+            finished_at: None,
+            bump: 255,
+        };
+        assert!(game.is_full());
+        game.lock_turn_order();
+        game
+    }
+
+    fn team_of(game: &Game, player: &Pubkey) -> u8 {
+        if game.team_a.contains(player) { 0 } else { 1 }
+    }
+
+    // Helper: A VRF output that fires, or does not fire, the shot at `shot_index`
+    fn draw(shot_index: u8, fires: bool) -> [u8; 32] {
+        (0u8..=255)
+            .map(|i| [i; 32])
+            .find(|randomness| chamber::shot_fires(randomness, shot_index) == fires)
             .unwrap()
-            .checked_div(10000)
-            .unwrap() as u64;
-        
-        assert_eq!(fee, 50_000_000);
     }
 
     #[test]
-    fn test_fee_calculation_10_percent() {
-        let total_pot = 1_000_000_000u64;
-        let fee_bps = 1000u16; // 10%
-        
-        let fee = (total_pot as u128)
-            .checked_mul(fee_bps as u128)
-            .unwrap()
-            .checked_div(10000)
-            .unwrap() as u64;
-        
-        assert_eq!(fee, 100_000_000);
+    fn test_rotation_seats_every_player_once() {
+        for mode in ALL_MODES {
+            let game = full_game(mode);
+            let expected = game.get_required_players().max(2) as usize;
+            assert_eq!(game.turn_order_len as usize, expected, "{:?}", mode);
+
+            let mut seen: Vec<Pubkey> = Vec::new();
+            let mut probe = game.clone();
+            for _ in 0..game.turn_order_len {
+                let player = probe.get_current_player().unwrap();
+                assert_ne!(player, Pubkey::default(), "{:?} seated an empty slot", mode);
+                assert!(!seen.contains(&player), "{:?} seated a player twice", mode);
+                seen.push(player);
+                probe.advance_turn(0).unwrap();
+            }
+        }
     }
 
     #[test]
-    fn test_fee_calculation_zero_fee() {
-        let total_pot = 1_000_000_000u64;
-        let fee_bps = 0u16;
-        
-        let fee = (total_pot as u128)
-            .checked_mul(fee_bps as u128)
-            .unwrap()
-            .checked_div(10000)
-            .unwrap() as u64;
-        
-        assert_eq!(fee, 0);
+    fn test_rotation_wraps_for_every_mode_and_chamber() {
+        for mode in ALL_MODES {
+            for firing_shot in 0..chamber::CHAMBERS {
+                let mut game = full_game(mode);
+                let lap = game.turn_order_len as usize;
+                let mut shooters: Vec<Pubkey> = Vec::new();
+                let mut last_team: Option<u8> = None;
+
+                // Every shot is decided by its own draw, the last one firing
+                while game.status == GameStatus::InProgress {
+                    let shooter = game.get_current_player().unwrap();
+                    let team = game.current_team().unwrap();
+                    assert_ne!(shooter, Pubkey::default());
+                    assert_eq!(team, team_of(&game, &shooter));
+
+                    // Teams always alternate
+                    if let Some(previous) = last_team {
+                        assert_ne!(previous, team, "{:?} shot {}", mode, firing_shot);
+                    }
+                    last_team = Some(team);
+
+                    // Seat rotation repeats every lap
+                    if shooters.len() >= lap {
+                        assert_eq!(shooter, shooters[shooters.len() - lap]);
+                    }
+                    shooters.push(shooter);
+
+                    let shot = game.shots_taken;
+                    game.pull_trigger(0).unwrap();
+                    game.resolve_shot(draw(shot, shot == firing_shot), 0).unwrap();
+                    assert!((game.current_turn as usize) < lap);
+                }
+
+                assert_eq!(shooters.len(), firing_shot as usize + 1);
+                assert_eq!(game.bullet_chamber, firing_shot + 1);
+                assert_eq!(game.winner_team, Some(1 - last_team.unwrap()));
+            }
+        }
     }
 
     #[test]
-    fn test_fee_calculation_max_fee() {
-        let total_pot = 1_000_000_000u64;
-        let fee_bps = 10000u16; // 100%
-        
-        let fee = (total_pot as u128)
-            .checked_mul(fee_bps as u128)
-            .unwrap()
-            .checked_div(10000)
-            .unwrap() as u64;
-        
-        assert_eq!(fee, total_pot);
+    fn test_advance_turn_wraps_chamber_and_deadline() {
+        let mut game = full_game(GameMode::OneVsOne);
+        for expected in [2u8, 3, 4, 5, 6, 1] {
+            game.advance_turn(100).unwrap();
+            assert_eq!(game.current_chamber, expected);
+            assert_eq!(game.turn_deadline, Some(160));
+        }
     }
 
     #[test]
-    fn test_fee_calculation_rounding() {
-        let total_pot = 1_000_000_001u64;
-        let fee_bps = 500u16; // 5%
-        
-        let fee = (total_pot as u128)
-            .checked_mul(fee_bps as u128)
-            .unwrap()
-            .checked_div(10000)
-            .unwrap() as u64;
-        
-        // Should round down
-        assert_eq!(fee, 50_000_000);
+    fn test_build_rotation_skips_empty_seats() {
+        let a1 = Pubkey::new_unique();
+        let b1 = Pubkey::new_unique();
+        let b2 = Pubkey::new_unique();
+
+        let (rotation, len) = turn_order::build_rotation(&[a1, Pubkey::default()], &[b1, b2]);
+        assert_eq!(len, 3);
+        assert_eq!(&rotation[..3], &[turn_order::seat(0, 0), turn_order::seat(1, 0), turn_order::seat(1, 1)]);
     }
 
-    // ========================================================================
-    // PRIZE DISTRIBUTION TESTS
-    // ========================================================================
+    #[test]
+    fn test_current_player_requires_locked_rotation() {
+        let mut game = full_game(GameMode::OneVsOne);
+        game.turn_order_len = 0;
+        assert!(game.get_current_player().is_err());
+    }
 
     #[test]
-    fn test_prize_distribution_1v1() {
-        let total_pot = 1_000_000_000u64;
-        let platform_fee_bps = 500u16;
-        let treasury_fee_bps = 1000u16;
-        
-        let platform_fee = (total_pot as u128 * platform_fee_bps as u128 / 10000) as u64;
-        let treasury_fee = (total_pot as u128 * treasury_fee_bps as u128 / 10000) as u64;
-        let winner_amount = total_pot - platform_fee - treasury_fee;
-        
-        assert_eq!(platform_fee, 50_000_000);
-        assert_eq!(treasury_fee, 100_000_000);
-        assert_eq!(winner_amount, 850_000_000);
-        assert_eq!(platform_fee + treasury_fee + winner_amount, total_pot);
+    fn test_survived_shots_credit_the_shooter() {
+        let mut game = full_game(GameMode::TwoVsTwo);
+        for shot in 0..5 {
+            game.pull_trigger(0).unwrap();
+            assert!(!game.resolve_shot(draw(shot, false), 0).unwrap());
+        }
+
+        // A1, B1, A2, B2, A1 pulled the trigger and lived
+        assert_eq!(game.shots_survived[turn_order::seat(0, 0) as usize], 2);
+        assert_eq!(game.shots_survived[turn_order::seat(1, 0) as usize], 1);
+        assert_eq!(game.shots_survived[turn_order::seat(0, 1) as usize], 1);
+        assert_eq!(game.shots_survived[turn_order::seat(1, 1) as usize], 1);
     }
 
     #[test]
-    fn test_prize_distribution_2v2() {
-        let total_pot = 1_000_000_000u64;
-        let platform_fee_bps = 500u16;
-        let treasury_fee_bps = 1000u16;
-        let winner_count = 2u64;
-        
-        let platform_fee = (total_pot as u128 * platform_fee_bps as u128 / 10000) as u64;
-        let treasury_fee = (total_pot as u128 * treasury_fee_bps as u128 / 10000) as u64;
-        let winner_amount = total_pot - platform_fee - treasury_fee;
-        let per_winner = winner_amount / winner_count;
-        let dust = winner_amount % winner_count;
-        
-        assert_eq!(per_winner, 425_000_000);
-        assert_eq!(dust, 0);
-        assert_eq!(per_winner * winner_count + dust, winner_amount);
+    fn test_pulled_trigger_waits_for_its_randomness() {
+        let mut game = full_game(GameMode::OneVsOne);
+        game.turn_deadline = Some(60);
+
+        let seed = game.pull_trigger(40).unwrap();
+        assert_eq!(seed, chamber::shot_seed(&game.vrf_seed, 0));
+        assert!(game.vrf_pending);
+        assert_eq!(game.shots_taken, 0);
+        assert_eq!(game.status, GameStatus::InProgress);
+        // The deadline restarts and now bounds the wait for the callback
+        assert_eq!(game.turn_deadline, Some(100));
+
+        assert_eq!(game.pull_trigger(40).unwrap_err(), GameError::VrfRequestPending.into());
     }
 
     #[test]
-    fn test_prize_distribution_no_overflow() {
-        let total_pot = u64::MAX / 2;
-        let platform_fee_bps = 500u16;
-        
-        let result = (total_pot as u128)
-            .checked_mul(platform_fee_bps as u128)
-            .and_then(|v| v.checked_div(10000));
-        
-        assert!(result.is_some());
+    fn test_overdue_shot_can_be_retried() {
+        let fires = draw(0, true);
+        let mut game = full_game(GameMode::OneVsOne);
+        assert_eq!(game.retry_trigger(0).unwrap_err(), GameError::VrfNotReady.into());
+
+        let seed = game.pull_trigger(0).unwrap();
+        assert_eq!(game.retry_trigger(60).unwrap_err(), GameError::TurnNotExpired.into());
+
+        // Callback lost - the same shot is re-requested with a fresh deadline
+        assert_eq!(game.retry_trigger(61).unwrap(), seed);
+        assert!(game.vrf_pending);
+        assert_eq!(game.shots_taken, 0);
+        assert_eq!(game.turn_deadline, Some(121));
+        assert_eq!(game.retry_trigger(100).unwrap_err(), GameError::TurnNotExpired.into());
+
+        assert!(game.resolve_shot(fires, 130).unwrap());
+        assert_eq!(game.retry_trigger(500).unwrap_err(), GameError::VrfNotReady.into());
     }
 
-    // ========================================================================
-    // ARITHMETIC TESTS
-    // ========================================================================
+    #[test]
+    fn test_shot_needs_a_pulled_trigger() {
+        let mut game = full_game(GameMode::OneVsOne);
+        assert_eq!(game.resolve_shot([7; 32], 0).unwrap_err(), GameError::VrfNotReady.into());
+
+        game.vrf_fulfilled = false;
+        assert_eq!(game.pull_trigger(0).unwrap_err(), GameError::VrfNotReady.into());
+    }
 
     #[test]
-    fn test_checked_add_no_overflow() {
-        let a = 1_000_000_000u64;
-        let b = 1_000_000_000u64;
-        
-        let result = a.checked_add(b);
-        assert!(result.is_some());
-        assert_eq!(result.unwrap(), 2_000_000_000);
+    fn test_shot_is_decided_by_its_own_randomness_only() {
+        // Whatever the game's stored VRF output says, the fresh draw decides
+        let (fires, survives) = (draw(0, true), draw(0, false));
+        let mut game = full_game(GameMode::OneVsOne);
+        game.vrf_result = fires;
+        let shooter = game.get_current_player().unwrap();
+
+        game.pull_trigger(0).unwrap();
+        assert!(!game.resolve_shot(survives, 100).unwrap());
+        assert_eq!(game.vrf_result, survives);
+        assert!(!game.vrf_pending);
+        assert_eq!(game.shots_taken, 1);
+        assert_eq!(game.shots_survived[turn_order::seat(0, 0) as usize], 1);
+        assert_ne!(game.get_current_player().unwrap(), shooter);
+        assert_eq!(game.turn_deadline, Some(160));
     }
 
     #[test]
-    fn test_checked_add_overflow() {
-        let a = u64::MAX;
-        let b = 1u64;
-        
-        let result = a.checked_add(b);
-        assert!(result.is_none());
+    fn test_fired_shot_loses_the_game_for_the_shooters_team() {
+        let survives = draw(0, false);
+        let fires = draw(1, true);
+        let mut game = full_game(GameMode::OneVsOne);
+
+        game.pull_trigger(0).unwrap();
+        game.resolve_shot(survives, 0).unwrap();
+        // B1 shoots second and hits the bullet in chamber 2
+        game.pull_trigger(0).unwrap();
+        assert!(game.resolve_shot(fires, 50).unwrap());
+
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner_team, Some(0));
+        assert_eq!(game.bullet_chamber, 2);
+        assert_eq!(game.finished_at, Some(50));
+        assert_eq!(game.turn_deadline, None);
+        assert_eq!(game.pull_trigger(0).unwrap_err(), GameError::GameNotInProgress.into());
     }
 
     #[test]
-    fn test_checked_mul_no_overflow() {
-        let a = 1_000_000_000u64 as u128;
-        let b = 500u128;
-        
-        let result = a.checked_mul(b);
-        assert!(result.is_some());
+    fn test_game_transition_leaves_status_on_rejection() {
+        let mut game = full_game(GameMode::OneVsOne);
+        assert!(game.transition(GameStatus::Settled).is_err());
+        assert_eq!(game.status, GameStatus::InProgress);
+
+        game.transition(GameStatus::Finished).unwrap();
+        game.transition(GameStatus::Settled).unwrap();
+        assert_eq!(game.status, GameStatus::Settled);
     }
 
     #[test]
-    fn test_checked_mul_overflow() {
-        let a = u128::MAX;
-        let b = 2u128;
-        
-        let result = a.checked_mul(b);
-        assert!(result.is_none());
+    fn test_winners_are_the_winning_team() {
+        for mode in ALL_MODES {
+            let mut game = full_game(mode);
+            assert!(game.winners().is_err());
+
+            game.winner_team = Some(0);
+            assert_eq!(game.winners().unwrap(), game.team_a[..game.team_a_count as usize]);
+
+            game.winner_team = Some(1);
+            let winners = game.winners().unwrap();
+            assert_eq!(winners, game.team_b[..game.team_b_count as usize]);
+            assert!(!winners.contains(&Pubkey::default()));
+        }
     }
 
-    // ========================================================================
-    // PLAYER VALIDATION TESTS
-    // ========================================================================
+    #[test]
+    fn test_game_len_fits_every_optional_field() {
+        use anchor_lang::AnchorSerialize;
+
+        let mut game = full_game(GameMode::HumanVsAi);
+        game.mint = Some(Pubkey::new_unique());
+        game.loan_seats = u8::MAX;
+        game.turn_deadline = Some(i64::MAX);
+        game.winner_team = Some(1);
+        game.finished_at = Some(i64::MAX);
+
+        // 8-byte discriminator + Borsh body
+        assert_eq!(8 + game.try_to_vec().unwrap().len(), Game::LEN);
+    }
 
     #[test]
-    fn test_player_not_duplicate_in_team_a() {
-        let player = Pubkey::new_unique();
-        let team_a = [player, Pubkey::new_unique()];
-        
-        assert!(team_a.contains(&player));
+    fn test_loan_seats_track_each_borrower() {
+        let mut game = full_game(GameMode::TwoVsTwo);
+        assert!(!game.has_loan());
+        assert!(game.borrowers().is_empty());
+
+        // B1 and A2 borrowed, listed in seat order whatever order they joined in
+        let (a2, b1) = (game.team_a[1], game.team_b[0]);
+        game.record_loan(&b1).unwrap();
+        game.record_loan(&a2).unwrap();
+        assert!(game.has_loan());
+        assert_eq!(game.loan_seats, 0b0110);
+        assert_eq!(game.borrowers(), vec![a2, b1]);
+
+        assert_eq!(game.seat_of(&game.creator), Some(0));
+        assert_eq!(game.seat_of(&game.team_b[1]), Some(3));
+        assert!(game.record_loan(&Pubkey::new_unique()).is_err());
     }
 
     #[test]
-    fn test_player_not_duplicate_in_team_b() {
-        let player = Pubkey::new_unique();
-        let team_b = [player, Pubkey::new_unique()];
-        
-        assert!(team_b.contains(&player));
+    fn test_joiners_fill_seats_in_turn_order() {
+        let mut game = full_game(GameMode::TwoVsTwo);
+        let joiners = [game.team_b[0], game.team_a[1], game.team_b[1]];
+        game.team_a = [game.creator, Pubkey::default()];
+        game.team_b = [Pubkey::default(); 2];
+        game.team_a_count = 1;
+        game.team_b_count = 0;
+
+        for (joiner, seat) in joiners.iter().zip([2, 1, 3]) {
+            game.add_joiner(*joiner).unwrap();
+            assert_eq!(game.seat_of(joiner), Some(seat));
+        }
+        assert!(game.is_full());
+
+        let mut ai_game = full_game(GameMode::HumanVsAi);
+        assert!(ai_game.add_joiner(Pubkey::new_unique()).is_err());
     }
 
     #[test]
-    fn test_player_not_in_both_teams() {
-        let player = Pubkey::new_unique();
-        let other = Pubkey::new_unique();
-        let team_a = [player, other];
-        let team_b = [Pubkey::new_unique(), Pubkey::new_unique()];
-        
-        assert!(team_a.contains(&player));
-        assert!(!team_b.contains(&player));
+    fn test_next_turn_wraps() {
+        for len in 1..=turn_order::MAX_SEATS as u8 {
+            for turn in 0..len {
+                let next = turn_order::next_turn(turn, len);
+                assert!(next < len);
+                assert_eq!(next, (turn + 1) % len);
+            }
+        }
     }
+}
 
-    // ========================================================================
-    // VRF TESTS
-    // ========================================================================
+// ========================================================================
+// GAME LIFECYCLE TESTS
+// ========================================================================
+
+#[cfg(test)]
+mod lifecycle_tests {
+    use magic_roulette::state::lifecycle::{can_transition, is_terminal, transition, ALL_STATUSES};
+    use magic_roulette::state::GameStatus::{self, *};
+
+    const LEGAL: [(GameStatus, GameStatus); 9] = [
+        (WaitingForPlayers, Ready),
+        (Ready, Delegated),
+        (Delegated, AwaitingRandomness),
+        (AwaitingRandomness, InProgress),
+        (InProgress, Finished),
+        (Finished, Settled),
+        (WaitingForPlayers, Refunded),
+        (WaitingForPlayers, Expired),
+        (Ready, Expired),
+    ];
 
     #[test]
-    fn test_vrf_randomness_to_chamber() {
-        let randomness = [42u8; 32];
-        let random_u64 = u64::from_le_bytes([
-            randomness[0], randomness[1], randomness[2], randomness[3],
-            randomness[4], randomness[5], randomness[6], randomness[7],
-        ]);
-        let chamber = ((random_u64 % 6) + 1) as u8;
-        
-        assert!(chamber >= 1 && chamber <= 6);
+    fn test_only_listed_transitions_are_legal() {
+        // Test: Every one of the 81 status pairs, legal or not
+        for from in ALL_STATUSES {
+            for to in ALL_STATUSES {
+                let legal = LEGAL.contains(&(from, to));
+                assert_eq!(can_transition(from, to), legal, "{:?} -> {:?}", from, to);
+                assert_eq!(transition(from, to).is_ok(), legal, "{:?} -> {:?}", from, to);
+            }
+        }
     }
 
     #[test]
-    fn test_vrf_randomness_distribution() {
-        for i in 0..256 {
-            let randomness = [i as u8; 32];
-            let random_u64 = u64::from_le_bytes([
-                randomness[0], randomness[1], randomness[2], randomness[3],
-                randomness[4], randomness[5], randomness[6], randomness[7],
-            ]);
-            let chamber = ((random_u64 % 6) + 1) as u8;
-            
-            assert!(chamber >= 1 && chamber <= 6);
+    fn test_happy_path_reaches_settled() {
+        let path = [WaitingForPlayers, Ready, Delegated, AwaitingRandomness, InProgress, Finished, Settled];
+        let mut status = path[0];
+        for next in &path[1..] {
+            status = transition(status, *next).unwrap();
         }
+        assert_eq!(status, Settled);
     }
 
-    // ========================================================================
-    // GAME LOGIC TESTS
-    // ========================================================================
-
     #[test]
-    fn test_bullet_hit_detection() {
-        let bullet_chamber = 3u8;
-        let current_chamber = 3u8;
-        
-        assert_eq!(bullet_chamber, current_chamber);
+    fn test_terminal_statuses_have_no_way_out() {
+        for from in ALL_STATUSES.into_iter().filter(|status| is_terminal(*status)) {
+            for to in ALL_STATUSES {
+                assert!(transition(from, to).is_err(), "{:?} -> {:?}", from, to);
+            }
+        }
+        assert_eq!(ALL_STATUSES.iter().filter(|status| is_terminal(**status)).count(), 3);
     }
 
     #[test]
-    fn test_bullet_miss_detection() {
-        let bullet_chamber = 3u8;
-        let current_chamber = 1u8;
-        
-        assert_ne!(bullet_chamber, current_chamber);
+    fn test_no_status_is_skipped_or_repeated() {
+        // Test: A status never moves to itself, and the game cannot jump
+        // past delegation or randomness
+        for status in ALL_STATUSES {
+            assert!(transition(status, status).is_err());
+        }
+        assert!(transition(WaitingForPlayers, Delegated).is_err());
+        assert!(transition(Ready, InProgress).is_err());
+        assert!(transition(Delegated, InProgress).is_err());
+        assert!(transition(InProgress, Settled).is_err());
+        assert!(transition(Finished, Refunded).is_err());
+        assert!(transition(InProgress, Expired).is_err());
     }
 
     #[test]
-    fn test_chamber_wraparound() {
-        let mut current_chamber = 6u8;
-        current_chamber += 1;
-        if current_chamber > 6 {
-            current_chamber = 1;
+    fn test_every_status_is_reachable() {
+        let mut reached = vec![WaitingForPlayers];
+        let mut index = 0;
+        while index < reached.len() {
+            for to in ALL_STATUSES {
+                if can_transition(reached[index], to) && !reached.contains(&to) {
+                    reached.push(to);
+                }
+            }
+            index += 1;
         }
-        
-        assert_eq!(current_chamber, 1);
+        assert_eq!(reached.len(), ALL_STATUSES.len());
     }
 
-    // ========================================================================
-    // PLATFORM CONFIG TESTS
-    // ========================================================================
+}
+
+// ========================================================================
+// CANCEL & REFUND TESTS
+// ========================================================================
+
+#[cfg(test)]
+mod cancel_tests {
+    use anchor_lang::prelude::Pubkey;
+    use magic_roulette::constants::GAME_EXPIRY_SECONDS;
+    use magic_roulette::errors::GameError;
+    use magic_roulette::state::{Game, GameMode, GameStatus};
+
+    const ENTRY_FEE: u64 = 100_000_000;
+    const CREATED_AT: i64 = 1_700_000_000;
+    const EXPIRED_AT: i64 = CREATED_AT + GAME_EXPIRY_SECONDS;
+
+    /// Waiting game with the creator and `joiners` more players paid in
+    fn waiting_game(game_mode: GameMode, joiners: usize) -> Game {
+        let creator = Pubkey::new_unique();
+        let mut game = Game {
+            creator,
+            game_mode,
+            entry_fee: ENTRY_FEE,
+            total_pot: ENTRY_FEE,
+            created_at: CREATED_AT,
+            ..Default::default()
+        };
+        game.team_a[0] = creator;
+        game.team_a_count = 1;
+        for _ in 0..joiners {
+            game.add_joiner(Pubkey::new_unique()).unwrap();
+            game.total_pot += ENTRY_FEE;
+        }
+        game
+    }
 
     #[test]
-    fn test_platform_fee_valid() {
-        let platform_fee_bps = 500u16;
-        let treasury_fee_bps = 1000u16;
-        let total = platform_fee_bps + treasury_fee_bps;
-        
-        assert!(total <= 10000);
+    fn test_refunds_go_to_each_entrant() {
+        let game = waiting_game(GameMode::TwoVsTwo, 2);
+        let refunds = game.refunds();
+
+        let entrants: Vec<Pubkey> = refunds.iter().map(|(entrant, _)| *entrant).collect();
+        assert_eq!(entrants, game.entrants());
+        assert!(refunds.iter().all(|(_, refund)| *refund == ENTRY_FEE));
     }
 
     #[test]
-    fn test_platform_fee_max() {
-        let platform_fee_bps = 5000u16;
-        let treasury_fee_bps = 5000u16;
-        let total = platform_fee_bps + treasury_fee_bps;
-        
-        assert_eq!(total, 10000);
+    fn test_refund_remainder_goes_to_the_creator() {
+        let mut game = waiting_game(GameMode::TwoVsTwo, 2);
+        game.total_pot += 2;
+        let refunds = game.refunds();
+
+        assert_eq!(refunds[0], (game.creator, ENTRY_FEE + 2));
+        assert!(refunds[1..].iter().all(|(_, refund)| *refund == ENTRY_FEE));
+        assert_eq!(refunds.iter().map(|(_, refund)| refund).sum::<u64>(), game.total_pot);
     }
 
     #[test]
-    fn test_platform_fee_overflow() {
-        let platform_fee_bps = 10000u16;
-        let treasury_fee_bps = 1u16;
-        let total = platform_fee_bps.checked_add(treasury_fee_bps);
-        
-        assert!(total.is_some());
-        assert!(total.unwrap() > 10000);
+    fn test_lone_creator_gets_the_whole_pot_back() {
+        let game = waiting_game(GameMode::OneVsOne, 0);
+        assert_eq!(game.refunds(), vec![(game.creator, ENTRY_FEE)]);
     }
 
-    // ========================================================================
-    // EDGE CASES
-    // ========================================================================
+    #[test]
+    fn test_creator_cancels_waiting_game() {
+        let mut game = waiting_game(GameMode::TwoVsTwo, 1);
+        let creator = game.creator;
+
+        game.cancel(&creator, CREATED_AT + 60).unwrap();
+        assert_eq!(game.status, GameStatus::Refunded);
+        assert_eq!(game.finished_at, Some(CREATED_AT + 60));
+    }
 
     #[test]
-    fn test_single_lamport_fee() {
-        let total_pot = 1u64;
-        let fee_bps = 1u16;
-        
-        let fee = (total_pot as u128 * fee_bps as u128 / 10000) as u64;
-        assert_eq!(fee, 0);
+    fn test_only_creator_can_cancel_while_waiting() {
+        let mut game = waiting_game(GameMode::TwoVsTwo, 1);
+        let creator = game.creator;
+        let joiner = game.team_b[0];
+
+        for caller in [joiner, Pubkey::new_unique()] {
+            let mut attempt = game.clone();
+            assert_eq!(
+                attempt.cancel(&caller, EXPIRED_AT - 1).unwrap_err(),
+                GameError::GameNotExpired.into()
+            );
+        }
+        game.cancel(&creator, EXPIRED_AT - 1).unwrap();
+        assert_eq!(game.status, GameStatus::Refunded);
     }
 
     #[test]
-    fn test_max_u64_fee_calculation() {
-        let total_pot = u64::MAX / 2;
-        let fee_bps = 500u16;
-        
-        let result = (total_pot as u128)
-            .checked_mul(fee_bps as u128)
-            .and_then(|v| v.checked_div(10000));
-        
-        assert!(result.is_some());
+    fn test_anyone_can_cancel_an_expired_game() {
+        let mut game = waiting_game(GameMode::TwoVsTwo, 1);
+
+        game.cancel(&Pubkey::new_unique(), EXPIRED_AT).unwrap();
+        assert_eq!(game.status, GameStatus::Expired);
+        assert_eq!(game.finished_at, Some(EXPIRED_AT));
     }
 
     #[test]
-    fn test_precision_loss_minimal() {
-        let total_pot = 999_999_999u64;
-        let fee_bps = 500u16;
-        
-        let fee = (total_pot as u128 * fee_bps as u128 / 10000) as u64;
-        let remainder = total_pot - fee;
-        
-        // Verify no precision loss
-        assert!(remainder > 0);
+    fn test_creator_cannot_cancel_a_full_game() {
+        let mut game = waiting_game(GameMode::OneVsOne, 1);
+        let creator = game.creator;
+
+        assert_eq!(
+            game.cancel(&creator, CREATED_AT).unwrap_err(),
+            GameError::GameFull.into()
+        );
     }
-}
 
-use anchor_lang::prelude::*;
+    #[test]
+    fn test_cancelled_game_cannot_be_cancelled_again() {
+        for now in [CREATED_AT, EXPIRED_AT] {
+            let mut game = waiting_game(GameMode::TwoVsTwo, 1);
+            let creator = game.creator;
+            game.cancel(&creator, now).unwrap();
 
-#[derive(Default)]
-pub struct Game {
-    pub game_mode: GameMode,
-    pub team_a_count: u8,
-    pub team_b_count: u8,
-    pub entry_fee: u64,
-    pub status: GameStatus,
-}
+            for caller in [creator, Pubkey::new_unique()] {
+                assert_eq!(
+                    game.cancel(&caller, EXPIRED_AT).unwrap_err(),
+                    GameError::InvalidGameStatus.into()
+                );
+            }
+        }
+    }
 
-#[derive(PartialEq, Eq, Default)]
-pub enum GameMode {
-    #[default]
-    OneVsOne,
-    TwoVsTwo,
-    HumanVsAi,
-}
+    #[test]
+    fn test_started_game_cannot_be_cancelled() {
+        let mut game = waiting_game(GameMode::OneVsOne, 1);
+        game.status = GameStatus::InProgress;
+        let creator = game.creator;
 
-#[derive(PartialEq, Eq, Default)]
-pub enum GameStatus {
-    #[default]
-    WaitingForPlayers,
-    Delegated,
-    InProgress,
-    Finished,
-    Cancelled,
+        assert_eq!(
+            game.cancel(&creator, EXPIRED_AT).unwrap_err(),
+            GameError::InvalidGameStatus.into()
+        );
+        assert_eq!(game.finished_at, None);
+    }
 }
 
-impl Game {
-    pub fn is_full(&self) -> bool {
-        match self.game_mode {
-            GameMode::OneVsOne => self.team_a_count == 1 && self.team_b_count == 1,
-            GameMode::TwoVsTwo => self.team_a_count == 2 && self.team_b_count == 2,
-            GameMode::HumanVsAi => self.team_a_count == 1,
+// ========================================================================
+// CHAMBER RESOLUTION TESTS
+// ========================================================================
+
+#[cfg(test)]
+mod chamber_tests {
+    use magic_roulette::state::chamber;
+
+    fn vrf_bytes(i: u32) -> [u8; 32] {
+        let mut randomness = [0u8; 32];
+        randomness[..4].copy_from_slice(&i.to_le_bytes());
+        randomness
+    }
+
+    #[test]
+    fn test_last_chamber_always_fires() {
+        for i in 0..256 {
+            assert!(chamber::shot_fires(&vrf_bytes(i), chamber::CHAMBERS - 1));
+        }
+    }
+
+    // Helper: Chamber (1-6) that fires when every shot draws fresh randomness
+    fn firing_chamber(game: u32) -> u8 {
+        (0..chamber::CHAMBERS)
+            .find(|&shot| chamber::shot_fires(&vrf_bytes(game * 8 + shot as u32), shot))
+            .map_or(chamber::CHAMBERS, |shot| shot + 1)
+    }
+
+    #[test]
+    fn test_every_shot_requests_distinct_randomness() {
+        let seed = vrf_bytes(42);
+        let seeds: Vec<[u8; 32]> = (0..chamber::CHAMBERS).map(|shot| chamber::shot_seed(&seed, shot)).collect();
+        for (i, a) in seeds.iter().enumerate() {
+            assert!(seeds[i + 1..].iter().all(|b| a != b));
+            assert_ne!(*a, seed);
+        }
+    }
+
+    #[test]
+    fn test_firing_chamber_distribution_roughly_uniform() {
+        let samples = 6_000u32;
+        let mut counts = [0u32; 6];
+        for i in 0..samples {
+            counts[(firing_chamber(i) - 1) as usize] += 1;
+        }
+
+        // Each chamber should land within 20% of the expected 1/6 share
+        for count in counts {
+            assert!(count > 800 && count < 1200, "skewed distribution: {:?}", counts);
         }
     }
+}
 
-    pub fn get_required_players(&self) -> u8 {
-        match self.game_mode {
-            GameMode::OneVsOne => 2,
-            GameMode::TwoVsTwo => 4,
-            GameMode::HumanVsAi => 1,
+// ========================================================================
+// PLAYER STATS TESTS
+// ========================================================================
+
+#[cfg(test)]
+mod player_stats_tests {
+    use anchor_lang::prelude::Pubkey;
+    use magic_roulette::state::PlayerStats;
+
+    fn empty_stats() -> PlayerStats {
+        PlayerStats {
+            player: Pubkey::default(),
+            games_played: 0,
+            games_won: 0,
+            total_wagered: 0,
+            total_winnings: 0,
+            shots_survived: 0,
+            bump: 0,
         }
     }
+
+    #[test]
+    fn test_ensure_initialized_binds_once() {
+        let first = Pubkey::new_unique();
+        let mut stats = empty_stats();
+        stats.ensure_initialized(first, 254);
+        stats.ensure_initialized(Pubkey::new_unique(), 1);
+
+        assert_eq!(stats.player, first);
+        assert_eq!(stats.bump, 254);
+    }
+
+    #[test]
+    fn test_record_game_accumulates_wins_and_losses() {
+        let mut stats = empty_stats();
+        stats.record_game(100, Some(170), 2).unwrap();
+        stats.record_game(100, None, 1).unwrap();
+
+        assert_eq!(stats.games_played, 2);
+        assert_eq!(stats.games_won, 1);
+        assert_eq!(stats.total_wagered, 200);
+        assert_eq!(stats.total_winnings, 170);
+        assert_eq!(stats.shots_survived, 3);
+    }
+
+    #[test]
+    fn test_record_game_rejects_overflow() {
+        let mut stats = empty_stats();
+        stats.total_wagered = u64::MAX;
+        assert!(stats.record_game(1, None, 0).is_err());
+    }
+}
+
+// ========================================================================
+// TREASURY REWARDS TESTS
+// ========================================================================
+
+#[cfg(test)]
+mod treasury_rewards_tests {
+    use anchor_lang::prelude::Pubkey;
+    use magic_roulette::state::TreasuryRewards;
+
+    #[test]
+    fn test_reward_pool_is_share_of_treasury_fee() {
+        assert_eq!(TreasuryRewards::reward_pool(100_000_000, 2000).unwrap(), 20_000_000);
+        assert_eq!(TreasuryRewards::reward_pool(100_000_000, 0).unwrap(), 0);
+        assert_eq!(TreasuryRewards::reward_pool(100_000_000, 10000).unwrap(), 100_000_000);
+        assert_eq!(TreasuryRewards::reward_pool(u64::MAX, 10000).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn test_wager_share_never_exceeds_pool() {
+        let pool = 1_000_003u64;
+        let wagers = [100u64, 100, 100, 100];
+        let total: u64 = wagers.iter().sum();
+
+        let credited: u64 = wagers
+            .iter()
+            .map(|w| TreasuryRewards::wager_share(pool, *w, total).unwrap())
+            .sum();
+        assert!(credited <= pool);
+        assert!(pool - credited < wagers.len() as u64);
+    }
+
+    #[test]
+    fn test_reward_shares_leave_dust_out_of_the_total() {
+        use magic_roulette::instructions::treasury_reward_shares;
+        use magic_roulette::state::{Game, GameMode};
+
+        let game = Game {
+            game_mode: GameMode::TwoVsTwo,
+            entry_fee: 100,
+            team_a: [Pubkey::new_unique(), Pubkey::new_unique()],
+            team_b: [Pubkey::new_unique(), Pubkey::new_unique()],
+            team_a_count: 2,
+            team_b_count: 2,
+            ..Default::default()
+        };
+        assert_eq!(treasury_reward_shares(&game, 1_000_003).unwrap(), (250_000, 1_000_000));
+        // A transfer fee shrinks what arrives, and every share with it
+        assert_eq!(treasury_reward_shares(&game, 990_003).unwrap(), (247_500, 990_000));
+        assert_eq!(treasury_reward_shares(&game, 3).unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn test_wager_share_weights_by_wager() {
+        assert_eq!(TreasuryRewards::wager_share(900, 200, 300).unwrap(), 600);
+        assert_eq!(TreasuryRewards::wager_share(900, 100, 300).unwrap(), 300);
+        assert_eq!(TreasuryRewards::wager_share(900, 0, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_credit_accumulates_claimable() {
+        let mut rewards = TreasuryRewards {
+            player: Pubkey::default(),
+            claimable_amount: 0,
+            total_claimed: 0,
+            last_claim: 0,
+            bump: 0,
+            claimable_lamports: 0,
+            total_claimed_lamports: 0,
+        };
+        let player = Pubkey::new_unique();
+        rewards.ensure_initialized(player, 253);
+        rewards.credit(40).unwrap();
+        rewards.credit(2).unwrap();
+
+        assert_eq!(rewards.player, player);
+        assert_eq!(rewards.claimable_amount, 42);
+        assert!(rewards.credit(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_claim_zeroes_both_assets_before_paying() {
+        // Test: SOL and platform-mint rewards are kept apart, and a claim
+        // books everything claimable before any transfer happens
+        let mut rewards = TreasuryRewards {
+            player: Pubkey::new_unique(),
+            claimable_amount: 0,
+            total_claimed: 0,
+            last_claim: 0,
+            bump: 0,
+            claimable_lamports: 0,
+            total_claimed_lamports: 0,
+        };
+        assert!(rewards.take_claimable(1).is_err());
+
+        rewards.credit(7).unwrap();
+        rewards.credit_lamports(5_000).unwrap();
+        assert_eq!(rewards.take_claimable(100).unwrap(), (7, 5_000));
+        assert_eq!(rewards.claimable_amount, 0);
+        assert_eq!(rewards.claimable_lamports, 0);
+        assert_eq!(rewards.total_claimed, 7);
+        assert_eq!(rewards.total_claimed_lamports, 5_000);
+        assert_eq!(rewards.last_claim, 100);
+
+        // Nothing left, so a second claim is rejected
+        assert!(rewards.take_claimable(101).is_err());
+
+        rewards.credit_lamports(1).unwrap();
+        assert_eq!(rewards.take_claimable(102).unwrap(), (0, 1));
+        assert_eq!(rewards.total_claimed_lamports, 5_001);
+    }
+}
+
+// ========================================================================
+// FEE SPLIT TESTS
+// ========================================================================
+
+#[cfg(test)]
+mod fee_split_tests {
+    use magic_roulette::settlement::{bps_of, FeeSplit};
+
+    #[test]
+    fn test_bps_of_rounds_down() {
+        assert_eq!(bps_of(1_000_000_000, 500).unwrap(), 50_000_000);
+        assert_eq!(bps_of(199, 500).unwrap(), 9);
+        assert_eq!(bps_of(u64::MAX, 10000).unwrap(), u64::MAX);
+        assert_eq!(bps_of(u64::MAX, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_split_accounts_for_whole_pot() {
+        for total_pot in [0, 1, 3, 199, 10_000_000, 1_000_000_007, u64::MAX] {
+            for (platform_bps, treasury_bps) in [(0, 0), (500, 1000), (1000, 1000), (333, 777), (5000, 5000)] {
+                let split = FeeSplit::new(total_pot, platform_bps, treasury_bps).unwrap();
+                assert_eq!(
+                    split.platform_fee as u128 + split.treasury_fee as u128 + split.winner_amount as u128,
+                    total_pot as u128
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_matches_platform_defaults() {
+        // 0.2 SOL pot, 5% platform and 10% treasury
+        let split = FeeSplit::new(200_000_000, 500, 1000).unwrap();
+        assert_eq!(split.platform_fee, 10_000_000);
+        assert_eq!(split.treasury_fee, 20_000_000);
+        assert_eq!(split.winner_amount, 170_000_000);
+        assert_eq!(split.per_winner(1).unwrap(), 170_000_000);
+        assert_eq!(split.per_winner(2).unwrap(), 85_000_000);
+    }
+
+    #[test]
+    fn test_per_winner_never_exceeds_winner_amount() {
+        let split = FeeSplit::new(1_000_000_007, 500, 1000).unwrap();
+        for winners in 1..=2usize {
+            assert!(split.per_winner(winners).unwrap() * winners as u64 <= split.winner_amount);
+        }
+        assert!(split.per_winner(0).is_err());
+    }
+
+    #[test]
+    fn test_fees_above_whole_pot_are_rejected() {
+        assert!(FeeSplit::new(1_000_000, 6000, 6000).is_err());
+    }
+}
+
+// ========================================================================
+// SETTLEMENT PLAN PROPERTY TESTS
+// ========================================================================
+
+#[cfg(test)]
+mod settlement_tests {
+    use magic_roulette::settlement::PayoutPlan;
+    use proptest::prelude::*;
+
+    // Helper: Everything the plan pays out, plus what it leaves behind
+    fn plan_total(plan: &PayoutPlan) -> u128 {
+        plan.platform_fee as u128
+            + plan.treasury_fee as u128
+            + plan.per_winner as u128 * plan.winner_count as u128
+            + plan.dust as u128
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10_000))]
+
+        #[test]
+        fn test_plan_sums_to_total_pot(
+            total_pot: u64,
+            platform_bps in 0u16..=2500,
+            treasury_bps in 0u16..=2500,
+            winner_count in 1usize..=2,
+            winner in 0usize..2,
+            repayment_share: u64,
+            rewards_share: u64,
+        ) {
+            let winner = winner % winner_count;
+            let mut plan = PayoutPlan::new(total_pot, platform_bps, treasury_bps, winner_count).unwrap();
+            prop_assert_eq!(plan_total(&plan), total_pot as u128);
+
+            let repayment = repayment_share % plan.per_winner.saturating_add(1);
+            plan = plan.with_loan_repayment(winner, repayment).unwrap();
+            prop_assert_eq!(plan_total(&plan), total_pot as u128);
+            prop_assert_eq!(plan.winner_payout(winner) + repayment, plan.per_winner);
+
+            let rewards = rewards_share % plan.treasury_fee.saturating_add(1);
+            plan = plan.with_player_rewards(rewards).unwrap();
+            prop_assert_eq!(plan_total(&plan), total_pot as u128);
+            prop_assert_eq!(plan.treasury_payout() + plan.player_rewards, plan.treasury_fee + plan.dust);
+            prop_assert!(plan.dust < plan.winner_count as u64);
+        }
+
+        #[test]
+        fn test_plan_pays_out_every_unit(
+            total_pot: u64,
+            platform_bps in 0u16..=2500,
+            treasury_bps in 0u16..=2500,
+            winner_count in 1usize..=2,
+            rewards_share: u64,
+        ) {
+            // Nothing is stranded in the vault once the plan is executed
+            let plan = PayoutPlan::new(total_pot, platform_bps, treasury_bps, winner_count).unwrap();
+            let rewards = rewards_share % plan.treasury_fee.saturating_add(1);
+            let plan = plan.with_player_rewards(rewards).unwrap();
+
+            let paid_out = plan.platform_fee as u128
+                + plan.treasury_payout() as u128
+                + plan.player_rewards as u128
+                + plan.per_winner as u128 * plan.winner_count as u128;
+            prop_assert_eq!(paid_out, total_pot as u128);
+        }
+    }
+
+    #[test]
+    fn test_split_remainder_goes_to_treasury() {
+        // 2v2 pot with an odd winner share: 170_000_001 split two ways
+        let plan = PayoutPlan::new(200_000_001, 500, 1000, 2).unwrap();
+        assert_eq!(plan.per_winner, 85_000_000);
+        assert_eq!(plan.dust, 1);
+        assert_eq!(plan.treasury_fee, 20_000_000);
+        assert_eq!(plan.treasury_payout(), 20_000_001);
+    }
+
+    #[test]
+    fn test_plan_matches_platform_defaults() {
+        // 2v2 with 0.1 SOL entry: 5% platform, 10% treasury
+        let plan = PayoutPlan::new(400_000_000, 500, 1000, 2).unwrap();
+        assert_eq!(plan.platform_fee, 20_000_000);
+        assert_eq!(plan.treasury_fee, 40_000_000);
+        assert_eq!(plan.per_winner, 170_000_000);
+        assert_eq!(plan.dust, 0);
+    }
+
+    #[test]
+    fn test_loan_repayment_comes_out_of_winnings() {
+        let plan = PayoutPlan::new(200_000_000, 500, 1000, 1).unwrap();
+        let repaid = plan.with_loan_repayment(0, 101_000_000).unwrap();
+        assert_eq!(repaid.loan_repayment, 101_000_000);
+        assert_eq!(repaid.winner_payout(0), 69_000_000);
+        assert_eq!(repaid.platform_fee, plan.platform_fee);
+        assert_eq!(repaid.treasury_fee, plan.treasury_fee);
+
+        assert!(plan.with_loan_repayment(0, 170_000_001).is_err());
+    }
+
+    #[test]
+    fn test_loan_repayment_comes_out_of_the_borrowers_share() {
+        // 2v2 where only the second winner borrowed
+        let plan = PayoutPlan::new(400_000_000, 500, 1000, 2).unwrap();
+        let repaid = plan.with_loan_repayment(1, 101_000_000).unwrap();
+        assert_eq!(repaid.winner_payout(0), 170_000_000);
+        assert_eq!(repaid.winner_payout(1), 69_000_000);
+
+        // Both borrowed: each repays their own loan, and only from their share
+        let repaid = repaid.with_loan_repayment(0, 170_000_000).unwrap();
+        assert_eq!(repaid.loan_repayment, 271_000_000);
+        assert_eq!(repaid.winner_payout(0), 0);
+        assert_eq!(
+            repaid.with_loan_repayment(1, 69_000_001).unwrap_err(),
+            magic_roulette::errors::GameError::InsufficientWinningsForRepayment.into()
+        );
+        assert!(plan.with_loan_repayment(2, 1).is_err());
+    }
+
+    #[test]
+    fn test_player_rewards_never_exceed_treasury_fee() {
+        let plan = PayoutPlan::new(200_000_000, 500, 1000, 1).unwrap();
+        assert!(plan.with_player_rewards(plan.treasury_fee).is_ok());
+        assert!(plan.with_player_rewards(plan.treasury_fee + 1).is_err());
+    }
+
+    #[test]
+    fn test_plan_needs_a_winner() {
+        assert!(PayoutPlan::new(200_000_000, 500, 1000, 0).is_err());
+        assert!(PayoutPlan::new(200_000_000, 500, 1000, 3).is_err());
+    }
+}
+
+// ========================================================================
+// KAMINO OBLIGATION DEBT TESTS
+// ========================================================================
+
+#[cfg(test)]
+mod kamino_debt_tests {
+    use anchor_lang::prelude::{AccountInfo, Pubkey};
+    use magic_roulette::constants::KAMINO_PROGRAM_ID;
+    use magic_roulette::errors::GameError;
+    use anchor_spl::token::spl_token::native_mint;
+    use magic_roulette::kamino::{
+        accrued_debt, loan_debt, obligation_collateral, obligation_debt, obligation_layout, reserve_layout, ObligationView, ReserveKeys,
+        FRACTION_BITS, OBLIGATION_DISCRIMINATOR, RESERVE_DISCRIMINATOR,
+    };
+    use magic_roulette::settlement::PayoutPlan;
+    use magic_roulette::state::{LoanPosition, LoanStatus};
+    use solana_sha256_hasher::hashv;
+
+    const ONE: u128 = 1 << FRACTION_BITS;
+
+    // Helper: Mock Kamino lending market with one reserve and one obligation,
+    // laid out byte-for-byte like klend's accounts
+    struct MockKamino {
+        lending_market: Pubkey,
+        reserve: Pubkey,
+        obligation: Pubkey,
+        reserve_data: Vec<u8>,
+        obligation_data: Vec<u8>,
+    }
+
+    impl MockKamino {
+        fn new() -> Self {
+            let lending_market = Pubkey::new_unique();
+            let mut reserve_data = vec![0u8; 8624];
+            reserve_data[..8].copy_from_slice(&RESERVE_DISCRIMINATOR);
+            reserve_data[reserve_layout::LENDING_MARKET..][..32].copy_from_slice(lending_market.as_ref());
+            let mut obligation_data = vec![0u8; 3344];
+            obligation_data[..8].copy_from_slice(&OBLIGATION_DISCRIMINATOR);
+            obligation_data[obligation_layout::LENDING_MARKET..][..32].copy_from_slice(lending_market.as_ref());
+
+            let mut kamino = Self {
+                lending_market,
+                reserve: Pubkey::new_unique(),
+                obligation: Pubkey::new_unique(),
+                reserve_data,
+                obligation_data,
+            };
+            kamino.set_reserve_rate(ONE);
+            kamino
+        }
+
+        fn set_reserve_rate(&mut self, rate_bsf: u128) {
+            self.reserve_data[reserve_layout::CUMULATIVE_BORROW_RATE..][..16].copy_from_slice(&rate_bsf.to_le_bytes());
+        }
+
+        // Borrow `amount_sf` from the mock reserve into borrow slot `slot`,
+        // as klend records it when the obligation is refreshed at `rate_bsf`
+        fn borrow(&mut self, slot: usize, amount_sf: u128, rate_bsf: u128) {
+            let offset = obligation_layout::BORROWS + slot * obligation_layout::BORROW_LEN;
+            let borrow = &mut self.obligation_data[offset..offset + obligation_layout::BORROW_LEN];
+            borrow[obligation_layout::BORROW_RESERVE..][..32].copy_from_slice(self.reserve.as_ref());
+            borrow[obligation_layout::BORROW_CUMULATIVE_RATE..][..16].copy_from_slice(&rate_bsf.to_le_bytes());
+            borrow[obligation_layout::BORROWED_AMOUNT_SF..][..16].copy_from_slice(&amount_sf.to_le_bytes());
+        }
+
+        // Pledge `ctokens` of `reserve` in deposit slot `slot`
+        fn deposit(&mut self, slot: usize, reserve: &Pubkey, ctokens: u64) {
+            let offset = obligation_layout::DEPOSITS + slot * obligation_layout::DEPOSIT_LEN;
+            let deposit = &mut self.obligation_data[offset..offset + obligation_layout::DEPOSIT_LEN];
+            deposit[obligation_layout::DEPOSIT_RESERVE..][..32].copy_from_slice(reserve.as_ref());
+            deposit[obligation_layout::DEPOSITED_AMOUNT..][..8].copy_from_slice(&ctokens.to_le_bytes());
+        }
+
+        fn collateral(&mut self, reserve: &Pubkey, owner: Pubkey) -> anchor_lang::Result<u64> {
+            let mut lamports = 0u64;
+            let obligation = AccountInfo::new(
+                &self.obligation, false, true, &mut lamports,
+                &mut self.obligation_data, &owner, false, 0,
+            );
+            obligation_collateral(&obligation, reserve, &self.lending_market)
+        }
+
+        fn debt(&mut self) -> anchor_lang::Result<u64> {
+            self.debt_owned_by(KAMINO_PROGRAM_ID)
+        }
+
+        fn debt_owned_by(&mut self, owner: Pubkey) -> anchor_lang::Result<u64> {
+            let (mut obligation_lamports, mut reserve_lamports) = (0u64, 0u64);
+            let obligation = AccountInfo::new(
+                &self.obligation, false, true, &mut obligation_lamports,
+                &mut self.obligation_data, &owner, false, 0,
+            );
+            let reserve = AccountInfo::new(
+                &self.reserve, false, true, &mut reserve_lamports,
+                &mut self.reserve_data, &owner, false, 0,
+            );
+            obligation_debt(&obligation, &reserve, &self.lending_market)
+        }
+
+        // What a loan of `principal`, borrowed at `rate_bsf`, owes out of the obligation's debt
+        fn loan_debt(&mut self, principal: u64, rate_bsf: u128) -> anchor_lang::Result<u64> {
+            let (mut obligation_lamports, mut reserve_lamports) = (0u64, 0u64);
+            let obligation = AccountInfo::new(
+                &self.obligation, false, true, &mut obligation_lamports,
+                &mut self.obligation_data, &KAMINO_PROGRAM_ID, false, 0,
+            );
+            let reserve = AccountInfo::new(
+                &self.reserve, false, true, &mut reserve_lamports,
+                &mut self.reserve_data, &KAMINO_PROGRAM_ID, false, 0,
+            );
+            loan_debt(&obligation, &reserve, &self.lending_market, principal, rate_bsf).map(|(debt, _)| debt)
+        }
+    }
+
+    #[test]
+    fn test_discriminators_match_klend_account_names() {
+        assert_eq!(hashv(&[b"account:Obligation"]).to_bytes()[..8], OBLIGATION_DISCRIMINATOR);
+        assert_eq!(hashv(&[b"account:Reserve"]).to_bytes()[..8], RESERVE_DISCRIMINATOR);
+    }
+
+    #[test]
+    fn test_fresh_borrow_owes_principal() {
+        let mut kamino = MockKamino::new();
+        kamino.borrow(0, 100_000_000 * ONE, ONE);
+        assert_eq!(kamino.debt().unwrap(), 100_000_000);
+    }
+
+    #[test]
+    fn test_debt_includes_interest_accrued_on_the_reserve() {
+        // Obligation last refreshed at rate 1.0; the reserve has since grown 5%
+        let mut kamino = MockKamino::new();
+        kamino.borrow(0, 100_000_000 * ONE, ONE);
+        kamino.set_reserve_rate(ONE + ONE / 20);
+        assert_eq!(kamino.debt().unwrap(), 105_000_000);
+
+        // Interest already folded into the obligation is not charged twice
+        let mut kamino = MockKamino::new();
+        kamino.borrow(2, 105_000_000 * ONE, ONE + ONE / 20);
+        kamino.set_reserve_rate(ONE + ONE / 20);
+        assert_eq!(kamino.debt().unwrap(), 105_000_000);
+    }
+
+    #[test]
+    fn test_debt_rounds_up_in_the_lenders_favour() {
+        let mut kamino = MockKamino::new();
+        kamino.borrow(4, 100 * ONE + 1, ONE);
+        assert_eq!(kamino.debt().unwrap(), 101);
+
+        let borrow = ObligationView::load(&kamino.obligation_data).unwrap().borrow(&kamino.reserve).unwrap().unwrap();
+        assert_eq!(accrued_debt(&borrow, ONE + 1).unwrap(), 102);
+    }
+
+    #[test]
+    fn test_obligation_without_this_reserve_owes_nothing() {
+        let mut kamino = MockKamino::new();
+        assert_eq!(kamino.debt().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_rejects_accounts_kamino_does_not_own() {
+        let mut kamino = MockKamino::new();
+        kamino.borrow(0, 100 * ONE, ONE);
+        assert_eq!(
+            kamino.debt_owned_by(Pubkey::new_unique()).unwrap_err(),
+            GameError::InvalidKaminoObligation.into()
+        );
+    }
+
+    #[test]
+    fn test_rejects_mismatched_layouts_and_markets() {
+        let mut kamino = MockKamino::new();
+        kamino.borrow(0, 100 * ONE, ONE);
+        kamino.obligation_data[0] ^= 0xff;
+        assert_eq!(kamino.debt().unwrap_err(), GameError::InvalidKaminoObligation.into());
+
+        let mut kamino = MockKamino::new();
+        kamino.reserve_data.truncate(reserve_layout::LEN - 1);
+        assert_eq!(kamino.debt().unwrap_err(), GameError::InvalidKaminoReserve.into());
+
+        let mut kamino = MockKamino::new();
+        kamino.lending_market = Pubkey::new_unique();
+        assert_eq!(kamino.debt().unwrap_err(), GameError::InvalidKaminoMarket.into());
+
+        // Reserve rate below the obligation's snapshot is corrupt
+        let mut kamino = MockKamino::new();
+        kamino.borrow(0, 100 * ONE, ONE + 1);
+        assert_eq!(kamino.debt().unwrap_err(), GameError::InvalidKaminoReserve.into());
+    }
+
+    #[test]
+    fn test_loan_owes_only_its_own_share_of_the_obligation() {
+        // The owner already owed 0.5 SOL when the game's 0.1 SOL loan was
+        // taken at rate 1.25; the reserve has since grown another 10%
+        let mut kamino = MockKamino::new();
+        kamino.borrow(0, 600_000_000 * ONE, ONE * 5 / 4);
+        kamino.set_reserve_rate(ONE * 11 / 8);
+        assert_eq!(kamino.debt().unwrap(), 660_000_000);
+        assert_eq!(kamino.loan_debt(100_000_000, ONE * 5 / 4).unwrap(), 110_000_000);
+
+        // Debt repaid outside the game caps the loan at what is left
+        let mut kamino = MockKamino::new();
+        kamino.borrow(0, 40_000_000 * ONE, ONE);
+        assert_eq!(kamino.loan_debt(100_000_000, ONE).unwrap(), 40_000_000);
+    }
+
+    #[test]
+    fn test_repayment_fails_only_when_debt_exceeds_winnings() {
+        // 1v1 at 0.1 SOL: 0.17 SOL to the winner after 15% in fees
+        let plan = PayoutPlan::new(200_000_000, 500, 1000, 1).unwrap();
+
+        let mut kamino = MockKamino::new();
+        kamino.borrow(0, 100_000_000 * ONE, ONE);
+        kamino.set_reserve_rate(ONE * 17 / 10);
+        let debt = kamino.debt().unwrap();
+        assert_eq!(debt, 170_000_000);
+        assert_eq!(plan.with_loan_repayment(0, debt).unwrap().winner_payout(0), 0);
+
+        kamino.set_reserve_rate(ONE * 17 / 10 + ONE / 1_000_000);
+        let debt = kamino.debt().unwrap();
+        assert_eq!(
+            plan.with_loan_repayment(0, debt).unwrap_err(),
+            GameError::InsufficientWinningsForRepayment.into()
+        );
+    }
+
+    fn loan_position(kamino: &MockKamino) -> LoanPosition {
+        LoanPosition {
+            game: Pubkey::new_unique(),
+            game_id: u64::MAX,
+            borrower: Pubkey::new_unique(),
+            lending_market: kamino.lending_market,
+            reserve: kamino.reserve,
+            collateral_reserve: Pubkey::new_unique(),
+            obligation: kamino.obligation,
+            collateral_amount: u64::MAX,
+            collateral_ctokens: u64::MAX,
+            loan_amount: u64::MAX,
+            status: LoanStatus::Outstanding,
+            bump: 255,
+            borrow_rate_bsf: u128::MAX,
+        }
+    }
+
+    #[test]
+    fn test_loan_position_len_matches_layout() {
+        use anchor_lang::AnchorSerialize;
+
+        let position = loan_position(&MockKamino::new());
+        assert_eq!(8 + position.try_to_vec().unwrap().len(), LoanPosition::LEN);
+    }
+
+    #[test]
+    fn test_loan_position_pins_its_kamino_accounts() {
+        let kamino = MockKamino::new();
+        let position = loan_position(&kamino);
+        assert!(position
+            .require_kamino_accounts(&kamino.lending_market, &kamino.reserve, &kamino.obligation)
+            .is_ok());
+
+        let other = Pubkey::new_unique();
+        assert_eq!(
+            position.require_kamino_accounts(&other, &kamino.reserve, &kamino.obligation).unwrap_err(),
+            GameError::InvalidKaminoMarket.into()
+        );
+        assert_eq!(
+            position.require_kamino_accounts(&kamino.lending_market, &other, &kamino.obligation).unwrap_err(),
+            GameError::InvalidKaminoReserve.into()
+        );
+        assert_eq!(
+            position.require_kamino_accounts(&kamino.lending_market, &kamino.reserve, &other).unwrap_err(),
+            GameError::InvalidKaminoObligation.into()
+        );
+    }
+
+    #[test]
+    fn test_collateral_is_read_in_ctokens_of_its_reserve() {
+        let mut kamino = MockKamino::new();
+        let (usdc_reserve, jitosol_reserve) = (Pubkey::new_unique(), Pubkey::new_unique());
+        kamino.deposit(0, &jitosol_reserve, 7_000);
+        kamino.deposit(3, &usdc_reserve, 95_000_000);
+        kamino.borrow(0, 100 * ONE, ONE);
+
+        assert_eq!(kamino.collateral(&usdc_reserve, KAMINO_PROGRAM_ID).unwrap(), 95_000_000);
+        assert_eq!(kamino.collateral(&jitosol_reserve, KAMINO_PROGRAM_ID).unwrap(), 7_000);
+        // The borrow reserve holds no collateral of this obligation
+        let borrow_reserve = kamino.reserve;
+        assert_eq!(kamino.collateral(&borrow_reserve, KAMINO_PROGRAM_ID).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_collateral_needs_a_kamino_obligation_of_the_market() {
+        let mut kamino = MockKamino::new();
+        let reserve = Pubkey::new_unique();
+        kamino.deposit(0, &reserve, 1_000);
+        assert_eq!(
+            kamino.collateral(&reserve, Pubkey::new_unique()).unwrap_err(),
+            GameError::InvalidKaminoObligation.into()
+        );
+
+        kamino.lending_market = Pubkey::new_unique();
+        assert_eq!(
+            kamino.collateral(&reserve, KAMINO_PROGRAM_ID).unwrap_err(),
+            GameError::InvalidKaminoMarket.into()
+        );
+    }
+
+    // Helper: Record `keys` in the mock reserve where klend keeps them
+    fn set_reserve_keys(kamino: &mut MockKamino, keys: &ReserveKeys) {
+        for (offset, key) in [
+            (reserve_layout::LENDING_MARKET, keys.lending_market),
+            (reserve_layout::LIQUIDITY_MINT, keys.liquidity_mint),
+            (reserve_layout::LIQUIDITY_SUPPLY, keys.liquidity_supply),
+            (reserve_layout::LIQUIDITY_FEE_VAULT, keys.liquidity_fee_vault),
+            (reserve_layout::COLLATERAL_MINT, keys.collateral_mint),
+            (reserve_layout::COLLATERAL_SUPPLY, keys.collateral_supply),
+        ] {
+            kamino.reserve_data[offset..][..32].copy_from_slice(key.as_ref());
+        }
+    }
+
+    fn reserve_keys(kamino: &mut MockKamino, owner: Pubkey) -> anchor_lang::Result<ReserveKeys> {
+        let mut lamports = 0u64;
+        let reserve = AccountInfo::new(
+            &kamino.reserve, false, true, &mut lamports,
+            &mut kamino.reserve_data, &owner, false, 0,
+        );
+        ReserveKeys::load(&reserve)
+    }
+
+    fn usdc_reserve(kamino: &MockKamino) -> ReserveKeys {
+        ReserveKeys {
+            lending_market: kamino.lending_market,
+            liquidity_mint: Pubkey::new_unique(),
+            liquidity_supply: Pubkey::new_unique(),
+            liquidity_fee_vault: Pubkey::new_unique(),
+            collateral_mint: Pubkey::new_unique(),
+            collateral_supply: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn test_reserve_keys_are_read_at_klend_offsets() {
+        let mut kamino = MockKamino::new();
+        let keys = usdc_reserve(&kamino);
+        set_reserve_keys(&mut kamino, &keys);
+        assert_eq!(reserve_keys(&mut kamino, KAMINO_PROGRAM_ID).unwrap(), keys);
+        assert_eq!(
+            reserve_keys(&mut kamino, Pubkey::new_unique()).unwrap_err(),
+            GameError::InvalidKaminoReserve.into()
+        );
+    }
+
+    #[test]
+    fn test_deposit_reserve_must_lend_the_collateral_mint() {
+        let kamino = MockKamino::new();
+        let keys = usdc_reserve(&kamino);
+        let deposit = |market: &Pubkey, mint: &Pubkey, supply: &Pubkey, ctoken_mint: &Pubkey, ctoken_supply: &Pubkey| {
+            keys.require_deposit(market, mint, supply, ctoken_mint, ctoken_supply)
+        };
+        let other = Pubkey::new_unique();
+
+        assert!(deposit(&keys.lending_market, &keys.liquidity_mint, &keys.liquidity_supply, &keys.collateral_mint, &keys.collateral_supply).is_ok());
+
+        // e.g. the SOL reserve passed in for USDC collateral
+        assert_eq!(
+            deposit(&keys.lending_market, &other, &keys.liquidity_supply, &keys.collateral_mint, &keys.collateral_supply).unwrap_err(),
+            GameError::InvalidMint.into()
+        );
+        assert_eq!(
+            deposit(&other, &keys.liquidity_mint, &keys.liquidity_supply, &keys.collateral_mint, &keys.collateral_supply).unwrap_err(),
+            GameError::InvalidKaminoMarket.into()
+        );
+        for (supply, ctoken_mint, ctoken_supply) in [
+            (&other, &keys.collateral_mint, &keys.collateral_supply),
+            (&keys.liquidity_supply, &other, &keys.collateral_supply),
+            (&keys.liquidity_supply, &keys.collateral_mint, &other),
+        ] {
+            assert_eq!(
+                deposit(&keys.lending_market, &keys.liquidity_mint, supply, ctoken_mint, ctoken_supply).unwrap_err(),
+                GameError::InvalidKaminoReserve.into()
+            );
+        }
+    }
+
+    #[test]
+    fn test_borrow_reserve_must_lend_sol_with_its_own_fee_vault() {
+        let kamino = MockKamino::new();
+        let keys = ReserveKeys { liquidity_mint: native_mint::ID, ..usdc_reserve(&kamino) };
+        let other = Pubkey::new_unique();
+
+        assert!(keys
+            .require_borrow(&keys.lending_market, &native_mint::ID, &keys.liquidity_supply, &keys.liquidity_fee_vault)
+            .is_ok());
+        assert_eq!(
+            keys.require_borrow(&keys.lending_market, &other, &keys.liquidity_supply, &keys.liquidity_fee_vault).unwrap_err(),
+            GameError::InvalidMint.into()
+        );
+        assert_eq!(
+            keys.require_borrow(&keys.lending_market, &native_mint::ID, &keys.liquidity_supply, &other).unwrap_err(),
+            GameError::InvalidKaminoReserve.into()
+        );
+    }
+}
+
+// ========================================================================
+// KAMINO CPI INSTRUCTION TESTS
+// ========================================================================
+
+#[cfg(test)]
+mod kamino_cpi_tests {
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+    use anchor_lang::solana_program::sysvar;
+    use magic_roulette::constants::KAMINO_PROGRAM_ID;
+    use magic_roulette::kamino::{
+        discriminator, BorrowObligationLiquidity, DepositReserveLiquidityAndObligationCollateral,
+        RepayObligationLiquidity, WithdrawObligationCollateral,
+    };
+    use solana_sha256_hasher::hashv;
+
+    // Helper: Split klend instruction data into its discriminator and amount
+    fn decode(ix: &Instruction) -> ([u8; 8], u64) {
+        assert_eq!(ix.program_id, KAMINO_PROGRAM_ID);
+        assert_eq!(ix.data.len(), 16, "discriminator plus one u64");
+        (
+            ix.data[..8].try_into().unwrap(),
+            u64::from_le_bytes(ix.data[8..].try_into().unwrap()),
+        )
+    }
+
+    // Helper: Anchor sighash computed at runtime, independently of the module
+    fn sighash(name: &str) -> [u8; 8] {
+        hashv(&[format!("global:{name}").as_bytes()]).to_bytes()[..8].try_into().unwrap()
+    }
+
+    fn signer(key: Pubkey, writable: bool) -> AccountMeta {
+        AccountMeta { pubkey: key, is_signer: true, is_writable: writable }
+    }
+
+    fn writable(key: Pubkey) -> AccountMeta {
+        AccountMeta::new(key, false)
+    }
+
+    fn readonly(key: Pubkey) -> AccountMeta {
+        AccountMeta::new_readonly(key, false)
+    }
+
+    fn keys<const N: usize>() -> [Pubkey; N] {
+        std::array::from_fn(|_| Pubkey::new_unique())
+    }
+
+    #[test]
+    fn test_program_id_is_klend_mainnet() {
+        assert_eq!(KAMINO_PROGRAM_ID.to_string(), "KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD");
+    }
+
+    #[test]
+    fn test_discriminators_are_anchor_sighashes() {
+        // Golden bytes, and the same sighash recomputed at runtime
+        let cases = [
+            (
+                discriminator::DEPOSIT_RESERVE_LIQUIDITY_AND_OBLIGATION_COLLATERAL,
+                "deposit_reserve_liquidity_and_obligation_collateral",
+                [0x81, 0xc7, 0x04, 0x02, 0xde, 0x27, 0x1a, 0x2e],
+            ),
+            (
+                discriminator::BORROW_OBLIGATION_LIQUIDITY,
+                "borrow_obligation_liquidity",
+                [0x79, 0x7f, 0x12, 0xcc, 0x49, 0xf5, 0xe1, 0x41],
+            ),
+            (
+                discriminator::REPAY_OBLIGATION_LIQUIDITY,
+                "repay_obligation_liquidity",
+                [0x91, 0xb2, 0x0d, 0xe1, 0x4c, 0xf0, 0x93, 0x48],
+            ),
+            (
+                discriminator::WITHDRAW_OBLIGATION_COLLATERAL,
+                "withdraw_obligation_collateral",
+                [0x25, 0x74, 0xcd, 0x67, 0xf3, 0xc0, 0x5c, 0xc6],
+            ),
+        ];
+        for (discriminator, name, golden) in cases {
+            assert_eq!(discriminator, golden, "{name}");
+            assert_eq!(discriminator, sighash(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_deposit_decodes_in_idl_order() {
+        let [owner, obligation, lending_market, lending_market_authority, reserve, reserve_liquidity_mint,
+            reserve_liquidity_supply, reserve_collateral_mint, reserve_destination_deposit_collateral,
+            user_source_liquidity, collateral_token_program, liquidity_token_program] = keys();
+        let ix = DepositReserveLiquidityAndObligationCollateral {
+            owner,
+            obligation,
+            lending_market,
+            lending_market_authority,
+            reserve,
+            reserve_liquidity_mint,
+            reserve_liquidity_supply,
+            reserve_collateral_mint,
+            reserve_destination_deposit_collateral,
+            user_source_liquidity,
+            collateral_token_program,
+            liquidity_token_program,
+        }
+        .instruction(110_000_000);
+
+        assert_eq!(
+            decode(&ix),
+            (sighash("deposit_reserve_liquidity_and_obligation_collateral"), 110_000_000)
+        );
+        assert_eq!(
+            ix.accounts,
+            vec![
+                signer(owner, true),
+                writable(obligation),
+                readonly(lending_market),
+                readonly(lending_market_authority),
+                writable(reserve),
+                readonly(reserve_liquidity_mint),
+                writable(reserve_liquidity_supply),
+                writable(reserve_collateral_mint),
+                writable(reserve_destination_deposit_collateral),
+                writable(user_source_liquidity),
+                // placeholder_user_destination_collateral: None
+                readonly(KAMINO_PROGRAM_ID),
+                readonly(collateral_token_program),
+                readonly(liquidity_token_program),
+                readonly(sysvar::instructions::ID),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_borrow_decodes_in_idl_order() {
+        let [owner, obligation, lending_market, lending_market_authority, borrow_reserve,
+            borrow_reserve_liquidity_mint, reserve_source_liquidity, borrow_reserve_liquidity_fee_receiver,
+            user_destination_liquidity, token_program] = keys();
+        let ix = BorrowObligationLiquidity {
+            owner,
+            obligation,
+            lending_market,
+            lending_market_authority,
+            borrow_reserve,
+            borrow_reserve_liquidity_mint,
+            reserve_source_liquidity,
+            borrow_reserve_liquidity_fee_receiver,
+            user_destination_liquidity,
+            token_program,
+        }
+        .instruction(100_000_000);
+
+        assert_eq!(decode(&ix), (sighash("borrow_obligation_liquidity"), 100_000_000));
+        assert_eq!(
+            ix.accounts,
+            vec![
+                signer(owner, false),
+                writable(obligation),
+                readonly(lending_market),
+                readonly(lending_market_authority),
+                writable(borrow_reserve),
+                readonly(borrow_reserve_liquidity_mint),
+                writable(reserve_source_liquidity),
+                writable(borrow_reserve_liquidity_fee_receiver),
+                writable(user_destination_liquidity),
+                // referrer_token_state: None
+                readonly(KAMINO_PROGRAM_ID),
+                readonly(token_program),
+                readonly(sysvar::instructions::ID),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repay_decodes_in_idl_order() {
+        let [owner, obligation, lending_market, repay_reserve, reserve_liquidity_mint,
+            reserve_destination_liquidity, user_source_liquidity, token_program] = keys();
+        let ix = RepayObligationLiquidity {
+            owner,
+            obligation,
+            lending_market,
+            repay_reserve,
+            reserve_liquidity_mint,
+            reserve_destination_liquidity,
+            user_source_liquidity,
+            token_program,
+        }
+        .instruction(u64::MAX);
+
+        assert_eq!(decode(&ix), (sighash("repay_obligation_liquidity"), u64::MAX));
+        assert_eq!(
+            ix.accounts,
+            vec![
+                signer(owner, false),
+                writable(obligation),
+                readonly(lending_market),
+                writable(repay_reserve),
+                readonly(reserve_liquidity_mint),
+                writable(reserve_destination_liquidity),
+                writable(user_source_liquidity),
+                readonly(token_program),
+                readonly(sysvar::instructions::ID),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_withdraw_decodes_in_idl_order() {
+        let [owner, obligation, lending_market, lending_market_authority, withdraw_reserve,
+            reserve_source_collateral, user_destination_collateral, token_program] = keys();
+        let ix = WithdrawObligationCollateral {
+            owner,
+            obligation,
+            lending_market,
+            lending_market_authority,
+            withdraw_reserve,
+            reserve_source_collateral,
+            user_destination_collateral,
+            token_program,
+        }
+        .instruction(42);
+
+        assert_eq!(decode(&ix), (sighash("withdraw_obligation_collateral"), 42));
+        assert_eq!(
+            ix.accounts,
+            vec![
+                signer(owner, false),
+                writable(obligation),
+                readonly(lending_market),
+                readonly(lending_market_authority),
+                writable(withdraw_reserve),
+                writable(reserve_source_collateral),
+                writable(user_destination_collateral),
+                readonly(token_program),
+                readonly(sysvar::instructions::ID),
+            ]
+        );
+    }
+}
+
+// ========================================================================
+// ORACLE COLLATERAL TESTS
+// ========================================================================
+
+#[cfg(test)]
+mod oracle_tests {
+    use anchor_lang::prelude::{AccountInfo, Pubkey};
+    use magic_roulette::constants::{PYTH_RECEIVER_PROGRAM_ID, SWITCHBOARD_ON_DEMAND_PROGRAM_ID};
+    use magic_roulette::errors::GameError;
+    use magic_roulette::oracle::{
+        self, price_update_layout, pull_feed_layout, OraclePrice, PRICE_UPDATE_V2_DISCRIMINATOR,
+        PULL_FEED_DISCRIMINATOR,
+    };
+    use magic_roulette::state::AssetOracle;
+    use solana_sha256_hasher::hashv;
+
+    const NOW: i64 = 1_700_000_000;
+    const SOL_FEED: [u8; 32] = [0xef; 32];
+
+    // Helper: Mock oracle account, laid out like the on-chain one
+    struct MockFeed {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+    }
+
+    impl MockFeed {
+        // Pyth PriceUpdateV2, fully verified
+        fn pyth(feed_id: [u8; 32], price: i64, conf: u64, exponent: i32, publish_time: i64) -> Self {
+            let mut data = vec![0u8; price_update_layout::LEN + 1];
+            data[..8].copy_from_slice(&PRICE_UPDATE_V2_DISCRIMINATOR);
+            data[price_update_layout::VERIFICATION_LEVEL] = price_update_layout::FULL;
+            data[price_update_layout::FEED_ID..][..32].copy_from_slice(&feed_id);
+            data[price_update_layout::PRICE..][..8].copy_from_slice(&price.to_le_bytes());
+            data[price_update_layout::CONF..][..8].copy_from_slice(&conf.to_le_bytes());
+            data[price_update_layout::EXPONENT..][..4].copy_from_slice(&exponent.to_le_bytes());
+            data[price_update_layout::PUBLISH_TIME..][..8].copy_from_slice(&publish_time.to_le_bytes());
+            Self { key: Pubkey::new_unique(), owner: PYTH_RECEIVER_PROGRAM_ID, lamports: 0, data }
+        }
+
+        // Switchboard PullFeedAccountData with an 18-decimal result
+        fn switchboard(value: i128, std_dev: i128, timestamp: i64) -> Self {
+            let mut data = vec![0u8; 3208];
+            data[..8].copy_from_slice(&PULL_FEED_DISCRIMINATOR);
+            data[pull_feed_layout::RESULT_VALUE..][..16].copy_from_slice(&value.to_le_bytes());
+            data[pull_feed_layout::RESULT_STD_DEV..][..16].copy_from_slice(&std_dev.to_le_bytes());
+            data[pull_feed_layout::LAST_UPDATE_TIMESTAMP..][..8].copy_from_slice(&timestamp.to_le_bytes());
+            Self { key: Pubkey::new_unique(), owner: SWITCHBOARD_ON_DEMAND_PROGRAM_ID, lamports: 0, data }
+        }
+
+        fn info(&mut self) -> AccountInfo<'_> {
+            AccountInfo::new(
+                &self.key, false, false, &mut self.lamports,
+                &mut self.data, &self.owner, false, 0,
+            )
+        }
+    }
+
+    fn sol_oracle(switchboard_feed: Pubkey) -> AssetOracle {
+        AssetOracle {
+            mint: anchor_spl::token::spl_token::native_mint::ID,
+            pyth_feed_id: SOL_FEED,
+            switchboard_feed,
+            bump: 255,
+        }
+    }
+
+    // Helper: Exact USD price with 8 decimals, as Pyth publishes it
+    fn usd(price: u128) -> OraclePrice {
+        OraclePrice { price: price * 100_000_000, confidence: 0, exponent: -8, publish_time: NOW }
+    }
+
+    fn read(pyth: &mut MockFeed, switchboard: &mut MockFeed) -> anchor_lang::Result<OraclePrice> {
+        let asset_oracle = sol_oracle(switchboard.key);
+        oracle::price(&asset_oracle, &pyth.info(), &switchboard.info(), NOW)
+    }
+
+    #[test]
+    fn test_discriminators_match_oracle_account_names() {
+        assert_eq!(hashv(&[b"account:PriceUpdateV2"]).to_bytes()[..8], PRICE_UPDATE_V2_DISCRIMINATOR);
+        assert_eq!(hashv(&[b"account:PullFeedAccountData"]).to_bytes()[..8], PULL_FEED_DISCRIMINATOR);
+    }
+
+    #[test]
+    fn test_fresh_pyth_price_is_read() {
+        // SOL at $150.00 +/- $0.15, published 10 seconds ago
+        let mut pyth = MockFeed::pyth(SOL_FEED, 15_000_000_000, 15_000_000, -8, NOW - 10);
+        let mut switchboard = MockFeed::switchboard(0, 0, 0);
+        assert_eq!(
+            read(&mut pyth, &mut switchboard).unwrap(),
+            OraclePrice { price: 15_000_000_000, confidence: 15_000_000, exponent: -8, publish_time: NOW - 10 }
+        );
+    }
+
+    #[test]
+    fn test_stale_or_uncertain_prices_are_rejected() {
+        let stale = OraclePrice { publish_time: NOW - 61, ..usd(150) };
+        assert_eq!(stale.validate(NOW).unwrap_err(), GameError::StaleOraclePrice.into());
+        assert!(OraclePrice { publish_time: NOW - 60, ..usd(150) }.validate(NOW).is_ok());
+
+        // 2% confidence is the widest accepted
+        let price = usd(150);
+        assert!(OraclePrice { confidence: price.price / 50, ..price }.validate(NOW).is_ok());
+        assert_eq!(
+            OraclePrice { confidence: price.price / 50 + 100_000_000, ..price }.validate(NOW).unwrap_err(),
+            GameError::OracleConfidenceTooWide.into()
+        );
+
+        assert_eq!(
+            OraclePrice { price: 0, ..price }.validate(NOW).unwrap_err(),
+            GameError::InvalidOracleAccount.into()
+        );
+    }
+
+    #[test]
+    fn test_pyth_rejects_foreign_or_unverified_updates() {
+        let mut wrong_feed = MockFeed::pyth([0x11; 32], 15_000_000_000, 0, -8, NOW);
+        assert_eq!(
+            oracle::pyth_price(&wrong_feed.info(), &SOL_FEED).unwrap_err(),
+            GameError::InvalidOracleAccount.into()
+        );
+
+        let mut wrong_owner = MockFeed::pyth(SOL_FEED, 15_000_000_000, 0, -8, NOW);
+        wrong_owner.owner = Pubkey::new_unique();
+        assert_eq!(
+            oracle::pyth_price(&wrong_owner.info(), &SOL_FEED).unwrap_err(),
+            GameError::InvalidOracleAccount.into()
+        );
+
+        let mut partial = MockFeed::pyth(SOL_FEED, 15_000_000_000, 0, -8, NOW);
+        partial.data[price_update_layout::VERIFICATION_LEVEL] = 0;
+        assert_eq!(
+            oracle::pyth_price(&partial.info(), &SOL_FEED).unwrap_err(),
+            GameError::InvalidOracleAccount.into()
+        );
+
+        let mut negative = MockFeed::pyth(SOL_FEED, -1, 0, -8, NOW);
+        assert_eq!(
+            oracle::pyth_price(&negative.info(), &SOL_FEED).unwrap_err(),
+            GameError::InvalidOracleAccount.into()
+        );
+    }
+
+    #[test]
+    fn test_switchboard_is_the_fallback_for_an_unusable_pyth_price() {
+        // Pyth is stale; Switchboard has SOL at $149.50 +/- $0.10
+        let mut pyth = MockFeed::pyth(SOL_FEED, 15_000_000_000, 0, -8, NOW - 120);
+        let mut switchboard = MockFeed::switchboard(
+            149_500_000_000_000_000_000,
+            100_000_000_000_000_000,
+            NOW - 5,
+        );
+        assert_eq!(
+            read(&mut pyth, &mut switchboard).unwrap(),
+            OraclePrice { price: 149_500_000_000, confidence: 100_000_000, exponent: -9, publish_time: NOW - 5 }
+        );
+
+        // A stale fallback fails the read
+        let mut switchboard = MockFeed::switchboard(149_500_000_000_000_000_000, 0, NOW - 120);
+        assert_eq!(
+            read(&mut pyth, &mut switchboard).unwrap_err(),
+            GameError::StaleOraclePrice.into()
+        );
+    }
+
+    #[test]
+    fn test_switchboard_fallback_must_be_the_registered_feed() {
+        let mut pyth = MockFeed::pyth(SOL_FEED, 15_000_000_000, 0, -8, NOW - 120);
+        let mut switchboard = MockFeed::switchboard(149_500_000_000_000_000_000, 0, NOW);
+        let asset_oracle = sol_oracle(Pubkey::new_unique());
+        assert_eq!(
+            oracle::price(&asset_oracle, &pyth.info(), &switchboard.info(), NOW).unwrap_err(),
+            GameError::InvalidOracleAccount.into()
+        );
+
+        let mut forged = MockFeed::switchboard(149_500_000_000_000_000_000, 0, NOW);
+        forged.owner = Pubkey::new_unique();
+        assert_eq!(
+            read(&mut pyth, &mut forged).unwrap_err(),
+            GameError::InvalidOracleAccount.into()
+        );
+    }
+
+    #[test]
+    fn test_sol_collateral_needs_the_minimum_ratio() {
+        // Borrowing 0.1 SOL against SOL at 110%
+        let sol = usd(150);
+        assert!(oracle::require_collateral_ratio(110_000_000, 9, &sol, 100_000_000, 9, &sol, 11_000).is_ok());
+        assert_eq!(
+            oracle::require_collateral_ratio(109_999_999, 9, &sol, 100_000_000, 9, &sol, 11_000).unwrap_err(),
+            GameError::InsufficientCollateral.into()
+        );
+
+        // The ratio is configurable
+        assert!(oracle::require_collateral_ratio(150_000_000, 9, &sol, 100_000_000, 9, &sol, 15_000).is_ok());
+        assert!(oracle::require_collateral_ratio(149_999_999, 9, &sol, 100_000_000, 9, &sol, 15_000).is_err());
+    }
+
+    #[test]
+    fn test_usdc_collateral_is_valued_against_sol() {
+        // 1 SOL at $150 needs $165 of USDC at 110%
+        let sol = usd(150);
+        let usdc = usd(1);
+        assert!(oracle::require_collateral_ratio(165_000_000, 6, &usdc, 1_000_000_000, 9, &sol, 11_000).is_ok());
+        assert_eq!(
+            oracle::require_collateral_ratio(164_999_999, 6, &usdc, 1_000_000_000, 9, &sol, 11_000).unwrap_err(),
+            GameError::InsufficientCollateral.into()
+        );
+
+        // Prices on different exponents still compare exactly
+        let switchboard_sol = OraclePrice { price: 150_000_000_000, confidence: 0, exponent: -9, publish_time: NOW };
+        assert!(oracle::require_collateral_ratio(165_000_000, 6, &usdc, 1_000_000_000, 9, &switchboard_sol, 11_000).is_ok());
+        assert!(oracle::require_collateral_ratio(164_999_999, 6, &usdc, 1_000_000_000, 9, &switchboard_sol, 11_000).is_err());
+    }
+
+    #[test]
+    fn test_confidence_counts_against_the_borrower() {
+        // SOL at $150 +/- $1.50: collateral at $148.50, debt at $151.50
+        let sol = OraclePrice { confidence: 150_000_000, ..usd(150) };
+        assert_eq!(
+            oracle::require_collateral_ratio(110_000_000, 9, &sol, 100_000_000, 9, &sol, 11_000).unwrap_err(),
+            GameError::InsufficientCollateral.into()
+        );
+        assert!(oracle::require_collateral_ratio(112_222_222, 9, &sol, 100_000_000, 9, &sol, 11_000).is_err());
+        assert!(oracle::require_collateral_ratio(112_222_223, 9, &sol, 100_000_000, 9, &sol, 11_000).is_ok());
+    }
+}
+
+// ========================================================================
+// SQUADS MULTISIG TESTS
+// ========================================================================
+
+#[cfg(test)]
+mod squads_tests {
+    use anchor_lang::prelude::{AccountInfo, Pubkey};
+    use magic_roulette::constants::{
+        SQUADS_PLATFORM_VAULT_INDEX, SQUADS_PROGRAM_ID, SQUADS_TREASURY_VAULT_INDEX,
+    };
+    use magic_roulette::errors::GameError;
+    use magic_roulette::squads::{self, MULTISIG_DISCRIMINATOR};
+    use magic_roulette::settlement::{record_settlement, PayoutPlan};
+    use magic_roulette::state::{Game, GameStatus, PlatformConfig};
+    use solana_sha256_hasher::hashv;
+    use std::str::FromStr;
+
+    // Helper: Check `data` owned by `owner` as a Squads multisig
+    fn check_multisig(owner: Pubkey, mut data: Vec<u8>) -> anchor_lang::Result<()> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0u64;
+        let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        squads::require_multisig(&account)
+    }
+
+    fn multisig_data() -> Vec<u8> {
+        let mut data = vec![0u8; 256];
+        data[..8].copy_from_slice(&MULTISIG_DISCRIMINATOR);
+        data
+    }
+
+    #[test]
+    fn test_program_id_is_squads_v4() {
+        assert_eq!(SQUADS_PROGRAM_ID.to_string(), "SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf");
+        assert_eq!(hashv(&[b"account:Multisig"]).to_bytes()[..8], MULTISIG_DISCRIMINATOR);
+    }
+
+    #[test]
+    fn test_vaults_derive_like_the_squads_sdk() {
+        // getVaultPda: ["multisig", multisig, "vault", u8 index]
+        let squads_program = Pubkey::from_str("SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf").unwrap();
+        let multisig = Pubkey::new_unique();
+        for index in [SQUADS_PLATFORM_VAULT_INDEX, SQUADS_TREASURY_VAULT_INDEX] {
+            let expected = Pubkey::find_program_address(
+                &[b"multisig", multisig.as_ref(), b"vault", &[index]],
+                &squads_program,
+            );
+            assert_eq!(squads::vault_address(&multisig, index), expected);
+        }
+
+        // Each index is its own vault, and each multisig has its own vaults
+        let treasury = squads::vault_address(&multisig, SQUADS_TREASURY_VAULT_INDEX).0;
+        assert_ne!(squads::vault_address(&multisig, SQUADS_PLATFORM_VAULT_INDEX).0, treasury);
+        assert_ne!(squads::vault_address(&Pubkey::new_unique(), SQUADS_TREASURY_VAULT_INDEX).0, treasury);
+    }
+
+    #[test]
+    fn test_multisig_must_be_a_squads_multisig() {
+        assert!(check_multisig(SQUADS_PROGRAM_ID, multisig_data()).is_ok());
+
+        assert_eq!(
+            check_multisig(Pubkey::new_unique(), multisig_data()).unwrap_err(),
+            GameError::InvalidMultisig.into()
+        );
+
+        let mut wrong_account = multisig_data();
+        wrong_account[0] ^= 0xff;
+        assert_eq!(
+            check_multisig(SQUADS_PROGRAM_ID, wrong_account).unwrap_err(),
+            GameError::InvalidMultisig.into()
+        );
+        assert_eq!(
+            check_multisig(SQUADS_PROGRAM_ID, Vec::new()).unwrap_err(),
+            GameError::InvalidMultisig.into()
+        );
+    }
+
+    // Helper: Platform config as initialize_platform(_with_multisig) leaves it
+    fn platform(multisig: Option<Pubkey>) -> PlatformConfig {
+        let vault = |index| multisig.map(|multisig| squads::vault_address(&multisig, index).0);
+        PlatformConfig {
+            authority: multisig.unwrap_or_else(Pubkey::new_unique),
+            pending_authority: None,
+            treasury: vault(SQUADS_TREASURY_VAULT_INDEX).unwrap_or_else(Pubkey::new_unique),
+            platform_mint: Pubkey::default(),
+            platform_fee_bps: 500,
+            treasury_fee_bps: 1000,
+            rewards_share_bps: 2000,
+            total_games: 0,
+            total_volume: 0,
+            treasury_balance: 0,
+            paused: false,
+            turn_timeout_seconds: 120,
+            min_collateral_ratio_bps: 11_000,
+            multisig_authority: multisig,
+            platform_vault: vault(SQUADS_PLATFORM_VAULT_INDEX),
+            treasury_vault: vault(SQUADS_TREASURY_VAULT_INDEX),
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_multisig_admin_is_its_vault_not_the_multisig() {
+        // Test: The multisig PDA can never sign, so admin instructions are
+        // signed by Squads vault 0 once the members approve them
+        let multisig = Pubkey::new_unique();
+        let config = platform(Some(multisig));
+        assert_eq!(config.admin(), squads::vault_address(&multisig, SQUADS_PLATFORM_VAULT_INDEX).0);
+        assert_ne!(config.admin(), multisig);
+
+        let config = platform(None);
+        assert_eq!(config.admin(), config.authority);
+    }
+
+    #[test]
+    fn test_fees_go_to_the_squads_vaults_not_the_multisig() {
+        // Test: Every finalize (token, SOL and loan) pays the platform fee to
+        // platform_fee_recipient and the treasury fee to the treasury
+        let multisig = Pubkey::new_unique();
+        let config = platform(Some(multisig));
+        assert_eq!(
+            config.platform_fee_recipient(),
+            squads::vault_address(&multisig, SQUADS_PLATFORM_VAULT_INDEX).0
+        );
+        assert_eq!(config.treasury, squads::vault_address(&multisig, SQUADS_TREASURY_VAULT_INDEX).0);
+        assert_ne!(config.platform_fee_recipient(), config.authority);
+
+        let config = platform(None);
+        assert_eq!(config.platform_fee_recipient(), config.authority);
+    }
+
+    #[test]
+    fn test_accepting_authority_drops_the_old_multisig() {
+        let multisig = Pubkey::new_unique();
+        let old_admin = squads::vault_address(&multisig, SQUADS_PLATFORM_VAULT_INDEX).0;
+        let mut config = platform(Some(multisig));
+        let new_authority = Pubkey::new_unique();
+        config.pending_authority = Some(new_authority);
+
+        config.transfer_authority(new_authority);
+        assert_eq!(config.admin(), new_authority);
+        assert_ne!(config.admin(), old_admin);
+        assert_eq!(config.pending_authority, None);
+        assert_eq!(config.multisig_authority, None);
+        assert_eq!(config.platform_fee_recipient(), new_authority);
+        assert_eq!(config.treasury_vault, None);
+    }
+
+    #[test]
+    fn test_multisig_treasury_cannot_be_moved() {
+        let config = platform(Some(Pubkey::new_unique()));
+        assert_eq!(
+            config.require_treasury_update(&Pubkey::new_unique()).unwrap_err(),
+            GameError::TreasuryManagedByMultisig.into()
+        );
+
+        let config = platform(None);
+        assert!(config.require_treasury_update(&Pubkey::new_unique()).is_ok());
+        assert_eq!(
+            config.require_treasury_update(&Pubkey::default()).unwrap_err(),
+            GameError::InvalidTreasury.into()
+        );
+    }
+
+    #[test]
+    fn test_treasury_balance_only_counts_lamports() {
+        // Test: withdraw_treasury pays lamports, so token-game fees, which go
+        // straight to the treasury's token account, are never booked
+        let mut config = platform(Some(Pubkey::new_unique()));
+        let plan = PayoutPlan::new(400_000_000, 500, 1000, 2).unwrap();
+        let finished = |mint| Game { status: GameStatus::Finished, mint, ..Game::default() };
+
+        let mut sol_game = finished(None);
+        record_settlement(&mut sol_game, &mut config, &plan).unwrap();
+        assert_eq!(config.treasury_balance, plan.treasury_payout());
+        assert_eq!(sol_game.status, GameStatus::Settled);
+
+        let mut token_game = finished(Some(Pubkey::new_unique()));
+        record_settlement(&mut token_game, &mut config, &plan).unwrap();
+        assert_eq!(config.treasury_balance, plan.treasury_payout());
+        assert_eq!(config.total_volume, 800_000_000);
+
+        // Withdrawals are booked against the lamports held, never past them
+        assert_eq!(
+            config.debit_treasury(plan.treasury_payout() + 1).unwrap_err(),
+            GameError::InsufficientTreasuryBalance.into()
+        );
+        config.debit_treasury(plan.treasury_payout()).unwrap();
+        assert_eq!(config.treasury_balance, 0);
+    }
+}
+
+// ========================================================================
+// TOKEN-2022 MINT EXTENSION TESTS
+// ========================================================================
+
+#[cfg(test)]
+mod mint_extension_tests {
+    use anchor_lang::prelude::Pubkey;
+    use anchor_spl::token_2022::spl_token_2022::extension::{
+        default_account_state::DefaultAccountState,
+        non_transferable::NonTransferable,
+        permanent_delegate::PermanentDelegate,
+        transfer_fee::TransferFeeConfig,
+        BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+    };
+    use anchor_spl::token_2022::spl_token_2022::state::{AccountState, Mint};
+    use magic_roulette::token::validate_mint_data;
+
+    // Helper: Build initialized mint data with the given extensions,
+    // letting `configure` fill them in
+    fn mint_with(extensions: &[ExtensionType], configure: impl FnOnce(&mut StateWithExtensionsMut<Mint>)) -> Vec<u8> {
+        let len = ExtensionType::try_calculate_account_len::<Mint>(extensions).unwrap();
+        let mut data = vec![0u8; len];
+        let mut mint = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        configure(&mut mint);
+        mint.base = Mint { decimals: 6, is_initialized: true, ..Mint::default() };
+        mint.pack_base();
+        mint.init_account_type().unwrap();
+        data
+    }
+
+    #[test]
+    fn test_plain_mint_returns_decimals() {
+        let data = mint_with(&[], |_| {});
+        assert_eq!(validate_mint_data(&data).unwrap(), 6);
+    }
+
+    #[test]
+    fn test_transfer_fee_mint_is_accepted() {
+        let data = mint_with(&[ExtensionType::TransferFeeConfig], |mint| {
+            mint.init_extension::<TransferFeeConfig>(true).unwrap();
+        });
+        assert_eq!(validate_mint_data(&data).unwrap(), 6);
+    }
+
+    #[test]
+    fn test_non_transferable_mint_is_rejected() {
+        let data = mint_with(&[ExtensionType::NonTransferable], |mint| {
+            mint.init_extension::<NonTransferable>(true).unwrap();
+        });
+        assert!(validate_mint_data(&data).is_err());
+    }
+
+    #[test]
+    fn test_permanent_delegate_mint_is_rejected() {
+        let data = mint_with(&[ExtensionType::PermanentDelegate], |mint| {
+            let extension = mint.init_extension::<PermanentDelegate>(true).unwrap();
+            extension.delegate = Some(Pubkey::new_unique()).try_into().unwrap();
+        });
+        assert!(validate_mint_data(&data).is_err());
+    }
+
+    #[test]
+    fn test_frozen_default_state_is_rejected() {
+        let frozen = mint_with(&[ExtensionType::DefaultAccountState], |mint| {
+            let extension = mint.init_extension::<DefaultAccountState>(true).unwrap();
+            extension.state = AccountState::Frozen as u8;
+        });
+        assert!(validate_mint_data(&frozen).is_err());
+
+        let initialized = mint_with(&[ExtensionType::DefaultAccountState], |mint| {
+            let extension = mint.init_extension::<DefaultAccountState>(true).unwrap();
+            extension.state = AccountState::Initialized as u8;
+        });
+        assert!(validate_mint_data(&initialized).is_ok());
+    }
+}
+
+// ========================================================================
+// GAME VAULT CLOSE TESTS
+// ========================================================================
+
+#[cfg(test)]
+mod vault_close_tests {
+    use anchor_lang::prelude::{AccountInfo, Pubkey};
+    use anchor_lang::solana_program::instruction::Instruction;
+    use anchor_lang::solana_program::program_error::ProgramError;
+    use anchor_lang::system_program;
+    use anchor_spl::token_2022::spl_token_2022::extension::{
+        transfer_fee::{TransferFeeAmount, TransferFeeConfig},
+        BaseStateWithExtensions, BaseStateWithExtensionsMut, ExtensionType,
+        StateWithExtensions, StateWithExtensionsMut,
+    };
+    use anchor_spl::token_2022::spl_token_2022::processor::Processor;
+    use anchor_spl::token_2022::spl_token_2022::state::{Account, AccountState, Mint};
+    use anchor_spl::token_2022::ID as TOKEN_2022_PROGRAM_ID;
+    use magic_roulette::state::Game;
+    use magic_roulette::token::close_vault_instructions;
+
+    // Helper: A cancelled game's vault after both entrants were refunded,
+    // still holding the transfer fees withheld from their deposits
+    struct CancelledVault {
+        mint: Pubkey,
+        vault: Pubkey,
+        game: Pubkey,
+        creator: Pubkey,
+        mint_data: Vec<u8>,
+        vault_data: Vec<u8>,
+        lamports: [u64; 4],
+        owners: [Pubkey; 4],
+    }
+
+    impl CancelledVault {
+        fn new(withheld: u64) -> Self {
+            let mint = Pubkey::new_unique();
+            let game = Game::find_address(7).0;
+
+            let len = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig]).unwrap();
+            let mut mint_data = vec![0u8; len];
+            let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut mint_data).unwrap();
+            state.init_extension::<TransferFeeConfig>(true).unwrap();
+            state.base = Mint { decimals: 6, is_initialized: true, ..Mint::default() };
+            state.pack_base();
+            state.init_account_type().unwrap();
+
+            let len = ExtensionType::try_calculate_account_len::<Account>(&[ExtensionType::TransferFeeAmount]).unwrap();
+            let mut vault_data = vec![0u8; len];
+            let mut state = StateWithExtensionsMut::<Account>::unpack_uninitialized(&mut vault_data).unwrap();
+            state.init_extension::<TransferFeeAmount>(true).unwrap().withheld_amount = withheld.into();
+            state.base = Account { mint, owner: game, state: AccountState::Initialized, ..Account::default() };
+            state.pack_base();
+            state.init_account_type().unwrap();
+
+            Self {
+                mint,
+                vault: Game::find_vault_address(&game).0,
+                game,
+                creator: Pubkey::new_unique(),
+                mint_data,
+                vault_data,
+                lamports: [1_500_000, 2_000_000, 0, 0],
+                owners: [TOKEN_2022_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, system_program::ID, magic_roulette::ID],
+            }
+        }
+
+        // Run `instructions` through the Token-2022 processor as the game
+        // PDA would through CPI, stopping at the first failure
+        fn close(&mut self, instructions: &[Instruction]) -> Result<(), ProgramError> {
+            let (mut destination_data, mut game_data) = (Vec::new(), Vec::new());
+            let [mint_lamports, vault_lamports, destination_lamports, game_lamports] = &mut self.lamports;
+            let [mint_owner, vault_owner, destination_owner, game_owner] = &self.owners;
+            let infos = [
+                AccountInfo::new(&self.mint, false, true, mint_lamports, &mut self.mint_data, mint_owner, false, 0),
+                AccountInfo::new(&self.vault, false, true, vault_lamports, &mut self.vault_data, vault_owner, false, 0),
+                AccountInfo::new(&self.creator, false, true, destination_lamports, &mut destination_data, destination_owner, false, 0),
+                AccountInfo::new(&self.game, true, false, game_lamports, &mut game_data, game_owner, false, 0),
+            ];
+
+            for instruction in instructions {
+                let accounts: Vec<AccountInfo> = instruction
+                    .accounts
+                    .iter()
+                    .map(|meta| infos.iter().find(|info| *info.key == meta.pubkey).unwrap().clone())
+                    .collect();
+                Processor::process(&instruction.program_id, &accounts, &instruction.data)?;
+            }
+            Ok(())
+        }
+
+        fn instructions(&self) -> Vec<Instruction> {
+            let (mut lamports, mut data) = (self.lamports[1], self.vault_data.clone());
+            let vault = AccountInfo::new(&self.vault, false, true, &mut lamports, &mut data, &TOKEN_2022_PROGRAM_ID, false, 0);
+            close_vault_instructions(&TOKEN_2022_PROGRAM_ID, &self.mint, &vault, &self.creator, &self.game).unwrap()
+        }
+
+        fn mint_withheld(&self) -> u64 {
+            let mint = StateWithExtensions::<Mint>::unpack(&self.mint_data).unwrap();
+            mint.get_extension::<TransferFeeConfig>().unwrap().withheld_amount.into()
+        }
+    }
+
+    #[test]
+    fn test_cancelled_transfer_fee_vault_harvests_then_closes() {
+        let mut vault = CancelledVault::new(20_000);
+        let instructions = vault.instructions();
+        assert_eq!(instructions.len(), 2);
+
+        vault.close(&instructions).unwrap();
+
+        // Withheld fees went to the mint and the rent to the creator
+        assert_eq!(vault.mint_withheld(), 20_000);
+        assert_eq!(vault.lamports[1], 0);
+        assert_eq!(vault.lamports[2], 2_000_000);
+        assert_eq!(vault.owners[1], system_program::ID);
+    }
+
+    #[test]
+    fn test_withheld_fees_block_a_bare_close() {
+        // Without the harvest Token-2022 refuses to close the vault
+        let mut vault = CancelledVault::new(20_000);
+        let close_only = vault.instructions().split_off(1);
+        assert!(vault.close(&close_only).is_err());
+        assert_eq!(vault.lamports[1], 2_000_000);
+    }
+
+    #[test]
+    fn test_vault_without_withheld_fees_just_closes() {
+        let mut vault = CancelledVault::new(0);
+        let instructions = vault.instructions();
+        assert_eq!(instructions.len(), 1);
+
+        vault.close(&instructions).unwrap();
+        assert_eq!(vault.lamports[2], 2_000_000);
+    }
+}
+
+// ========================================================================
+// GAME VAULT WRAPPED SOL TESTS
+// ========================================================================
+
+#[cfg(test)]
+mod vault_wsol_tests {
+    use anchor_lang::prelude::{AccountInfo, Pubkey, Rent};
+    use anchor_lang::solana_program::instruction::Instruction;
+    use anchor_lang::solana_program::program_error::ProgramError;
+    use anchor_lang::solana_program::program_option::COption;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_lang::solana_program::system_instruction;
+    use anchor_lang::{system_program, AccountSerialize};
+    use anchor_spl::associated_token::{self, get_associated_token_address};
+    use anchor_spl::token::spl_token::{self, native_mint, processor::Processor};
+    use anchor_spl::token::spl_token::state::{Account as TokenAccount, AccountState, Mint};
+    use magic_roulette::constants::KAMINO_PROGRAM_ID;
+    use magic_roulette::instructions::finalize_game_with_loan::BorrowerLoan;
+    use magic_roulette::kamino::{
+        self, obligation_layout, reserve_layout, FRACTION_BITS, OBLIGATION_DISCRIMINATOR, RESERVE_DISCRIMINATOR,
+    };
+    use magic_roulette::state::{Game, LoanPosition, LoanStatus};
+    use magic_roulette::token::VaultWsol;
+    use solana_sha256_hasher::hashv;
+    use std::collections::HashMap;
+
+    const POT: u64 = 2_000_000_000;
+    const DEBT: u64 = 1_000_000_000;
+    const SUPPLY: u64 = 50_000_000_000;
+
+    #[derive(Clone, Default)]
+    struct Stored {
+        lamports: u64,
+        data: Vec<u8>,
+        owner: Pubkey,
+    }
+
+    // Helper: Just enough of a chain to run the vault's wSOL plans: the
+    // System and Associated Token programs emulated, SPL Token run for real,
+    // and a mock Kamino moving liquidity the way klend's repay and borrow do
+    struct MockChain {
+        accounts: HashMap<Pubkey, Stored>,
+        // Transaction signers, plus the PDAs the programs sign for
+        signers: Vec<Pubkey>,
+        game: Pubkey,
+        vault: Pubkey,
+        player: Pubkey,
+        lending_market: Pubkey,
+        lending_market_authority: Pubkey,
+        reserve: Pubkey,
+        reserve_liquidity_supply: Pubkey,
+        obligation: Pubkey,
+    }
+
+    impl MockChain {
+        fn new() -> Self {
+            let game = Game::find_address(11).0;
+            let vault = Game::find_vault_address(&game).0;
+            let player = Pubkey::new_unique();
+            let lending_market_authority = Pubkey::new_unique();
+            let reserve_liquidity_supply = Pubkey::new_unique();
+            let mut chain = Self {
+                accounts: HashMap::new(),
+                signers: vec![player, vault, lending_market_authority],
+                game,
+                vault,
+                player,
+                lending_market: Pubkey::new_unique(),
+                lending_market_authority,
+                reserve: Pubkey::new_unique(),
+                reserve_liquidity_supply,
+                obligation: Pubkey::new_unique(),
+            };
+
+            let mut mint = vec![0u8; Mint::LEN];
+            Mint { decimals: 9, is_initialized: true, ..Mint::default() }.pack_into_slice(&mut mint);
+            chain.accounts.insert(native_mint::ID, Stored { lamports: 1, data: mint, owner: spl_token::ID });
+            chain.accounts.insert(vault, Stored { lamports: POT, ..Stored::default() });
+            chain.accounts.insert(player, Stored { lamports: 10_000_000, ..Stored::default() });
+            chain.accounts.insert(
+                reserve_liquidity_supply,
+                Self::native_account(lending_market_authority, SUPPLY),
+            );
+            chain
+        }
+
+        fn native_account(owner: Pubkey, amount: u64) -> Stored {
+            let rent = Self::rent();
+            let mut data = vec![0u8; TokenAccount::LEN];
+            TokenAccount {
+                mint: native_mint::ID,
+                owner,
+                amount,
+                is_native: COption::Some(rent),
+                state: AccountState::Initialized,
+                ..TokenAccount::default()
+            }
+            .pack_into_slice(&mut data);
+            Stored { lamports: amount + rent, data, owner: spl_token::ID }
+        }
+
+        fn rent() -> u64 {
+            Rent::default().minimum_balance(TokenAccount::LEN)
+        }
+
+        fn lamports(&self, key: &Pubkey) -> u64 {
+            self.accounts.get(key).map_or(0, |account| account.lamports)
+        }
+
+        fn token_amount(&self, key: &Pubkey) -> u64 {
+            TokenAccount::unpack(&self.accounts[key].data).unwrap().amount
+        }
+
+        fn run(&mut self, instructions: &[Instruction]) -> Result<(), ProgramError> {
+            instructions.iter().try_for_each(|instruction| self.execute(instruction))
+        }
+
+        fn execute(&mut self, instruction: &Instruction) -> Result<(), ProgramError> {
+            if instruction.accounts.iter().any(|meta| meta.is_signer && !self.signers.contains(&meta.pubkey)) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            match instruction.program_id {
+                id if id == system_program::ID => self.system_transfer(instruction),
+                id if id == associated_token::ID => self.create_associated_account(instruction),
+                id if id == KAMINO_PROGRAM_ID => self.kamino(instruction),
+                id if id == spl_token::ID => self.token(instruction),
+                _ => Err(ProgramError::IncorrectProgramId),
+            }
+        }
+
+        fn transfer(&mut self, from: Pubkey, to: Pubkey, lamports: u64) -> Result<(), ProgramError> {
+            // The System program only debits accounts it owns that hold no data
+            let source = self.accounts.entry(from).or_default();
+            if source.owner != system_program::ID || !source.data.is_empty() {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            source.lamports = source.lamports.checked_sub(lamports).ok_or(ProgramError::InsufficientFunds)?;
+            self.accounts.entry(to).or_default().lamports += lamports;
+            Ok(())
+        }
+
+        fn system_transfer(&mut self, instruction: &Instruction) -> Result<(), ProgramError> {
+            assert_eq!(instruction.data[..4], [2, 0, 0, 0], "only transfers are emulated");
+            let lamports = u64::from_le_bytes(instruction.data[4..12].try_into().unwrap());
+            self.transfer(instruction.accounts[0].pubkey, instruction.accounts[1].pubkey, lamports)
+        }
+
+        fn create_associated_account(&mut self, instruction: &Instruction) -> Result<(), ProgramError> {
+            let [payer, address, wallet, mint] = [0, 1, 2, 3].map(|i| instruction.accounts[i].pubkey);
+            assert_eq!(address, get_associated_token_address(&wallet, &mint));
+            assert_eq!(mint, native_mint::ID);
+            if self.accounts.get(&address).is_some_and(|account| account.owner == spl_token::ID) {
+                return Ok(());
+            }
+
+            self.transfer(payer, address, Self::rent())?;
+            let funded = self.lamports(&address) - Self::rent();
+            self.accounts.insert(address, Self::native_account(wallet, funded));
+            Ok(())
+        }
+
+        fn kamino(&mut self, instruction: &Instruction) -> Result<(), ProgramError> {
+            let sighash = |name: &str| hashv(&[format!("global:{name}").as_bytes()]).to_bytes();
+            let amount = u64::from_le_bytes(instruction.data[8..16].try_into().unwrap());
+            let (source, destination, authority) = match &instruction.data[..8] {
+                data if data == &sighash("repay_obligation_liquidity")[..8] => (6, 5, 0),
+                data if data == &sighash("borrow_obligation_liquidity")[..8] => (6, 8, 3),
+                _ => return Err(ProgramError::InvalidInstructionData),
+            };
+            let key = |i: usize| &instruction.accounts[i].pubkey;
+            self.execute(&spl_token::instruction::transfer(
+                &spl_token::ID,
+                key(source),
+                key(destination),
+                key(authority),
+                &[],
+                amount,
+            )?)
+        }
+
+        fn token(&mut self, instruction: &Instruction) -> Result<(), ProgramError> {
+            let mut keys: Vec<Pubkey> = instruction.accounts.iter().map(|meta| meta.pubkey).collect();
+            keys.sort();
+            keys.dedup();
+            let mut stored: Vec<(Pubkey, Stored)> = keys
+                .into_iter()
+                .map(|key| (key, self.accounts.get(&key).cloned().unwrap_or_default()))
+                .collect();
+
+            let result = {
+                let infos: Vec<AccountInfo> = stored
+                    .iter_mut()
+                    .map(|(key, account)| {
+                        AccountInfo::new(key, false, true, &mut account.lamports, &mut account.data, &account.owner, false, 0)
+                    })
+                    .collect();
+                let accounts: Vec<AccountInfo> = instruction
+                    .accounts
+                    .iter()
+                    .map(|meta| {
+                        let mut info = infos.iter().find(|info| *info.key == meta.pubkey).unwrap().clone();
+                        info.is_signer = meta.is_signer;
+                        info.is_writable = meta.is_writable;
+                        info
+                    })
+                    .collect();
+                Processor::process(&instruction.program_id, &accounts, &instruction.data)
+            };
+
+            for (key, account) in stored {
+                if account.lamports == 0 && account.owner == system_program::ID {
+                    self.accounts.remove(&key);
+                } else {
+                    self.accounts.insert(key, account);
+                }
+            }
+            result
+        }
+
+        // The borrower's loan as finalize loads it, owing `debt` to the reserve
+        fn loan(&self, debt: u64) -> BorrowerLoan<'static> {
+            let mut position = Vec::new();
+            LoanPosition {
+                game: self.game,
+                game_id: 11,
+                borrower: self.player,
+                lending_market: self.lending_market,
+                reserve: self.reserve,
+                collateral_reserve: Pubkey::new_unique(),
+                obligation: self.obligation,
+                collateral_amount: 0,
+                collateral_ctokens: 0,
+                loan_amount: debt,
+                status: LoanStatus::Open,
+                bump: 0,
+                borrow_rate_bsf: 1u128 << FRACTION_BITS,
+            }
+            .try_serialize(&mut position)
+            .unwrap();
+
+            let mut reserve = vec![0u8; reserve_layout::LEN];
+            reserve[..8].copy_from_slice(&RESERVE_DISCRIMINATOR);
+            reserve[reserve_layout::LENDING_MARKET..][..32].copy_from_slice(self.lending_market.as_ref());
+            reserve[reserve_layout::CUMULATIVE_BORROW_RATE..][..16].copy_from_slice(&(1u128 << FRACTION_BITS).to_le_bytes());
+
+            let mut obligation = vec![0u8; obligation_layout::LEN];
+            obligation[..8].copy_from_slice(&OBLIGATION_DISCRIMINATOR);
+            obligation[obligation_layout::LENDING_MARKET..][..32].copy_from_slice(self.lending_market.as_ref());
+            let borrow = &mut obligation[obligation_layout::BORROWS..][..obligation_layout::BORROW_LEN];
+            borrow[obligation_layout::BORROW_RESERVE..][..32].copy_from_slice(self.reserve.as_ref());
+            borrow[obligation_layout::BORROW_CUMULATIVE_RATE..][..16].copy_from_slice(&(1u128 << FRACTION_BITS).to_le_bytes());
+            borrow[obligation_layout::BORROWED_AMOUNT_SF..][..16].copy_from_slice(&((debt as u128) << FRACTION_BITS).to_le_bytes());
+
+            let leak = |key: Pubkey, data: Vec<u8>, owner: Pubkey| {
+                AccountInfo::new(
+                    Box::leak(Box::new(key)), false, true, Box::leak(Box::new(0)),
+                    Box::leak(data.into_boxed_slice()), Box::leak(Box::new(owner)), false, 0,
+                )
+            };
+            let accounts = Box::leak(Box::new([
+                leak(LoanPosition::find_address(&self.game, &self.player).0, position, magic_roulette::ID),
+                leak(self.lending_market, Vec::new(), KAMINO_PROGRAM_ID),
+                leak(self.reserve, reserve, KAMINO_PROGRAM_ID),
+                leak(self.reserve_liquidity_supply, Vec::new(), spl_token::ID),
+                leak(self.obligation, obligation, KAMINO_PROGRAM_ID),
+            ]));
+            BorrowerLoan::load_all(&self.game, &[self.player], accounts).unwrap().remove(0)
+        }
+    }
+
+    #[test]
+    fn test_repay_plan_pays_kamino_out_of_the_vault() {
+        let mut chain = MockChain::new();
+        let wsol = VaultWsol::new(&chain.vault);
+        let mut loan = chain.loan(DEBT);
+        assert_eq!(loan.cover(POT), DEBT);
+
+        let plan = loan.repay_instructions(&wsol, &native_mint::ID, &spl_token::ID).unwrap();
+        chain.run(&plan).unwrap();
+
+        // The vault is down exactly the repayment and got the wSOL rent back
+        assert_eq!(chain.lamports(&chain.vault), POT - DEBT);
+        assert_eq!(chain.token_amount(&chain.reserve_liquidity_supply), SUPPLY + DEBT);
+        assert!(!chain.accounts.contains_key(&wsol.account));
+    }
+
+    #[test]
+    fn test_nothing_covered_repays_nothing() {
+        let chain = MockChain::new();
+        let loan = chain.loan(DEBT);
+        let plan = loan.repay_instructions(&VaultWsol::new(&chain.vault), &native_mint::ID, &spl_token::ID).unwrap();
+        assert!(plan.is_empty());
+    }
+
+    #[test]
+    fn test_kamino_cannot_repay_from_the_bare_vault() {
+        // Kamino moves SPL tokens, so the system-owned vault is no source
+        let mut chain = MockChain::new();
+        let repay = kamino::RepayObligationLiquidity {
+            owner: chain.vault,
+            obligation: chain.obligation,
+            lending_market: chain.lending_market,
+            repay_reserve: chain.reserve,
+            reserve_liquidity_mint: native_mint::ID,
+            reserve_destination_liquidity: chain.reserve_liquidity_supply,
+            user_source_liquidity: chain.vault,
+            token_program: spl_token::ID,
+        }
+        .instruction(DEBT);
+
+        assert!(chain.run(&[repay]).is_err());
+        assert_eq!(chain.lamports(&chain.vault), POT);
+        assert_eq!(chain.token_amount(&chain.reserve_liquidity_supply), SUPPLY);
+    }
+
+    #[test]
+    fn test_borrow_is_unwrapped_into_the_vault() {
+        // The steps of borrow_into_vault: open at the player's expense,
+        // borrow, unwrap into the vault and hand the rent back
+        let mut chain = MockChain::new();
+        let wsol = VaultWsol::new(&chain.vault);
+        let player_before = chain.lamports(&chain.player);
+        let borrow = kamino::BorrowObligationLiquidity {
+            owner: chain.player,
+            obligation: chain.obligation,
+            lending_market: chain.lending_market,
+            lending_market_authority: chain.lending_market_authority,
+            borrow_reserve: chain.reserve,
+            borrow_reserve_liquidity_mint: native_mint::ID,
+            reserve_source_liquidity: chain.reserve_liquidity_supply,
+            borrow_reserve_liquidity_fee_receiver: Pubkey::new_unique(),
+            user_destination_liquidity: wsol.account,
+            token_program: spl_token::ID,
+        }
+        .instruction(DEBT);
+
+        chain.run(&[wsol.open(&chain.player), borrow]).unwrap();
+        let wsol_account = chain.accounts[&wsol.account].clone();
+        let mut lamports = wsol_account.lamports;
+        let mut data = wsol_account.data.clone();
+        let info = AccountInfo::new(&wsol.account, false, true, &mut lamports, &mut data, &spl_token::ID, false, 0);
+        let (borrowed, rent) = VaultWsol::balance(&info).unwrap();
+        assert_eq!((borrowed, rent), (DEBT, MockChain::rent()));
+
+        chain.run(&[wsol.close().unwrap(), system_instruction::transfer(&chain.vault, &chain.player, rent)]).unwrap();
+
+        assert_eq!(chain.lamports(&chain.vault), POT + DEBT);
+        assert_eq!(chain.lamports(&chain.player), player_before);
+        assert_eq!(chain.token_amount(&chain.reserve_liquidity_supply), SUPPLY - DEBT);
+        assert!(!chain.accounts.contains_key(&wsol.account));
+    }
+
+    #[test]
+    fn test_balance_rejects_a_non_token_account() {
+        let (mut lamports, mut data) = (POT, Vec::new());
+        let vault = Pubkey::new_unique();
+        let info = AccountInfo::new(&vault, false, true, &mut lamports, &mut data, &system_program::ID, false, 0);
+        assert_eq!(
+            VaultWsol::balance(&info).unwrap_err(),
+            magic_roulette::errors::GameError::InvalidVaultOwner.into()
+        );
+    }
 }